@@ -0,0 +1,11 @@
+-- Notes are an append-only work log on a task, writable by its owner and assignee.
+CREATE TABLE task_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT note_body_length CHECK (char_length(body) BETWEEN 1 AND 2000)
+);
+
+CREATE INDEX idx_task_notes_task_id ON task_notes(task_id, created_at);
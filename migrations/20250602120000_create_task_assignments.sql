@@ -0,0 +1,12 @@
+-- Assignment history: one row per change of a task's assignee, including unassignments.
+CREATE TABLE task_assignments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    assigned_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    previous_assignee_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    -- NULL when the task was unassigned.
+    assignee_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    assigned_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_task_assignments_task_id ON task_assignments(task_id, assigned_at);
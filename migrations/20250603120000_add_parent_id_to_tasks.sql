@@ -0,0 +1,7 @@
+-- Subtasks: a task may have a parent task owned by the same user. Cycles and the
+-- maximum depth are enforced by the application, which knows the configured limit.
+ALTER TABLE tasks
+    ADD COLUMN parent_id UUID REFERENCES tasks(id) ON DELETE SET NULL,
+    ADD CONSTRAINT task_not_own_parent CHECK (parent_id <> id);
+
+CREATE INDEX idx_tasks_parent_id ON tasks(parent_id);
@@ -0,0 +1,15 @@
+-- Comments are a threaded discussion on a task between its owner and assignee.
+-- Replies point at another comment on the same task through parent_id.
+CREATE TABLE task_comments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    parent_id UUID REFERENCES task_comments(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    edited_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT comment_body_length CHECK (char_length(body) BETWEEN 1 AND 5000)
+);
+
+CREATE INDEX idx_task_comments_task_id ON task_comments(task_id, created_at);
+CREATE INDEX idx_task_comments_parent_id ON task_comments(parent_id);
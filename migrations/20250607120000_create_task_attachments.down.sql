@@ -0,0 +1,3 @@
+DROP TABLE task_attachments;
+DROP FUNCTION queue_storage_orphan();
+DROP TABLE storage_orphans;
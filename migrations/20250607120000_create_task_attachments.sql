@@ -0,0 +1,33 @@
+-- Attachment metadata; the contents live in the configured storage backend under storage_key.
+CREATE TABLE task_attachments (
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    uploaded_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    sha256 CHAR(64) NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_task_attachments_task_id ON task_attachments(task_id, created_at);
+CREATE INDEX idx_task_attachments_uploaded_by ON task_attachments(uploaded_by);
+
+-- Objects whose metadata row is gone, however it was deleted, are queued here
+-- until the storage backend has removed them.
+CREATE TABLE storage_orphans (
+    storage_key TEXT PRIMARY KEY,
+    queued_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE FUNCTION queue_storage_orphan() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO storage_orphans (storage_key) VALUES (OLD.storage_key) ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER task_attachments_queue_orphan
+    AFTER DELETE ON task_attachments
+    FOR EACH ROW EXECUTE FUNCTION queue_storage_orphan();
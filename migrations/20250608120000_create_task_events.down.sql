@@ -0,0 +1,2 @@
+DROP TABLE task_events;
+DROP TYPE task_event_type;
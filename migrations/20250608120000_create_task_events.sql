@@ -0,0 +1,17 @@
+-- Audit log of task mutations. Rows outlive their task: `task_id` deliberately has
+-- no foreign key, and `owner_id` records who owned the task so they can still read
+-- its history after it is deleted.
+CREATE TYPE task_event_type AS ENUM ('created', 'updated', 'deleted', 'assigned', 'unassigned');
+
+CREATE TABLE task_events (
+    id BIGSERIAL PRIMARY KEY,
+    task_id UUID NOT NULL,
+    owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    event_type task_event_type NOT NULL,
+    -- Changed fields as {"field": {"old": ..., "new": ...}}.
+    changes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_task_events_task_id ON task_events(task_id, id);
@@ -0,0 +1,12 @@
+-- Recent task events with the users they were delivered to, so event stream
+-- clients can resume after reconnecting. Unlike `task_events`, rows are pruned
+-- once they are older than the configured retention.
+CREATE TABLE task_event_log (
+    event_id BIGINT PRIMARY KEY REFERENCES task_events(id) ON DELETE CASCADE,
+    -- Users who could see the task before or after the change.
+    audience INTEGER[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_task_event_log_audience ON task_event_log USING GIN (audience);
+CREATE INDEX idx_task_event_log_created_at ON task_event_log(created_at);
@@ -0,0 +1,17 @@
+-- Postgres cannot drop an enum value, so the type is recreated without it.
+DELETE FROM task_events WHERE event_type = 'restored';
+ALTER TYPE task_event_type RENAME TO task_event_type_old;
+CREATE TYPE task_event_type AS ENUM ('created', 'updated', 'deleted', 'assigned', 'unassigned');
+ALTER TABLE task_events
+    ALTER COLUMN event_type TYPE task_event_type USING event_type::text::task_event_type;
+ALTER TABLE webhook_deliveries
+    ALTER COLUMN event_type TYPE task_event_type USING event_type::text::task_event_type;
+ALTER TABLE webhooks
+    ALTER COLUMN event_types TYPE task_event_type[]
+    USING array_remove(event_types::text[], 'restored')::task_event_type[];
+DROP TYPE task_event_type_old;
+
+-- Trashed tasks would otherwise reappear.
+DELETE FROM tasks WHERE deleted_at IS NOT NULL;
+DROP INDEX idx_tasks_deleted_at;
+ALTER TABLE tasks DROP COLUMN deleted_at;
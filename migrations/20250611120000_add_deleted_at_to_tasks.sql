@@ -0,0 +1,6 @@
+-- Soft delete: deleted tasks stay in the trash until restored or purged.
+ALTER TABLE tasks ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_tasks_deleted_at ON tasks(user_id, deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TYPE task_event_type ADD VALUE 'restored';
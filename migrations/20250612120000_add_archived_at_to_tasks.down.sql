@@ -0,0 +1,18 @@
+-- Postgres cannot drop an enum value, so the type is recreated without them.
+DELETE FROM task_events WHERE event_type IN ('archived', 'unarchived');
+ALTER TYPE task_event_type RENAME TO task_event_type_old;
+CREATE TYPE task_event_type AS ENUM ('created', 'updated', 'deleted', 'assigned', 'unassigned', 'restored');
+ALTER TABLE task_events
+    ALTER COLUMN event_type TYPE task_event_type USING event_type::text::task_event_type;
+ALTER TABLE webhook_deliveries
+    ALTER COLUMN event_type TYPE task_event_type USING event_type::text::task_event_type;
+ALTER TABLE webhooks
+    ALTER COLUMN event_types TYPE task_event_type[]
+    USING array_remove(array_remove(event_types::text[], 'archived'), 'unarchived')::task_event_type[];
+DROP TYPE task_event_type_old;
+
+DROP INDEX idx_tasks_completed_at;
+DROP TRIGGER set_tasks_completed_at ON tasks;
+DROP FUNCTION set_task_completed_at();
+ALTER TABLE tasks DROP COLUMN archived_at;
+ALTER TABLE tasks DROP COLUMN completed_at;
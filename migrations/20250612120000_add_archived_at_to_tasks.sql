@@ -0,0 +1,28 @@
+-- Archiving hides tasks from listings without deleting them. `completed_at` records
+-- when a task last reached 'done', so finished tasks can be archived automatically.
+ALTER TABLE tasks ADD COLUMN completed_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE tasks ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE tasks SET completed_at = updated_at WHERE status = 'done';
+
+CREATE FUNCTION set_task_completed_at() RETURNS trigger AS $$
+BEGIN
+    IF NEW.status <> 'done' THEN
+        NEW.completed_at = NULL;
+    ELSIF TG_OP = 'INSERT' OR OLD.status <> 'done' THEN
+        NEW.completed_at = now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_tasks_completed_at
+    BEFORE INSERT OR UPDATE OF status ON tasks
+    FOR EACH ROW EXECUTE FUNCTION set_task_completed_at();
+
+-- Finished tasks waiting to be auto-archived.
+CREATE INDEX idx_tasks_completed_at ON tasks(completed_at)
+    WHERE archived_at IS NULL AND deleted_at IS NULL;
+
+ALTER TYPE task_event_type ADD VALUE 'archived';
+ALTER TYPE task_event_type ADD VALUE 'unarchived';
@@ -0,0 +1,13 @@
+-- Status workflows, one per user, covering the tasks they own. A user without rows
+-- here is on the default workflow, in which any status can move to any other.
+CREATE TABLE workflow_transitions (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    from_status task_status NOT NULL,
+    to_status task_status NOT NULL,
+    -- The task must have an assignee.
+    requires_assignee BOOLEAN NOT NULL DEFAULT false,
+    -- Only the task's owner may make the transition.
+    requires_reviewer BOOLEAN NOT NULL DEFAULT false,
+    PRIMARY KEY (user_id, from_status, to_status),
+    CONSTRAINT workflow_transition_changes_status CHECK (from_status <> to_status)
+);
@@ -0,0 +1,41 @@
+-- Tasks in custom statuses fall back to the default status of the same category.
+DROP TRIGGER set_tasks_completed_at ON tasks;
+ALTER TABLE tasks DROP CONSTRAINT tasks_status_fkey;
+ALTER TABLE workflow_transitions DROP CONSTRAINT workflow_transitions_from_status_fkey;
+ALTER TABLE workflow_transitions DROP CONSTRAINT workflow_transitions_to_status_fkey;
+
+UPDATE tasks SET status = CASE s.category
+        WHEN 'todo' THEN 'todo' WHEN 'active' THEN 'in_progress' ELSE 'done' END
+FROM task_statuses s
+WHERE s.user_id = tasks.user_id AND s.name = tasks.status
+  AND tasks.status NOT IN ('todo', 'in_progress', 'review', 'done');
+DELETE FROM workflow_transitions
+WHERE from_status NOT IN ('todo', 'in_progress', 'review', 'done')
+   OR to_status NOT IN ('todo', 'in_progress', 'review', 'done');
+
+CREATE TYPE task_status AS ENUM ('todo', 'in_progress', 'review', 'done');
+ALTER TABLE tasks ALTER COLUMN status TYPE task_status USING status::task_status;
+ALTER TABLE tasks ALTER COLUMN status SET DEFAULT 'todo';
+ALTER TABLE workflow_transitions
+    ALTER COLUMN from_status TYPE task_status USING from_status::task_status,
+    ALTER COLUMN to_status TYPE task_status USING to_status::task_status;
+
+DROP TRIGGER create_users_default_task_statuses ON users;
+DROP FUNCTION create_default_task_statuses();
+DROP TABLE task_statuses;
+DROP TYPE status_category;
+
+CREATE OR REPLACE FUNCTION set_task_completed_at() RETURNS trigger AS $$
+BEGIN
+    IF NEW.status <> 'done' THEN
+        NEW.completed_at = NULL;
+    ELSIF TG_OP = 'INSERT' OR OLD.status <> 'done' THEN
+        NEW.completed_at = now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_tasks_completed_at
+    BEFORE INSERT OR UPDATE OF status ON tasks
+    FOR EACH ROW EXECUTE FUNCTION set_task_completed_at();
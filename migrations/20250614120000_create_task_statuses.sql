@@ -0,0 +1,77 @@
+-- Task statuses become per-user rows instead of the fixed `task_status` enum. Each
+-- status has a category, which is what the rest of the schema relies on: tasks in a
+-- 'done' status count as completed, and 'active' and 'done' statuses need the task's
+-- blockers to be finished.
+CREATE TYPE status_category AS ENUM ('todo', 'active', 'done');
+
+CREATE TABLE task_statuses (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(50) NOT NULL,
+    category status_category NOT NULL,
+    sort_order INTEGER NOT NULL,
+    PRIMARY KEY (user_id, name)
+);
+
+-- Every user starts with the four statuses the enum used to have.
+CREATE FUNCTION create_default_task_statuses() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO task_statuses (user_id, name, category, sort_order) VALUES
+        (NEW.id, 'todo', 'todo', 0),
+        (NEW.id, 'in_progress', 'active', 1),
+        (NEW.id, 'review', 'active', 2),
+        (NEW.id, 'done', 'done', 3);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER create_users_default_task_statuses
+    AFTER INSERT ON users
+    FOR EACH ROW EXECUTE FUNCTION create_default_task_statuses();
+
+INSERT INTO task_statuses (user_id, name, category, sort_order)
+SELECT users.id, defaults.name, defaults.category::status_category, defaults.sort_order
+FROM users CROSS JOIN (VALUES
+    ('todo', 'todo', 0),
+    ('in_progress', 'active', 1),
+    ('review', 'active', 2),
+    ('done', 'done', 3)
+) AS defaults (name, category, sort_order);
+
+-- Postgres cannot change the type of a column a trigger is defined on.
+DROP TRIGGER set_tasks_completed_at ON tasks;
+
+ALTER TABLE tasks ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE tasks ALTER COLUMN status TYPE VARCHAR(50) USING status::text;
+-- Renaming a status renames it on every task.
+ALTER TABLE tasks ADD CONSTRAINT tasks_status_fkey
+    FOREIGN KEY (user_id, status) REFERENCES task_statuses (user_id, name) ON UPDATE CASCADE;
+
+ALTER TABLE workflow_transitions
+    ALTER COLUMN from_status TYPE VARCHAR(50) USING from_status::text,
+    ALTER COLUMN to_status TYPE VARCHAR(50) USING to_status::text;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_from_status_fkey
+    FOREIGN KEY (user_id, from_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE CASCADE;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_to_status_fkey
+    FOREIGN KEY (user_id, to_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE CASCADE;
+
+DROP TYPE task_status;
+
+-- `completed_at` now follows the status category. A task that stays completed, for
+-- example because its status was renamed, keeps its original `completed_at`.
+CREATE OR REPLACE FUNCTION set_task_completed_at() RETURNS trigger AS $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM task_statuses
+                   WHERE user_id = NEW.user_id AND name = NEW.status AND category = 'done') THEN
+        NEW.completed_at = NULL;
+    ELSIF TG_OP = 'INSERT' OR OLD.completed_at IS NULL THEN
+        NEW.completed_at = now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_tasks_completed_at
+    BEFORE INSERT OR UPDATE OF status ON tasks
+    FOR EACH ROW EXECUTE FUNCTION set_task_completed_at();
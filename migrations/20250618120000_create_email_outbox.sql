@@ -0,0 +1,13 @@
+-- Emails waiting to be sent by the mail worker. Rows are deleted once the email is
+-- sent, or given up on, so the links they carry do not outlive their delivery.
+CREATE TABLE email_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_email_outbox_due ON email_outbox(next_attempt_at);
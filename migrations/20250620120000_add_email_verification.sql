@@ -0,0 +1,19 @@
+-- Email verification. Accounts created before verification existed are treated as
+-- verified, so restricting unverified accounts does not lock out existing users.
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE;
+UPDATE users SET email_verified_at = COALESCE(created_at, now());
+
+-- Single-use email verification tokens, stored as SHA-256 hashes like password
+-- reset tokens.
+CREATE TABLE email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Hex SHA-256 of the token; the token itself only appears in the email.
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Set when the token is used, or when another of the user's tokens is.
+    used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_email_verification_tokens_user_id ON email_verification_tokens(user_id);
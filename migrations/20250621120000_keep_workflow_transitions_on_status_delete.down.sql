@@ -0,0 +1,9 @@
+ALTER TABLE workflow_transitions
+    DROP CONSTRAINT workflow_transitions_from_status_fkey,
+    DROP CONSTRAINT workflow_transitions_to_status_fkey;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_from_status_fkey
+    FOREIGN KEY (user_id, from_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE CASCADE;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_to_status_fkey
+    FOREIGN KEY (user_id, to_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE CASCADE;
@@ -0,0 +1,13 @@
+-- Deleting a status no longer deletes the workflow transitions using it, which
+-- could leave a workflow empty and silently fall back to allowing every change.
+-- NO ACTION rather than RESTRICT, so deleting a user, which cascades to both
+-- tables, is only checked once both cascades have run.
+ALTER TABLE workflow_transitions
+    DROP CONSTRAINT workflow_transitions_from_status_fkey,
+    DROP CONSTRAINT workflow_transitions_to_status_fkey;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_from_status_fkey
+    FOREIGN KEY (user_id, from_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE NO ACTION;
+ALTER TABLE workflow_transitions ADD CONSTRAINT workflow_transitions_to_status_fkey
+    FOREIGN KEY (user_id, to_status) REFERENCES task_statuses (user_id, name)
+    ON UPDATE CASCADE ON DELETE NO ACTION;
@@ -0,0 +1,86 @@
+//! # Task Archiving
+//!
+//! Archived tasks keep their status but are left out of `GET /api/tasks` unless
+//! `include_archived=true`. Owners archive and unarchive tasks through
+//! `routes::archive`, and `run_auto_archiver` archives tasks that have been in a
+//! `done` status for `Config::auto_archive_after_days`.
+
+use crate::{
+    error::AppError,
+    models::{Task, TaskEventType},
+    routes::{activity::record_event, tasks::TASK_COLUMNS},
+};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// How often `run_auto_archiver` looks for tasks to archive.
+const AUTO_ARCHIVE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Most tasks archived in one transaction.
+const AUTO_ARCHIVE_BATCH_SIZE: i64 = 100;
+
+/// Archives tasks that were completed more than `after_days` ago and returns how
+/// many were archived.
+///
+/// Each archived task gets an `archived` event with no actor. Tasks their owner
+/// unarchived after they were completed are left alone until they are completed again.
+pub async fn archive_completed_tasks(pool: &PgPool, after_days: u32) -> Result<u64, AppError> {
+    let mut archived = 0;
+    loop {
+        let mut tx = pool.begin().await?;
+        let due = sqlx::query_as::<_, Task>(&format!(
+            "SELECT {} FROM tasks
+             WHERE archived_at IS NULL AND deleted_at IS NULL
+               AND EXISTS (SELECT 1 FROM task_statuses s
+                           WHERE s.user_id = tasks.user_id AND s.name = tasks.status
+                             AND s.category = 'done')
+               AND completed_at < now() - make_interval(days => $1)
+               AND NOT EXISTS (SELECT 1 FROM task_events e
+                               WHERE e.task_id = tasks.id AND e.event_type = 'unarchived'
+                                 AND e.created_at > tasks.completed_at)
+             ORDER BY completed_at LIMIT $2
+             FOR UPDATE SKIP LOCKED",
+            TASK_COLUMNS
+        ))
+        .bind(after_days as i32)
+        .bind(AUTO_ARCHIVE_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+        if due.is_empty() {
+            return Ok(archived);
+        }
+
+        for before in &due {
+            let after = sqlx::query_as::<_, Task>(&format!(
+                "UPDATE tasks SET archived_at = now() WHERE id = $1 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(before.id)
+            .fetch_one(&mut *tx)
+            .await?;
+            record_event(
+                &mut tx,
+                None,
+                TaskEventType::Archived,
+                Some(before),
+                Some(&after),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        archived += due.len() as u64;
+    }
+}
+
+/// Runs `archive_completed_tasks` every `AUTO_ARCHIVE_INTERVAL` until the server stops.
+pub async fn run_auto_archiver(pool: PgPool, after_days: u32) {
+    let mut interval = actix_web::rt::time::interval(AUTO_ARCHIVE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match archive_completed_tasks(&pool, after_days).await {
+            Ok(0) => {}
+            Ok(archived) => log::info!("Archived {} completed tasks", archived),
+            Err(e) => log::error!("Auto-archiving failed: {}", e),
+        }
+    }
+}
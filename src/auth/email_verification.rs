@@ -0,0 +1,62 @@
+use crate::{
+    auth::refresh::{generate_refresh_token, hash_refresh_token},
+    error::AppError,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor};
+
+/// Issues an email verification token for `user_id`, valid until `expires_at`, and
+/// returns it.
+///
+/// Like password reset tokens, only the hash is stored, and the token itself goes
+/// into the verification email.
+pub async fn issue_email_verification_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    expires_at: DateTime<Utc>,
+) -> Result<String, AppError> {
+    let token = generate_refresh_token();
+    sqlx::query(
+        "INSERT INTO email_verification_tokens (user_id, token_hash, expires_at)
+         VALUES ($1, $2, $3)",
+    )
+    .bind(user_id)
+    .bind(hash_refresh_token(&token))
+    .bind(expires_at)
+    .execute(executor)
+    .await?;
+    Ok(token)
+}
+
+/// Uses up the email verification token `token`, marks the email of the user it
+/// was issued to as verified, and returns the user's ID. Returns `None` if the
+/// token is unknown, expired or already used.
+///
+/// The user's other outstanding verification tokens are used up as well.
+pub async fn verify_email(conn: &mut PgConnection, token: &str) -> Result<Option<i32>, AppError> {
+    let user_id: Option<i32> = sqlx::query_scalar(
+        "UPDATE email_verification_tokens SET used_at = now()
+         WHERE token_hash = $1 AND used_at IS NULL AND expires_at > now()
+         RETURNING user_id",
+    )
+    .bind(hash_refresh_token(token))
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(user_id) = user_id {
+        sqlx::query(
+            "UPDATE email_verification_tokens SET used_at = now()
+             WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            "UPDATE users SET email_verified_at = COALESCE(email_verified_at, now()) WHERE id = $1",
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(user_id)
+}
@@ -2,6 +2,7 @@ use actix_web::dev::Payload;
 use actix_web::{Error as ActixError, FromRequest, HttpMessage, HttpRequest};
 use std::future::{ready, Ready};
 
+use crate::auth::token::Claims;
 use crate::error::AppError;
 
 /// Extracts the authenticated user's ID from request extensions.
@@ -41,6 +42,32 @@ impl FromRequest for AuthenticatedUserId {
     }
 }
 
+/// Extracts the claims of the authenticated request's token from request extensions.
+///
+/// Like `AuthenticatedUserId`, this relies on `AuthMiddleware`, and returns an
+/// `AppError::Unauthorized` error if the claims are missing. Handlers that act on
+/// the token itself, such as logout, use it to learn the token's `jti`.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedClaims(pub Claims);
+
+impl FromRequest for AuthenticatedClaims {
+    type Error = ActixError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        match req.extensions().get::<Claims>().cloned() {
+            Some(claims) => ready(Ok(AuthenticatedClaims(claims))),
+            None => {
+                let err = AppError::Unauthorized(
+                    "Token claims not found in request. Ensure AuthMiddleware is active."
+                        .to_string(),
+                );
+                ready(Err(err.into()))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,24 +1,31 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    web, Error, HttpMessage,
 };
 use futures::future::{ready, LocalBoxFuture, Ready};
+use sqlx::PgPool;
+use std::rc::Rc;
 
-use crate::auth::token::verify_token;
+use crate::auth::{revocation::TOKEN_REVOCATIONS, token::verify_token};
+use crate::error::AppError;
 
 /// Authentication middleware factory.
 ///
 /// This middleware is responsible for checking the `Authorization` header
-/// for a Bearer token and verifying it. If the token is valid, the claims
+/// for a Bearer token, verifying it and checking that it has not been revoked
+/// (see `TokenRevocations`). If the token is valid, the user ID and the claims
 /// are inserted into the request extensions for later use by handlers.
 ///
-/// Certain paths like `/health`, `/api/auth/login`, and `/api/auth/register`
-/// are excluded from authentication checks.
+/// Certain paths like `/health`, `/api/auth/login`, `/api/auth/register`,
+/// `/api/auth/refresh`, `/api/auth/password/*` and `/api/auth/email/verify` are excluded
+/// from authentication checks. `/api/ws` is excluded too, because
+/// browsers cannot send the header on a WebSocket handshake; that handler
+/// verifies the token itself.
 pub struct AuthMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -29,7 +36,9 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthMiddlewareService { service }))
+        ready(Ok(AuthMiddlewareService {
+            service: Rc::new(service),
+        }))
     }
 }
 
@@ -38,13 +47,14 @@ where
 /// This service is created by `AuthMiddleware` and performs the actual
 /// authentication logic for each request before passing it to the next service.
 pub struct AuthMiddlewareService<S> {
-    /// The next service in the Actix Web processing chain.
-    service: S,
+    /// The next service in the Actix Web processing chain, shared with the futures
+    /// that run after the revocation check.
+    service: Rc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -60,6 +70,10 @@ where
         if path == "/health"
             || path.starts_with("/api/auth/login")
             || path.starts_with("/api/auth/register")
+            || path == "/api/auth/refresh"
+            || path.starts_with("/api/auth/password/")
+            || path == "/api/auth/email/verify"
+            || path == "/api/ws"
         {
             let fut = self.service.call(req);
             return Box::pin(fut);
@@ -71,26 +85,24 @@ where
             .and_then(|value| value.to_str().ok())
             .and_then(|value| value.strip_prefix("Bearer "));
 
-        match auth_header {
-            Some(token) => {
-                match verify_token(token) {
-                    // verify_token returns Result<Claims, AppError>
-                    Ok(claims) => {
-                        let user_id_to_insert = claims.sub;
-                        req.extensions_mut().insert(user_id_to_insert);
-                        let fut = self.service.call(req);
-                        Box::pin(fut)
-                    }
-                    Err(app_err) => {
-                        // app_err is AppError
-                        Box::pin(async move { Err(app_err.into()) }) // Convert AppError to actix_web::Error
-                    }
-                }
-            }
-            None => {
-                let app_err = crate::error::AppError::Unauthorized("Missing token".into());
-                Box::pin(async move { Err(app_err.into()) }) // Convert AppError to actix_web::Error
-            }
-        }
+        let claims = match auth_header {
+            // verify_token returns Result<Claims, AppError>
+            Some(token) => verify_token(token),
+            None => Err(AppError::Unauthorized("Missing token".into())),
+        };
+        let pool = req.app_data::<web::Data<PgPool>>().cloned();
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let claims = claims?;
+            let pool = pool.ok_or_else(|| {
+                AppError::InternalServerError("Database pool not configured".into())
+            })?;
+            TOKEN_REVOCATIONS.check(&pool, &claims).await?;
+
+            req.extensions_mut().insert(claims.sub);
+            req.extensions_mut().insert(claims);
+            service.call(req).await
+        })
     }
 }
@@ -4,14 +4,23 @@
 //! - User registration and login request structures.
 //! - Password hashing and verification.
 //! - JWT (JSON Web Token) generation and verification.
+//! - Rotating refresh tokens, stored hashed, with reuse detection.
+//! - Server-side revocation of access tokens, for logout.
+//! - Sessions, recording where users are signed in.
+//! - Single-use password reset and email verification tokens, stored hashed.
 //! - Middleware for protecting routes that require authentication.
 //! - Custom extractors for accessing authenticated user information.
 //!
 //! It re-exports key functionalities for use elsewhere in the application.
 
+pub mod email_verification;
 pub mod extractors;
 pub mod middleware;
 pub mod password;
+pub mod password_reset;
+pub mod refresh;
+pub mod revocation;
+pub mod session;
 pub mod token;
 
 use lazy_static::lazy_static;
@@ -19,8 +28,12 @@ use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 // Re-export necessary items
+pub use email_verification::{issue_email_verification_token, verify_email};
 pub use middleware::AuthMiddleware;
 pub use password::{hash_password, verify_password};
+pub use password_reset::{issue_password_reset_token, redeem_password_reset_token};
+pub use refresh::{issue_refresh_token, rotate_refresh_token};
+pub use revocation::{TokenRevocations, TOKEN_REVOCATIONS};
 pub use token::{generate_token, verify_token, Claims};
 
 lazy_static! {
@@ -64,14 +77,55 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
-/// Response structure after successful authentication (login or registration).
-/// Contains the JWT access token and the ID of the authenticated user.
+/// Represents the payload for a token refresh request.
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    /// The refresh token from the last login or refresh. It can only be used once.
+    pub refresh_token: String,
+}
+
+/// Represents the payload for a password reset request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    /// Email address of the account to recover.
+    /// Must be a valid email format.
+    #[validate(email)]
+    pub email: String,
+}
+
+/// Represents the payload for choosing a new password with a reset token.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    /// The token from the password reset email. It can only be used once.
+    pub token: String,
+    /// The new password.
+    /// Must be at least 6 characters long.
+    #[validate(length(min = 6))]
+    pub password: String,
+}
+
+/// Represents the payload for verifying an email address.
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    /// The token from the verification email. It can only be used once.
+    pub token: String,
+}
+
+/// Response structure after successful authentication (login, registration or refresh).
+/// Contains the JWT access token, the refresh token that renews it, and the ID of the
+/// authenticated user.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthResponse {
-    /// The JWT (JSON Web Token) for session authentication.
+    /// The short-lived JWT (JSON Web Token) access token for session authentication.
     pub token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+    /// The opaque token to send to `POST /api/auth/refresh` for a new pair of tokens.
+    pub refresh_token: String,
     /// The unique identifier of the authenticated user.
     pub user_id: i32,
+    /// Whether the user has verified their email address.
+    pub email_verified: bool,
 }
 
 #[cfg(test)]
@@ -123,4 +177,20 @@ mod tests {
         };
         assert!(short_username_register.validate().is_err());
     }
+
+    #[test]
+    fn test_password_reset_request_validation() {
+        let forgot = |email: &str| ForgotPasswordRequest {
+            email: email.to_string(),
+        };
+        assert!(forgot("test@example.com").validate().is_ok());
+        assert!(forgot("testexample.com").validate().is_err());
+
+        let reset = |password: &str| ResetPasswordRequest {
+            token: "token".to_string(),
+            password: password.to_string(),
+        };
+        assert!(reset("password123").validate().is_ok());
+        assert!(reset("123").validate().is_err());
+    }
 }
@@ -0,0 +1,58 @@
+use crate::{
+    auth::refresh::{generate_refresh_token, hash_refresh_token},
+    error::AppError,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor};
+
+/// Issues a password reset token for `user_id`, valid until `expires_at`, and
+/// returns it.
+///
+/// Reset tokens are random values generated and hashed like refresh tokens; only
+/// the hash is stored, and the token itself goes into the reset email.
+pub async fn issue_password_reset_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    expires_at: DateTime<Utc>,
+) -> Result<String, AppError> {
+    let token = generate_refresh_token();
+    sqlx::query(
+        "INSERT INTO password_reset_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+    )
+    .bind(user_id)
+    .bind(hash_refresh_token(&token))
+    .bind(expires_at)
+    .execute(executor)
+    .await?;
+    Ok(token)
+}
+
+/// Uses up the password reset token `token` and returns the ID of the user it was
+/// issued to, or `None` if it is unknown, expired or already used.
+///
+/// The user's other outstanding reset tokens are used up as well, so each reset
+/// email stops working once any of them has been followed.
+pub async fn redeem_password_reset_token(
+    conn: &mut PgConnection,
+    token: &str,
+) -> Result<Option<i32>, AppError> {
+    let user_id: Option<i32> = sqlx::query_scalar(
+        "UPDATE password_reset_tokens SET used_at = now()
+         WHERE token_hash = $1 AND used_at IS NULL AND expires_at > now()
+         RETURNING user_id",
+    )
+    .bind(hash_refresh_token(token))
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(user_id) = user_id {
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used_at = now()
+             WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(user_id)
+}
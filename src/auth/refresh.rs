@@ -0,0 +1,159 @@
+use crate::{
+    auth::{revocation::TOKEN_REVOCATIONS, session::revoke_session},
+    error::AppError,
+};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// The columns of a `refresh_tokens` row that rotation looks at.
+#[derive(Debug, FromRow)]
+struct RefreshTokenRow {
+    id: Uuid,
+    user_id: i32,
+    family_id: Uuid,
+    expires_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Generates a new opaque refresh token: 64 hex characters from two random UUIDs.
+pub fn generate_refresh_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Returns the hex SHA-256 of `token`, which is all `refresh_tokens` stores.
+///
+/// Refresh tokens are random rather than chosen by users, so an unsalted hash is
+/// enough to keep a copy of the table from yielding usable tokens.
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Issues a refresh token for `user_id` in the session `session_id`, valid until
+/// `expires_at`, and returns it.
+///
+/// Each session is one token family: `refresh_tokens.family_id` is the session's ID.
+pub async fn issue_refresh_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    session_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<String, AppError> {
+    let token = generate_refresh_token();
+    sqlx::query(
+        "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .bind(hash_refresh_token(&token))
+    .bind(expires_at)
+    .execute(executor)
+    .await?;
+    Ok(token)
+}
+
+/// A refresh token exchanged for its successor by `rotate_refresh_token`.
+#[derive(Debug)]
+pub struct RotatedRefreshToken {
+    /// The user the token belongs to.
+    pub user_id: i32,
+    /// The session, and token family, the token belongs to.
+    pub session_id: Uuid,
+    /// The new refresh token.
+    pub token: String,
+}
+
+/// Exchanges `token` for a new refresh token in the same session, valid for `ttl`,
+/// and extends the session to match.
+///
+/// Unknown, revoked and expired tokens are rejected with `AppError::Unauthorized`.
+/// A token that was already exchanged must have been copied, so presenting it again
+/// revokes its whole session, including the latest token held by the legitimate
+/// client, before it is rejected.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    token: &str,
+    ttl: Duration,
+) -> Result<RotatedRefreshToken, AppError> {
+    let invalid = || AppError::Unauthorized("Invalid refresh token".into());
+    let token_hash = hash_refresh_token(token);
+
+    // Locking the session, then the token, makes concurrent refreshes with the same
+    // token take turns, so only the first one succeeds. Session revocation locks in
+    // the same order.
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "SELECT s.id FROM sessions s JOIN refresh_tokens r ON r.family_id = s.id
+         WHERE r.token_hash = $1 FOR UPDATE OF s",
+    )
+    .bind(&token_hash)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(invalid)?;
+    let row = sqlx::query_as::<_, RefreshTokenRow>(
+        "SELECT id, user_id, family_id, expires_at, used_at, revoked_at
+         FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE",
+    )
+    .bind(&token_hash)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if row.revoked_at.is_some() {
+        return Err(invalid());
+    }
+    if row.used_at.is_some() {
+        revoke_session(&mut tx, row.user_id, row.family_id).await?;
+        tx.commit().await?;
+        TOKEN_REVOCATIONS.session_revoked(row.family_id);
+        log::warn!(
+            "Refresh token reused for user {}; revoked session {}",
+            row.user_id,
+            row.family_id
+        );
+        return Err(AppError::Unauthorized(
+            "Refresh token has already been used; please log in again".into(),
+        ));
+    }
+    if row.expires_at <= Utc::now() {
+        return Err(invalid());
+    }
+
+    let expires_at = Utc::now() + ttl;
+    sqlx::query("UPDATE refresh_tokens SET used_at = now() WHERE id = $1")
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE sessions SET last_seen_at = now(), expires_at = $2 WHERE id = $1")
+        .bind(row.family_id)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+    let new_token = issue_refresh_token(&mut *tx, row.user_id, row.family_id, expires_at).await?;
+    tx.commit().await?;
+
+    Ok(RotatedRefreshToken {
+        user_id: row.user_id,
+        session_id: row.family_id,
+        token: new_token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_tokens_are_random_and_hashed() {
+        let token = generate_refresh_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_refresh_token());
+
+        let hash = hash_refresh_token(&token);
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, token);
+        assert_eq!(hash, hash_refresh_token(&token));
+    }
+}
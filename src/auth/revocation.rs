@@ -0,0 +1,249 @@
+use crate::{
+    auth::{
+        session::{revoke_all_sessions, revoke_session},
+        token::Claims,
+    },
+    error::AppError,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use sqlx::{PgConnection, PgPool};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+/// How long the outcome of a revocation check is cached. A token revoked through
+/// another server instance keeps working on this one for at most this long.
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Number of cached checks above which stale ones are pruned.
+const CACHE_PRUNE_THRESHOLD: usize = 10_000;
+
+lazy_static! {
+    /// The revocation store shared by every worker of this process.
+    pub static ref TOKEN_REVOCATIONS: TokenRevocations = TokenRevocations::new(CACHE_TTL);
+}
+
+/// The outcome of a revocation check, cached until `expires`.
+#[derive(Debug, Clone, Copy)]
+struct CachedCheck {
+    user_id: i32,
+    session_id: Uuid,
+    revoked: bool,
+    expires: Instant,
+}
+
+/// Revocation store for access tokens.
+///
+/// Postgres is the source of truth: `revoked_tokens` lists the `jti` of every token
+/// revoked by a logout, `sessions.revoked_at` revokes every token of a session, and
+/// `users.token_generation` outdates every token issued before the user logged out of
+/// all sessions. The outcome of each check is cached in memory for `ttl`, so
+/// `AuthMiddleware` does not query the database on every request. Revocations made
+/// through this server instance update the cache at once.
+pub struct TokenRevocations {
+    ttl: Duration,
+    cache: Mutex<HashMap<Uuid, CachedCheck>>,
+}
+
+impl TokenRevocations {
+    /// Creates a store that caches checks for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        TokenRevocations {
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached outcome for the token `jti`, if it is still fresh.
+    fn cached(&self, jti: Uuid) -> Option<bool> {
+        let cache = self.cache.lock().expect("revocation cache lock poisoned");
+        cache
+            .get(&jti)
+            .filter(|check| check.expires > Instant::now())
+            .map(|check| check.revoked)
+    }
+
+    /// Caches whether the token `claims` belongs to is revoked.
+    fn remember(&self, claims: &Claims, revoked: bool) {
+        let now = Instant::now();
+        let mut cache = self.cache.lock().expect("revocation cache lock poisoned");
+        if cache.len() >= CACHE_PRUNE_THRESHOLD {
+            cache.retain(|_, check| check.expires > now);
+        }
+        cache.insert(
+            claims.jti,
+            CachedCheck {
+                user_id: claims.sub,
+                session_id: claims.sid,
+                revoked,
+                expires: now + self.ttl,
+            },
+        );
+    }
+
+    /// Marks the cached checks of `filter`'s tokens as revoked.
+    fn mark_revoked(&self, filter: impl Fn(&CachedCheck) -> bool) {
+        let mut cache = self.cache.lock().expect("revocation cache lock poisoned");
+        for check in cache.values_mut().filter(|check| filter(check)) {
+            check.revoked = true;
+        }
+    }
+
+    /// Checks that the token `claims` belongs to has not been revoked.
+    ///
+    /// Tokens that were logged out, whose session was revoked, that predate the
+    /// user's current token generation, or whose user no longer exists are rejected
+    /// with `AppError::Unauthorized`. Checks that reach the database also record the
+    /// session as seen.
+    pub async fn check(&self, pool: &PgPool, claims: &Claims) -> Result<(), AppError> {
+        let revoked = match self.cached(claims.jti) {
+            Some(revoked) => revoked,
+            None => {
+                let row: Option<(i32, bool, bool)> = sqlx::query_as(
+                    "WITH seen AS (
+                         UPDATE sessions SET last_seen_at = now()
+                         WHERE id = $3 AND user_id = $1 AND revoked_at IS NULL
+                         RETURNING id
+                     )
+                     SELECT token_generation,
+                            EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $2),
+                            EXISTS (SELECT 1 FROM seen)
+                     FROM users WHERE id = $1",
+                )
+                .bind(claims.sub)
+                .bind(claims.jti)
+                .bind(claims.sid)
+                .fetch_optional(pool)
+                .await?;
+                let revoked = !row.is_some_and(|(generation, logged_out, session_active)| {
+                    !logged_out && session_active && generation == claims.generation
+                });
+                self.remember(claims, revoked);
+                revoked
+            }
+        };
+
+        if revoked {
+            return Err(AppError::Unauthorized("Token has been revoked".into()));
+        }
+        Ok(())
+    }
+
+    /// Revokes the token `claims` belongs to until it expires.
+    pub async fn revoke(&self, pool: &PgPool, claims: &Claims) -> Result<(), AppError> {
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp as i64, 0)
+            .ok_or_else(|| AppError::InternalServerError("Invalid token expiry".into()))?;
+
+        // Rows are only needed while their token could still be presented.
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < now()")
+            .execute(pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO revoked_tokens (jti, user_id, expires_at) VALUES ($1, $2, $3)
+             ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(claims.jti)
+        .bind(claims.sub)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        self.remember(claims, true);
+        Ok(())
+    }
+
+    /// Revokes `user_id`'s session `session_id`, with all of its access and refresh
+    /// tokens.
+    ///
+    /// Returns `false` if the user has no such session, or it was already revoked.
+    pub async fn revoke_session(
+        &self,
+        pool: &PgPool,
+        user_id: i32,
+        session_id: Uuid,
+    ) -> Result<bool, AppError> {
+        let mut tx = pool.begin().await?;
+        let revoked = revoke_session(&mut tx, user_id, session_id).await?;
+        tx.commit().await?;
+
+        if revoked {
+            self.session_revoked(session_id);
+        }
+        Ok(revoked)
+    }
+
+    /// Updates the cache after the session `session_id` was revoked in the database.
+    pub(crate) fn session_revoked(&self, session_id: Uuid) {
+        self.mark_revoked(|check| check.session_id == session_id);
+    }
+
+    /// Revokes every session, access token and refresh token of `user_id` so far, by
+    /// bumping their token generation.
+    pub async fn revoke_all(&self, pool: &PgPool, user_id: i32) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+        revoke_all_tokens(&mut tx, user_id).await?;
+        tx.commit().await?;
+
+        self.user_revoked(user_id);
+        Ok(())
+    }
+
+    /// Updates the cache after every token of `user_id` was revoked in the database.
+    pub(crate) fn user_revoked(&self, user_id: i32) {
+        self.mark_revoked(|check| check.user_id == user_id);
+    }
+}
+
+/// Revokes every session, access token and refresh token of `user_id` so far in the
+/// database, as part of a larger change. Call `TokenRevocations::user_revoked` once
+/// it commits.
+pub(crate) async fn revoke_all_tokens(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE users SET token_generation = token_generation + 1 WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    revoke_all_sessions(conn, user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(user_id: i32) -> Claims {
+        Claims {
+            sub: user_id,
+            exp: 0,
+            jti: Uuid::new_v4(),
+            sid: Uuid::new_v4(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn test_revocation_cache_expires() {
+        let store = TokenRevocations::new(Duration::from_millis(50));
+        let (valid, revoked) = (claims(1), claims(1));
+        store.remember(&valid, false);
+        store.remember(&revoked, true);
+
+        assert_eq!(store.cached(valid.jti), Some(false));
+        assert_eq!(store.cached(revoked.jti), Some(true));
+        assert_eq!(store.cached(Uuid::new_v4()), None);
+
+        let other_session = claims(1);
+        store.remember(&other_session, false);
+        store.session_revoked(valid.sid);
+        assert_eq!(store.cached(valid.jti), Some(true));
+        assert_eq!(store.cached(other_session.jti), Some(false));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(store.cached(valid.jti), None);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::AppError;
+use actix_web::{http::header, HttpRequest};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Longest `User-Agent` recorded for a session, in characters.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// What is recorded about the client that starts a session.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    /// The `User-Agent` header, truncated to `MAX_USER_AGENT_LENGTH` characters.
+    pub user_agent: Option<String>,
+    /// The client's IP address: the connection's peer, or the address named by
+    /// `Forwarded` or `X-Forwarded-For` when the peer is a trusted proxy.
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    /// Reads the client details of `req`.
+    ///
+    /// Forwarding headers are only honoured on requests from `trusted_proxies`
+    /// (`Config::trusted_proxies`); anyone else could put any address in them.
+    pub fn from_request(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Self {
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        ClientInfo {
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.chars().take(MAX_USER_AGENT_LENGTH).collect()),
+            ip_address: match peer {
+                Some(ip) if trusted_proxies.contains(&ip) => req
+                    .connection_info()
+                    .realip_remote_addr()
+                    .map(str::to_string),
+                peer => peer.map(|ip| ip.to_string()),
+            },
+        }
+    }
+}
+
+/// Records a new session for `user_id` that ends at `expires_at` unless it is
+/// refreshed, and returns its ID.
+pub async fn create_session<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    client: &ClientInfo,
+    expires_at: DateTime<Utc>,
+) -> Result<Uuid, AppError> {
+    let (session_id,): (Uuid,) = sqlx::query_as(
+        "INSERT INTO sessions (user_id, user_agent, ip_address, expires_at)
+         VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(&client.user_agent)
+    .bind(&client.ip_address)
+    .bind(expires_at)
+    .fetch_one(executor)
+    .await?;
+    Ok(session_id)
+}
+
+/// Revokes `user_id`'s session `session_id` along with its refresh tokens.
+///
+/// Returns `false` if the user has no such session, or it was already revoked.
+/// The session row is locked first, as `rotate_refresh_token` does, so a refresh
+/// racing with the revocation cannot leave a live refresh token behind.
+pub async fn revoke_session(
+    conn: &mut PgConnection,
+    user_id: i32,
+    session_id: Uuid,
+) -> Result<bool, AppError> {
+    let revoked = sqlx::query(
+        "UPDATE sessions SET revoked_at = now()
+         WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    if revoked == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = now()
+         WHERE family_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(true)
+}
+
+/// Revokes every session of `user_id` along with their refresh tokens.
+pub async fn revoke_all_sessions(conn: &mut PgConnection, user_id: i32) -> Result<(), AppError> {
+    sqlx::query("UPDATE sessions SET revoked_at = now() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = now() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_client_info_from_request() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let forwarded = |peer: &str| {
+            TestRequest::default()
+                .peer_addr(format!("{}:40000", peer).parse().unwrap())
+                .insert_header((header::USER_AGENT, "a".repeat(600)))
+                .insert_header(("X-Forwarded-For", "203.0.113.7"))
+                .to_http_request()
+        };
+
+        // Only a trusted proxy may name the client.
+        let client = ClientInfo::from_request(&forwarded("10.0.0.2"), &[proxy]);
+        assert_eq!(client.user_agent, Some("a".repeat(MAX_USER_AGENT_LENGTH)));
+        assert_eq!(client.ip_address.as_deref(), Some("203.0.113.7"));
+
+        let client = ClientInfo::from_request(&forwarded("198.51.100.9"), &[proxy]);
+        assert_eq!(client.ip_address.as_deref(), Some("198.51.100.9"));
+        let client = ClientInfo::from_request(&forwarded("10.0.0.2"), &[]);
+        assert_eq!(client.ip_address.as_deref(), Some("10.0.0.2"));
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(
+            ClientInfo::from_request(&req, &[proxy]),
+            ClientInfo::default()
+        );
+    }
+}
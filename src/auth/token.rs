@@ -1,6 +1,7 @@
 use crate::error::AppError;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 /// Represents the claims encoded within a JWT (JSON Web Token).
 #[derive(Debug, Serialize, Deserialize, Clone)] // Added Clone for potential use in middleware
@@ -9,28 +10,47 @@ pub struct Claims {
     pub sub: i32, // user id
     /// Expiration timestamp (seconds since epoch) for the token.
     pub exp: usize,
+    /// Unique identifier of the token, by which it can be revoked before it expires.
+    pub jti: Uuid,
+    /// The session the token was issued for. Revoking the session revokes the token.
+    pub sid: Uuid,
+    /// The user's token generation when the token was issued. Logging out of all
+    /// sessions bumps the generation, which revokes every older token.
+    pub generation: i32,
 }
 
-/// Generates a JWT for a given user ID.
+/// Generates a JWT access token for a given user ID.
 ///
-/// The token is set to expire in 24 hours.
+/// Access tokens are short-lived; clients renew them with a refresh token (see
+/// `auth::refresh`) instead of logging in again.
 /// It requires the `JWT_SECRET` environment variable to be set for signing the token.
 ///
 /// # Arguments
 /// * `user_id` - The ID of the user for whom the token is generated.
+/// * `session_id` - The session the token is issued for.
+/// * `generation` - The user's current token generation (`users.token_generation`).
+/// * `ttl` - How long the token is valid for.
 ///
 /// # Returns
 /// A `Result` containing the JWT string if successful.
 /// Returns `AppError::InternalServerError` if `JWT_SECRET` is not set or if token encoding fails.
-pub fn generate_token(user_id: i32) -> Result<String, AppError> {
+pub fn generate_token(
+    user_id: i32,
+    session_id: Uuid,
+    generation: i32,
+    ttl: chrono::Duration,
+) -> Result<String, AppError> {
     let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
+        .checked_add_signed(ttl)
         .expect("valid timestamp")
         .timestamp() as usize;
 
     let claims = Claims {
         sub: user_id,
         exp: expiration,
+        jti: Uuid::new_v4(),
+        sid: session_id,
+        generation,
     };
 
     let secret = match std::env::var("JWT_SECRET") {
@@ -117,9 +137,18 @@ mod tests {
     fn test_token_generation_and_verification() {
         run_with_temp_jwt_secret("test_secret_for_gen_verify", || {
             let user_id = 1;
-            let token = generate_token(user_id).unwrap();
+            let session_id = Uuid::new_v4();
+            let token =
+                generate_token(user_id, session_id, 3, chrono::Duration::minutes(15)).unwrap();
             let claims = verify_token(&token).unwrap();
             assert_eq!(claims.sub, user_id);
+            assert_eq!(claims.sid, session_id);
+            assert_eq!(claims.generation, 3);
+            let other =
+                generate_token(user_id, session_id, 3, chrono::Duration::minutes(15)).unwrap();
+            assert_ne!(verify_token(&other).unwrap().jti, claims.jti);
+            let expires_in = claims.exp as i64 - chrono::Utc::now().timestamp();
+            assert!((14 * 60..=15 * 60).contains(&expires_in));
         });
     }
 
@@ -136,6 +165,9 @@ mod tests {
             let claims_expired = Claims {
                 sub: user_id,
                 exp: expiration,
+                jti: Uuid::new_v4(),
+                sid: Uuid::new_v4(),
+                generation: 0,
             };
             // JWT_SECRET is set by run_with_temp_jwt_secret, no need to get it from env here directly for encode
             let expired_token = encode(
@@ -27,8 +27,22 @@ pub enum AppError {
     Unauthorized(String),
     /// Represents a client-side error due to a malformed or invalid request (HTTP 400).
     BadRequest(String),
+    /// Represents an authenticated request for an action the user is not permitted to perform (HTTP 403).
+    /// Used when the resource is visible to the user but the action requires more rights.
+    Forbidden(String),
     /// Represents a situation where a requested resource was not found (HTTP 404).
     NotFound(String),
+    /// Represents a request that conflicts with the current state of a resource (HTTP 409),
+    /// e.g. starting a task whose blockers are not done yet.
+    Conflict(String),
+    /// Represents a status change that the task's workflow does not allow (HTTP 409),
+    /// e.g. moving straight from `todo` to `done` when the workflow requires a review.
+    InvalidTransition(String),
+    /// Represents a failed conditional request, e.g. an `If-Match` header that does not
+    /// match the resource's current `ETag` (HTTP 412).
+    PreconditionFailed(String),
+    /// Represents an upload that exceeds a size limit or quota (HTTP 413).
+    PayloadTooLarge(String),
     /// Represents an unexpected server-side error (HTTP 500).
     /// This can be used for generic internal errors not covered by more specific types.
     InternalServerError(String),
@@ -45,7 +59,12 @@ impl fmt::Display for AppError {
         match self {
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad Request: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not Found: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::InvalidTransition(msg) => write!(f, "Invalid Transition: {}", msg),
+            AppError::PreconditionFailed(msg) => write!(f, "Precondition Failed: {}", msg),
+            AppError::PayloadTooLarge(msg) => write!(f, "Payload Too Large: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "Internal Server Error: {}", msg),
             AppError::DatabaseError(msg) => write!(f, "Database Error: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation Error: {}", msg),
@@ -53,6 +72,9 @@ impl fmt::Display for AppError {
     }
 }
 
+/// Lets `AppError` be the error type of streamed response bodies.
+impl std::error::Error for AppError {}
+
 /// Converts `AppError` variants into `HttpResponse` objects.
 ///
 /// This implementation allows Actix Web to automatically translate `AppError`
@@ -66,9 +88,24 @@ impl ResponseError for AppError {
             AppError::BadRequest(msg) => HttpResponse::BadRequest().json(json!({
                 "error": msg
             })),
+            AppError::Forbidden(msg) => HttpResponse::Forbidden().json(json!({
+                "error": msg
+            })),
             AppError::NotFound(msg) => HttpResponse::NotFound().json(json!({
                 "error": msg
             })),
+            AppError::Conflict(msg) => HttpResponse::Conflict().json(json!({
+                "error": msg
+            })),
+            AppError::InvalidTransition(msg) => HttpResponse::Conflict().json(json!({
+                "error": msg
+            })),
+            AppError::PreconditionFailed(msg) => HttpResponse::PreconditionFailed().json(json!({
+                "error": msg
+            })),
+            AppError::PayloadTooLarge(msg) => HttpResponse::PayloadTooLarge().json(json!({
+                "error": msg
+            })),
             AppError::InternalServerError(msg) => HttpResponse::InternalServerError().json(json!({
                 "error": msg
             })),
@@ -190,10 +227,30 @@ mod tests {
             AppError::BadRequest("test".into()).to_string(),
             "Bad Request: test"
         );
+        assert_eq!(
+            AppError::Forbidden("test".into()).to_string(),
+            "Forbidden: test"
+        );
         assert_eq!(
             AppError::NotFound("test".into()).to_string(),
             "Not Found: test"
         );
+        assert_eq!(
+            AppError::Conflict("test".into()).to_string(),
+            "Conflict: test"
+        );
+        assert_eq!(
+            AppError::InvalidTransition("test".into()).to_string(),
+            "Invalid Transition: test"
+        );
+        assert_eq!(
+            AppError::PreconditionFailed("test".into()).to_string(),
+            "Precondition Failed: test"
+        );
+        assert_eq!(
+            AppError::PayloadTooLarge("test".into()).to_string(),
+            "Payload Too Large: test"
+        );
         assert_eq!(
             AppError::InternalServerError("test".into()).to_string(),
             "Internal Server Error: test"
@@ -221,11 +278,36 @@ mod tests {
                 StatusCode::BAD_REQUEST,
                 json!({"error": "Invalid input"}),
             ),
+            (
+                AppError::Forbidden("Not allowed".into()),
+                StatusCode::FORBIDDEN,
+                json!({"error": "Not allowed"}),
+            ),
             (
                 AppError::NotFound("Resource not found".into()),
                 StatusCode::NOT_FOUND,
                 json!({"error": "Resource not found"}),
             ),
+            (
+                AppError::Conflict("Task is blocked".into()),
+                StatusCode::CONFLICT,
+                json!({"error": "Task is blocked"}),
+            ),
+            (
+                AppError::InvalidTransition("Cannot move from todo to done".into()),
+                StatusCode::CONFLICT,
+                json!({"error": "Cannot move from todo to done"}),
+            ),
+            (
+                AppError::PreconditionFailed("ETag mismatch".into()),
+                StatusCode::PRECONDITION_FAILED,
+                json!({"error": "ETag mismatch"}),
+            ),
+            (
+                AppError::PayloadTooLarge("Quota exceeded".into()),
+                StatusCode::PAYLOAD_TOO_LARGE,
+                json!({"error": "Quota exceeded"}),
+            ),
             (
                 AppError::InternalServerError("Server error".into()),
                 StatusCode::INTERNAL_SERVER_ERROR,
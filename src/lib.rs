@@ -4,11 +4,17 @@
 #![doc = "mechanisms, routing configuration, and error handling for the TaskForge application."]
 #![doc = "It is used by the main binary (`main.rs`) to construct and run the application."]
 
+pub mod archive;
 pub mod auth;
 pub mod config;
 pub mod error;
+pub mod mail;
 pub mod models;
+pub mod realtime;
 pub mod routes;
+pub mod storage;
+pub mod trash;
+pub mod webhooks;
 
 // lib.rs now primarily declares modules for the library crate.
 // The main application setup (app factory) has been moved to main.rs
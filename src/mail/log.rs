@@ -0,0 +1,58 @@
+use super::{Email, Mailer};
+use crate::error::AppError;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+/// "Sends" emails by writing them to the application log, and appending them to a
+/// file if one is configured, so links can be followed during development.
+pub struct LogMailer {
+    from: String,
+    path: Option<PathBuf>,
+}
+
+impl LogMailer {
+    /// Creates a mailer sending from `from`, which also appends emails to `path` if set.
+    /// The file is created on first use.
+    pub fn new(from: String, path: Option<PathBuf>) -> Self {
+        Self { from, path }
+    }
+
+    /// Renders `email` the way it is logged and written to the file.
+    fn render(&self, email: &Email) -> String {
+        format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            self.from, email.to, email.subject, email.body
+        )
+    }
+}
+
+#[async_trait(?Send)]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), AppError> {
+        let rendered = self.render(email);
+        log::info!("Email:\n{}", rendered);
+
+        if let Some(path) = &self.path {
+            let io_error = |e: std::io::Error| {
+                AppError::InternalServerError(format!(
+                    "Failed to write email to {}: {}",
+                    path.display(),
+                    e
+                ))
+            };
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await
+                .map_err(io_error)?;
+            // One write per email, so concurrent emails do not interleave.
+            file.write_all(format!("{}\n", rendered).as_bytes())
+                .await
+                .map_err(io_error)?;
+            file.flush().await.map_err(io_error)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+//! # Outgoing Email
+//!
+//! This module defines the `Mailer` trait that emails to users are sent through,
+//! along with its implementations:
+//! - `log`: Writes emails to the application log, and optionally appends them to a
+//!   file. Meant for development.
+//! - `smtp`: Sends emails through an SMTP server, such as a relay or a local SMTP
+//!   sink like MailHog.
+//!
+//! Handlers do not talk to the mailer. `queue_email` adds an email to the
+//! `email_outbox` table, in the transaction that needs it sent, and every server
+//! instance runs `run_mail_worker`, which claims due emails (`send_due`) and sends
+//! them. Responses therefore never wait on, or fail with, the mail server. Claims use
+//! `FOR UPDATE SKIP LOCKED` and a lease, so instances never send the same attempt.
+//!
+//! Failed sends are retried with exponential backoff up to `MAX_SEND_ATTEMPTS` times.
+//! Emails are deleted from the outbox once sent or given up on, since the links
+//! they carry should not outlive their delivery.
+
+pub mod log;
+pub mod smtp;
+
+use crate::{config::MailerConfig, error::AppError};
+use async_trait::async_trait;
+use sqlx::{FromRow, PgExecutor, PgPool};
+use std::{sync::Arc, time::Duration};
+
+pub use self::log::LogMailer;
+pub use smtp::SmtpMailer;
+
+/// Attempts made before an email is given up on.
+pub const MAX_SEND_ATTEMPTS: i32 = 5;
+
+/// Delay before the first retry; each further retry waits twice as long.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// How long a claimed email is reserved for the instance sending it. Must exceed
+/// `smtp::SMTP_TIMEOUT`, or a slow attempt could be repeated by another instance.
+const CLAIM_LEASE_SECS: f64 = 120.0;
+
+/// Most emails claimed and sent at once.
+const SEND_BATCH_SIZE: i64 = 20;
+
+/// How often `run_mail_worker` looks for due emails.
+const SEND_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A plain-text email to a single recipient.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Email {
+    /// The recipient's address.
+    #[sqlx(rename = "recipient")]
+    pub to: String,
+    /// The subject line.
+    pub subject: String,
+    /// The plain-text body, with `\n` line endings.
+    pub body: String,
+}
+
+/// A way of delivering emails. The sender address is part of the mailer's setup.
+#[async_trait(?Send)]
+pub trait Mailer: Send + Sync {
+    /// Sends `email`. An error means it was not accepted and may be retried.
+    async fn send(&self, email: &Email) -> Result<(), AppError>;
+}
+
+/// Builds the mailer selected in the configuration, sending from `from`
+/// (e.g. "TaskForge <noreply@example.com>").
+pub fn from_config(config: &MailerConfig, from: &str) -> Arc<dyn Mailer> {
+    match config {
+        MailerConfig::Log { path } => Arc::new(LogMailer::new(from.to_string(), path.clone())),
+        MailerConfig::Smtp {
+            host,
+            port,
+            tls,
+            credentials,
+        } => Arc::new(SmtpMailer::new(
+            host.clone(),
+            *port,
+            *tls,
+            credentials.clone(),
+            from.to_string(),
+        )),
+    }
+}
+
+/// Queues `email` to be sent by the mail worker.
+///
+/// Call it with the transaction that makes the email necessary, so that the email
+/// is sent exactly when the change commits.
+pub async fn queue_email<'e, E: PgExecutor<'e>>(
+    executor: E,
+    email: &Email,
+) -> Result<(), AppError> {
+    sqlx::query("INSERT INTO email_outbox (recipient, subject, body) VALUES ($1, $2, $3)")
+        .bind(&email.to)
+        .bind(&email.subject)
+        .bind(&email.body)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Returns how long to wait after the `attempts`-th failed attempt.
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    RETRY_BASE_DELAY.saturating_mul(2u32.pow(exponent))
+}
+
+/// An email claimed from the outbox.
+#[derive(Debug, FromRow)]
+struct ClaimedEmail {
+    id: i64,
+    /// Attempts made so far, including this one.
+    attempts: i32,
+    #[sqlx(flatten)]
+    email: Email,
+}
+
+/// Removes a sent or abandoned email from the outbox.
+async fn delete_email(pool: &PgPool, id: i64) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM email_outbox WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Claims the emails that are due and sends them, returning how many were attempted.
+///
+/// The background worker calls this every few seconds; it is public so that a
+/// single pass can be driven directly.
+pub async fn send_due(pool: &PgPool, mailer: &dyn Mailer) -> Result<usize, AppError> {
+    let claimed = sqlx::query_as::<_, ClaimedEmail>(
+        "UPDATE email_outbox
+         SET attempts = attempts + 1, next_attempt_at = now() + make_interval(secs => $2)
+         WHERE id IN (SELECT id FROM email_outbox
+                      WHERE next_attempt_at <= now()
+                      ORDER BY next_attempt_at, id
+                      LIMIT $1
+                      FOR UPDATE SKIP LOCKED)
+         RETURNING id, attempts, recipient, subject, body",
+    )
+    .bind(SEND_BATCH_SIZE)
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_all(pool)
+    .await?;
+
+    for claimed_email in &claimed {
+        let (id, attempts) = (claimed_email.id, claimed_email.attempts);
+        match mailer.send(&claimed_email.email).await {
+            Ok(()) => delete_email(pool, id).await?,
+            Err(e) if attempts >= MAX_SEND_ATTEMPTS => {
+                ::log::error!(
+                    "Giving up on email {} after {} attempts: {}",
+                    id,
+                    attempts,
+                    e
+                );
+                delete_email(pool, id).await?;
+            }
+            Err(e) => {
+                ::log::warn!("Sending email {} (attempt {}) failed: {}", id, attempts, e);
+                sqlx::query(
+                    "UPDATE email_outbox SET next_attempt_at = now() + make_interval(secs => $2)
+                     WHERE id = $1",
+                )
+                .bind(id)
+                .bind(retry_delay(attempts).as_secs_f64())
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(claimed.len())
+}
+
+/// Runs `send_due` every `SEND_POLL_INTERVAL` until the server stops.
+pub async fn run_mail_worker(pool: PgPool, mailer: Arc<dyn Mailer>) {
+    let mut interval = actix_web::rt::time::interval(SEND_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        // Keep going while there is a backlog.
+        loop {
+            match send_due(&pool, mailer.as_ref()).await {
+                Ok(sent) if sent as i64 == SEND_BATCH_SIZE => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    ::log::error!("Sending queued emails failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+    }
+}
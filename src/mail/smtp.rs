@@ -0,0 +1,349 @@
+use super::{Email, Mailer};
+use crate::{config::SmtpTls, error::AppError};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{self, OwnedTrustAnchor, RootCertStore, ServerName},
+    TlsConnector,
+};
+use uuid::Uuid;
+
+/// How long sending one email, from connecting to `QUIT`, may take.
+pub const SMTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Sends emails through an SMTP server, one connection per email.
+///
+/// Supports plain connections (for local sinks), STARTTLS and implicit TLS, with
+/// server certificates checked against the Mozilla root store, and `AUTH PLAIN`.
+pub struct SmtpMailer {
+    host: String,
+    port: u16,
+    tls: SmtpTls,
+    credentials: Option<(String, String)>,
+    from: String,
+    connector: TlsConnector,
+}
+
+impl SmtpMailer {
+    /// Creates a mailer sending from `from` through the server at `host:port`,
+    /// authenticating with `credentials` (username and password) if given.
+    pub fn new(
+        host: String,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<(String, String)>,
+        from: String,
+    ) -> Self {
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        }));
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            host,
+            port,
+            tls,
+            credentials,
+            from,
+            connector: TlsConnector::from(Arc::new(config)),
+        }
+    }
+
+    async fn start_tls<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        stream: S,
+    ) -> Result<tokio_rustls::client::TlsStream<S>, AppError> {
+        let name = ServerName::try_from(self.host.as_str())
+            .map_err(|_| smtp_error(format!("invalid host name {}", self.host)))?;
+        self.connector
+            .connect(name, stream)
+            .await
+            .map_err(|e| smtp_error(format!("TLS handshake failed: {}", e)))
+    }
+
+    async fn deliver(&self, email: &Email) -> Result<(), AppError> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| smtp_error(format!("cannot connect to {}: {}", self.host, e)))?;
+
+        match self.tls {
+            SmtpTls::None => self.session(Connection::new(stream), email).await,
+            SmtpTls::Tls => {
+                let stream = self.start_tls(stream).await?;
+                self.session(Connection::new(stream), email).await
+            }
+            SmtpTls::StartTls => {
+                let mut connection = Connection::new(stream);
+                connection.reply(220).await?;
+                let extensions = connection.command(EHLO, 250).await?;
+                if !has_extension(&extensions, "STARTTLS") {
+                    return Err(smtp_error("the server does not support STARTTLS"));
+                }
+                connection.command("STARTTLS", 220).await?;
+
+                let stream = self.start_tls(connection.into_inner()).await?;
+                let mut connection = Connection::new(stream);
+                let extensions = connection.command(EHLO, 250).await?;
+                self.transaction(connection, &extensions, email).await
+            }
+        }
+    }
+
+    /// Greets the server and sends `email`.
+    async fn session<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        mut connection: Connection<S>,
+        email: &Email,
+    ) -> Result<(), AppError> {
+        connection.reply(220).await?;
+        let extensions = connection.command(EHLO, 250).await?;
+        self.transaction(connection, &extensions, email).await
+    }
+
+    /// Authenticates if configured to, sends `email` and closes the connection.
+    /// `extensions` is the server's reply to `EHLO`.
+    async fn transaction<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        mut connection: Connection<S>,
+        extensions: &str,
+        email: &Email,
+    ) -> Result<(), AppError> {
+        if let Some((username, password)) = &self.credentials {
+            if !has_extension(extensions, "AUTH") {
+                return Err(smtp_error("the server does not support AUTH"));
+            }
+            let token = BASE64.encode(format!("\0{}\0{}", username, password));
+            connection
+                .command(&format!("AUTH PLAIN {}", token), 235)
+                .await?;
+        }
+
+        connection
+            .command(&format!("MAIL FROM:<{}>", address(&self.from)?), 250)
+            .await?;
+        connection
+            .command(&format!("RCPT TO:<{}>", address(&email.to)?), 250)
+            .await?;
+        connection.command("DATA", 354).await?;
+        let message = format_message(&self.from, email, Utc::now(), Uuid::new_v4());
+        connection.write(&message).await?;
+        connection.command(".", 250).await?;
+
+        // The email is accepted at this point; a failed goodbye does not matter.
+        let _ = connection.command("QUIT", 221).await;
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), AppError> {
+        tokio::time::timeout(SMTP_TIMEOUT, self.deliver(email))
+            .await
+            .map_err(|_| smtp_error("timed out"))?
+    }
+}
+
+/// The greeting sent to the server. There is no meaningful name to introduce
+/// ourselves with, and servers do not rely on it.
+const EHLO: &str = "EHLO localhost";
+
+fn smtp_error(message: impl std::fmt::Display) -> AppError {
+    AppError::InternalServerError(format!("SMTP error: {}", message))
+}
+
+/// An SMTP conversation over `S`.
+struct Connection<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// Returns the underlying stream, for STARTTLS. Nothing is buffered at that point,
+    /// because servers wait for the client after accepting STARTTLS.
+    fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    async fn write(&mut self, data: &str) -> Result<(), AppError> {
+        let io_error = |e: std::io::Error| smtp_error(format!("connection failed: {}", e));
+        self.stream
+            .write_all(data.as_bytes())
+            .await
+            .map_err(io_error)?;
+        self.stream.flush().await.map_err(io_error)
+    }
+
+    /// Reads a reply, which may span several lines, and returns its text if its
+    /// code is in the same class as `expected` (e.g. any 2xx for 250).
+    async fn reply(&mut self, expected: u16) -> Result<String, AppError> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stream
+                .read_line(&mut line)
+                .await
+                .map_err(|e| smtp_error(format!("connection failed: {}", e)))?;
+            if read == 0 {
+                return Err(smtp_error("the server closed the connection"));
+            }
+            let line = line.trim_end();
+            let code: u16 = line
+                .get(..3)
+                .and_then(|code| code.parse().ok())
+                .ok_or_else(|| smtp_error(format!("unexpected reply {:?}", line)))?;
+            text.push_str(line.get(4..).unwrap_or(""));
+            text.push('\n');
+
+            if line.as_bytes().get(3) != Some(&b'-') {
+                if code / 100 != expected / 100 {
+                    return Err(smtp_error(format!(
+                        "expected {}, got {} {}",
+                        expected,
+                        code,
+                        text.trim_end()
+                    )));
+                }
+                return Ok(text);
+            }
+        }
+    }
+
+    /// Sends `command` and reads its reply, which must be in the class of `expected`.
+    async fn command(&mut self, command: &str, expected: u16) -> Result<String, AppError> {
+        self.write(&format!("{}\r\n", command)).await?;
+        self.reply(expected).await
+    }
+}
+
+/// Returns `true` if the `EHLO` reply `extensions` lists the extension `name`.
+fn has_extension(extensions: &str, name: &str) -> bool {
+    extensions.lines().skip(1).any(|line| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|keyword| keyword.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Returns the bare address of a mailbox such as "TaskForge <noreply@example.com>".
+fn address(mailbox: &str) -> Result<&str, AppError> {
+    let address = match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    };
+    if address.is_empty() || address.contains(|c: char| c.is_whitespace() || c == '<' || c == '>') {
+        return Err(smtp_error(format!("invalid address {:?}", mailbox)));
+    }
+    Ok(address)
+}
+
+/// Makes `value` safe to use as a header value: line breaks are replaced, and
+/// values that are not plain ASCII are encoded as an RFC 2047 encoded word.
+fn header_value(value: &str) -> String {
+    let value = value.replace(['\r', '\n'], " ");
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?utf-8?B?{}?=", BASE64.encode(value))
+    }
+}
+
+/// Formats `email` for the `DATA` command: headers, then the body with CRLF line
+/// endings and dot-stuffing. Ends with a line break, before the terminating `.`.
+fn format_message(from: &str, email: &Email, date: DateTime<Utc>, message_id: Uuid) -> String {
+    let domain = address(from)
+        .ok()
+        .and_then(|address| address.rsplit_once('@'))
+        .map_or("localhost", |(_, domain)| domain);
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\n\
+         MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: 8bit\r\n\r\n",
+        from.replace(['\r', '\n'], " "),
+        email.to.replace(['\r', '\n'], " "),
+        header_value(&email.subject),
+        date.to_rfc2822(),
+        message_id.simple(),
+        domain
+    );
+    for line in email.body.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            address("TaskForge <noreply@example.com>").unwrap(),
+            "noreply@example.com"
+        );
+        assert_eq!(address(" user@example.com ").unwrap(), "user@example.com");
+        assert!(address("").is_err());
+        assert!(address("a@b.c>\r\nRCPT TO:<x@y.z").is_err());
+    }
+
+    #[test]
+    fn test_has_extension() {
+        let extensions = "smtp.example.com greets you\nPIPELINING\nAUTH PLAIN LOGIN\nSTARTTLS\n";
+        assert!(has_extension(extensions, "starttls"));
+        assert!(has_extension(extensions, "AUTH"));
+        assert!(!has_extension(extensions, "SMTPUTF8"));
+        assert!(!has_extension("smtp.example.com STARTTLS\n", "STARTTLS"));
+    }
+
+    #[test]
+    fn test_format_message() {
+        let email = Email {
+            to: "user@example.com".to_string(),
+            subject: "Réinitialisation\r\nBcc: x@y.z".to_string(),
+            body: "Hello\n.hidden line\n..\nBye".to_string(),
+        };
+        let date = DateTime::parse_from_rfc3339("2025-06-18T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let message = format_message("TaskForge <noreply@example.com>", &email, date, Uuid::nil());
+
+        let (headers, body) = message.split_once("\r\n\r\n").unwrap();
+        assert!(headers
+            .starts_with("From: TaskForge <noreply@example.com>\r\nTo: user@example.com\r\n"));
+        assert!(headers.contains(&format!(
+            "Subject: =?utf-8?B?{}?=\r\n",
+            BASE64.encode("Réinitialisation  Bcc: x@y.z")
+        )));
+        assert!(headers.contains("Date: Wed, 18 Jun 2025 12:00:00 +0000\r\n"));
+        assert!(headers.contains(&format!(
+            "Message-ID: <{}@example.com>",
+            Uuid::nil().simple()
+        )));
+        assert!(!headers.contains("\r\nBcc:"));
+        assert_eq!(body, "Hello\r\n..hidden line\r\n...\r\nBye\r\n");
+    }
+}
@@ -27,11 +27,67 @@ async fn run_app() -> std::io::Result<()> {
         .await
         .expect("Failed to create pool"); // This line will be tested for panic
 
+    // Attachment storage, plus a background task that removes the objects of
+    // deleted attachments
+    let storage = taskforge::storage::from_config(&config.storage);
+    actix_web::rt::spawn(taskforge::storage::run_orphan_sweeper(
+        pool.clone(),
+        storage.clone(),
+    ));
+
+    // Real-time task updates, fanned out to this instance's WebSocket and event
+    // stream clients through Postgres LISTEN/NOTIFY, plus a background task that
+    // prunes the replay log
+    let broadcaster = std::sync::Arc::new(taskforge::realtime::TaskBroadcaster::new());
+    actix_web::rt::spawn(taskforge::realtime::run_listener(
+        pool.clone(),
+        broadcaster.clone(),
+    ));
+    actix_web::rt::spawn(taskforge::realtime::run_event_log_pruner(
+        pool.clone(),
+        config.event_log_retention_hours,
+    ));
+
+    // Background task that permanently deletes tasks left in the trash
+    actix_web::rt::spawn(taskforge::trash::run_trash_purger(
+        pool.clone(),
+        config.trash_retention_days,
+    ));
+
+    // Background task that archives tasks some days after they are done
+    if let Some(after_days) = config.auto_archive_after_days {
+        actix_web::rt::spawn(taskforge::archive::run_auto_archiver(
+            pool.clone(),
+            after_days,
+        ));
+    }
+
+    // Background worker that sends queued webhook deliveries
+    actix_web::rt::spawn(taskforge::webhooks::run_delivery_worker(
+        pool.clone(),
+        taskforge::webhooks::http_client(config.webhook_allow_private_targets),
+    ));
+
+    // Background worker that sends queued emails
+    actix_web::rt::spawn(taskforge::mail::run_mail_worker(
+        pool.clone(),
+        taskforge::mail::from_config(&config.mailer, &config.mail_from),
+    ));
+
     // Start HTTP server
+    let bind_address = (config.server_host.clone(), config.server_port);
+    let app_config = web::Data::new(config);
     HttpServer::new(move || {
         // App factory logic inlined here, as this resolved previous compilation issues
         App::new()
-            .wrap(middleware::Logger::default())
+            // The default format, except that the WebSocket endpoint's query string,
+            // which may hold a token, is left out of the request line
+            .wrap(
+                middleware::Logger::new(
+                    r#"%a "%{request_line}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
+                )
+                .custom_request_replace("request_line", taskforge::routes::ws::logged_request_line),
+            )
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -40,6 +96,9 @@ async fn run_app() -> std::io::Result<()> {
                     .max_age(3600),
             )
             .app_data(web::Data::new(pool.clone())) // pool is captured by the closure
+            .app_data(app_config.clone())
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::from(broadcaster.clone()))
             .service(
                 web::scope("/api")
                     .wrap(taskforge::auth::AuthMiddleware) // Sourced from lib.rs modules
@@ -47,7 +106,7 @@ async fn run_app() -> std::io::Result<()> {
             )
             .service(taskforge::routes::health::health) // Sourced from lib.rs modules
     })
-    .bind((config.server_host, config.server_port))?
+    .bind(bind_address)?
     .run()
     .await
 }
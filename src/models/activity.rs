@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Kind of change recorded in a task's activity log.
+/// Corresponds to the `task_event_type` SQL enum.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "task_event_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TaskEventType {
+    /// The task was created.
+    Created,
+    /// One or more fields of the task were edited.
+    Updated,
+    /// The task was moved to the trash.
+    Deleted,
+    /// The task was assigned to a user through the assign endpoint.
+    Assigned,
+    /// The task's assignee was removed through the unassign endpoint.
+    Unassigned,
+    /// The task was restored from the trash.
+    Restored,
+    /// The task was archived, by its owner or automatically after being done for a while.
+    Archived,
+    /// The task was taken out of the archive.
+    Unarchived,
+}
+
+// Allows binding and decoding `task_event_type[]`, as used by webhook subscriptions.
+impl sqlx::postgres::PgHasArrayType for TaskEventType {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_task_event_type")
+    }
+}
+
+/// An entry in a task's activity log.
+///
+/// Entries are kept after their task is deleted. `actor_id` is `None` for changes
+/// made by the server itself, and becomes `None` if the user who made the change is
+/// deleted.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskEvent {
+    /// Unique, increasing identifier for the entry.
+    pub id: i64,
+    /// The task that changed.
+    pub task_id: Uuid,
+    /// The user who made the change.
+    pub actor_id: Option<i32>,
+    /// What kind of change this was.
+    pub event_type: TaskEventType,
+    /// The fields that changed, as `{ "field": { "old": …, "new": … } }`.
+    pub changes: serde_json::Value,
+    /// Timestamp of the change.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for paging through a task's activity log.
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// Page size, between 1 and 100. Defaults to 50.
+    pub limit: Option<i64>,
+    /// The `next_cursor` value from a previous page.
+    pub cursor: Option<String>,
+}
+
+/// Keyset cursor for activity listings: the `id` of the last entry on a page.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ActivityCursor {
+    /// ID of the last entry on the page.
+    pub id: i64,
+}
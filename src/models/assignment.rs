@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A single change of a task's assignee.
+///
+/// A row is recorded each time the assignee actually changes, whether through
+/// `POST /api/tasks/{id}/assign`, `DELETE /api/tasks/{id}/assign` or a `PATCH`
+/// of `assigned_to`. User IDs become `None` if the referenced user is deleted.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskAssignment {
+    /// Unique identifier for the history entry.
+    pub id: Uuid,
+    /// The task whose assignee changed.
+    pub task_id: Uuid,
+    /// The user who made the change.
+    pub assigned_by: Option<i32>,
+    /// The assignee before the change, if the task was assigned.
+    pub previous_assignee_id: Option<i32>,
+    /// The assignee after the change, or `None` if the task was unassigned.
+    pub assignee_id: Option<i32>,
+    /// Timestamp of the change.
+    pub assigned_at: DateTime<Utc>,
+}
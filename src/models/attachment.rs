@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Metadata of a file attached to a task.
+///
+/// The contents are kept in the configured storage backend and downloaded
+/// through `GET /api/tasks/{id}/attachments/{attachment_id}`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskAttachment {
+    /// Unique identifier for the attachment.
+    pub id: Uuid,
+    /// The task the attachment belongs to.
+    pub task_id: Uuid,
+    /// The user who uploaded the file; counts against their quota.
+    pub uploaded_by: i32,
+    /// The file name given by the uploader, without any directory part.
+    pub filename: String,
+    /// The MIME type given by the uploader, or `application/octet-stream`.
+    pub content_type: String,
+    /// Size of the contents in bytes.
+    pub size_bytes: i64,
+    /// Hex-encoded SHA-256 digest of the contents.
+    pub sha256: String,
+    /// Key of the contents in the storage backend. Never exposed to clients.
+    #[serde(skip)]
+    pub storage_key: String,
+    /// Timestamp of when the file was uploaded.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::task::{Task, TaskInput, TaskPriority, TaskStatus};
+
+/// Maximum number of operations accepted in a single bulk request.
+pub const MAX_BULK_OPERATIONS: usize = 100;
+
+/// How a bulk request treats failing operations.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Either every operation is applied or none is.
+    #[default]
+    Atomic,
+    /// Successful operations are kept even if others fail.
+    Partial,
+}
+
+/// A single operation in a bulk request, tagged by `op`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkOperation {
+    /// Creates a task owned by the authenticated user. Fields are those of `TaskInput`.
+    Create(TaskInput),
+    /// Changes the status and/or priority of a task.
+    ///
+    /// Changing the status is allowed for the owner and the assignee; changing the
+    /// priority is owner-only.
+    Update {
+        /// The task to update.
+        id: Uuid,
+        /// New status, if it should change.
+        status: Option<TaskStatus>,
+        /// New priority, if it should change.
+        priority: Option<TaskPriority>,
+    },
+    /// Assigns a task to a user (owner only).
+    Assign {
+        /// The task to assign.
+        id: Uuid,
+        /// The user to assign the task to.
+        assignee_id: i32,
+    },
+    /// Deletes a task (owner only).
+    Delete {
+        /// The task to delete.
+        id: Uuid,
+    },
+}
+
+/// Request body for `POST /api/tasks/bulk`.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BulkRequest {
+    /// Failure handling mode. Defaults to `atomic`.
+    #[serde(default)]
+    pub mode: BulkMode,
+    /// Operations to apply, in order.
+    /// Must contain between 1 and `MAX_BULK_OPERATIONS` items.
+    #[validate(length(min = 1, max = 100))]
+    pub operations: Vec<BulkOperation>,
+}
+
+/// Outcome of one operation in a bulk request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkOperationResult {
+    /// Position of the operation in the request.
+    pub index: usize,
+    /// HTTP status the equivalent single-task request would have returned.
+    pub status: u16,
+    /// The created or updated task, for successful non-delete operations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<Task>,
+    /// Error message, for failed operations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response body for `POST /api/tasks/bulk`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkResponse {
+    /// The mode the request ran in.
+    pub mode: BulkMode,
+    /// Whether the changes were committed. `false` when an atomic request was rolled back.
+    pub committed: bool,
+    /// One result per operation, in request order.
+    pub results: Vec<BulkOperationResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bulk_request_parsing() {
+        let request: BulkRequest = serde_json::from_value(json!({
+            "operations": [
+                { "op": "create", "title": "New", "status": "todo" },
+                { "op": "update", "id": Uuid::nil(), "status": "done" },
+                { "op": "assign", "id": Uuid::nil(), "assignee_id": 7 },
+                { "op": "delete", "id": Uuid::nil() }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(request.mode, BulkMode::Atomic);
+        assert!(request.validate().is_ok());
+        assert!(
+            matches!(&request.operations[0], BulkOperation::Create(input) if input.title == "New")
+        );
+        assert!(matches!(
+            &request.operations[1],
+            BulkOperation::Update {
+                status: Some(status),
+                priority: None,
+                ..
+            } if *status == TaskStatus::new("done")
+        ));
+        assert!(matches!(
+            request.operations[2],
+            BulkOperation::Assign { assignee_id: 7, .. }
+        ));
+
+        let unknown_op = serde_json::from_value::<BulkRequest>(json!({
+            "operations": [{ "op": "archive", "id": Uuid::nil() }]
+        }));
+        assert!(unknown_op.is_err());
+    }
+
+    #[test]
+    fn test_bulk_request_size_limits() {
+        let empty = BulkRequest {
+            mode: BulkMode::Partial,
+            operations: vec![],
+        };
+        assert!(empty.validate().is_err());
+
+        let too_many = BulkRequest {
+            mode: BulkMode::Partial,
+            operations: (0..=MAX_BULK_OPERATIONS)
+                .map(|_| BulkOperation::Delete { id: Uuid::nil() })
+                .collect(),
+        };
+        assert!(too_many.validate().is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A comment in a task's discussion thread.
+///
+/// Top-level comments have no `parent_id`; replies point at another comment on
+/// the same task.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskComment {
+    /// Unique identifier for the comment.
+    pub id: Uuid,
+    /// The task the comment belongs to.
+    pub task_id: Uuid,
+    /// The comment this one replies to, if any.
+    pub parent_id: Option<Uuid>,
+    /// The user who wrote the comment.
+    pub user_id: i32,
+    /// The comment text.
+    pub body: String,
+    /// Timestamp of when the comment was posted.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp of the last edit, or `None` if the comment was never edited.
+    pub edited_at: Option<DateTime<Utc>>,
+}
+
+/// Input structure for posting a comment or a reply.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaskCommentInput {
+    /// The comment text.
+    /// Must be between 1 and 5000 characters.
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+    /// The comment being replied to. Must belong to the same task.
+    pub parent_id: Option<Uuid>,
+}
+
+/// Input structure for editing a comment's text.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaskCommentUpdate {
+    /// The new comment text.
+    /// Must be between 1 and 5000 characters.
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_input_validation() {
+        let valid = TaskCommentInput {
+            body: "Can we ship this behind a flag?".to_string(),
+            parent_id: None,
+        };
+        assert!(valid.validate().is_ok());
+
+        let empty = TaskCommentInput {
+            body: "".to_string(),
+            parent_id: Some(Uuid::new_v4()),
+        };
+        assert!(empty.validate().is_err());
+
+        let too_long = TaskCommentUpdate {
+            body: "a".repeat(5001),
+        };
+        assert!(too_long.validate().is_err());
+    }
+}
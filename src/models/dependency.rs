@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::task::{Task, TaskStatus};
+
+/// A "blocker blocks blocked" edge between two tasks.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskDependency {
+    /// The task that has to be done first.
+    pub blocker_id: Uuid,
+    /// The task that is blocked until the blocker is done.
+    pub blocked_id: Uuid,
+    /// The user who added the dependency.
+    pub created_by: Option<i32>,
+    /// Timestamp of when the dependency was added.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Input structure for adding a blocker to a task.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDependencyInput {
+    /// The task that should block the task in the path.
+    pub blocker_id: Uuid,
+}
+
+/// Summary of a task on the other end of a dependency.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DependencyLink {
+    /// The linked task's ID.
+    pub id: Uuid,
+    /// The linked task's title.
+    pub title: String,
+    /// The linked task's current status.
+    pub status: TaskStatus,
+}
+
+/// A task as returned by `GET /api/tasks/{id}`, with its dependencies.
+///
+/// Serializes as a plain `Task` with `blocked_by` and `blocks` added. Only linked
+/// tasks the authenticated user can see are listed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDetail {
+    /// The task itself.
+    #[serde(flatten)]
+    pub task: Task,
+    /// Upstream tasks that block this one.
+    pub blocked_by: Vec<DependencyLink>,
+    /// Downstream tasks this one blocks.
+    pub blocks: Vec<DependencyLink>,
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// A label a user can attach to their tasks.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Label {
+    /// Unique identifier for the label.
+    pub id: Uuid,
+    /// The user who owns the label.
+    pub user_id: i32,
+    /// The label name, unique per user.
+    pub name: String,
+    /// The label colour as a `#rrggbb` hex string.
+    pub color: String,
+    /// Timestamp of when the label was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A label as embedded in the `labels` array of a `Task`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskLabel {
+    /// The label's ID.
+    pub id: Uuid,
+    /// The label name.
+    pub name: String,
+    /// The label colour as a `#rrggbb` hex string.
+    pub color: String,
+}
+
+/// Input structure for creating or updating a label.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LabelInput {
+    /// The label name.
+    /// Must be between 1 and 50 characters and must not contain commas, which
+    /// separate names in the `labels` filter.
+    #[validate(length(min = 1, max = 50), custom = "validate_label_name")]
+    pub name: String,
+    /// The label colour as a `#rrggbb` hex string.
+    #[validate(custom = "validate_color")]
+    pub color: String,
+}
+
+/// How the `labels` filter of `TaskQuery` combines several label names.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelMatch {
+    /// Tasks with at least one of the labels (default).
+    #[default]
+    Any,
+    /// Tasks with every one of the labels.
+    All,
+}
+
+fn validate_label_name(name: &str) -> Result<(), ValidationError> {
+    if name.contains(',') {
+        return Err(ValidationError::new("label_name_comma"));
+    }
+    Ok(())
+}
+
+fn validate_color(color: &str) -> Result<(), ValidationError> {
+    let is_hex = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hex {
+        return Err(ValidationError::new("hex_color"));
+    }
+    Ok(())
+}
+
+/// Splits a comma-separated `labels` query value into distinct, trimmed names.
+pub fn parse_label_names(labels: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in labels.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_input_validation() {
+        let valid = LabelInput {
+            name: "backend".to_string(),
+            color: "#1f6FeB".to_string(),
+        };
+        assert!(valid.validate().is_ok());
+
+        for (name, color) in [
+            ("", "#1f6feb"),
+            ("a,b", "#1f6feb"),
+            ("backend", "1f6feb"),
+            ("backend", "#1f6fe"),
+            ("backend", "#zzzzzz"),
+        ] {
+            let input = LabelInput {
+                name: name.to_string(),
+                color: color.to_string(),
+            };
+            assert!(input.validate().is_err(), "{:?} should be invalid", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_label_names() {
+        assert_eq!(
+            parse_label_names("bug, frontend,,bug ,urgent"),
+            vec!["bug", "frontend", "urgent"]
+        );
+        assert!(parse_label_names(" , ").is_empty());
+    }
+}
@@ -5,8 +5,38 @@
 //! It also includes input structures for data validation and query structures
 //! for database interactions.
 
+pub mod activity;
+pub mod assignment;
+pub mod attachment;
+pub mod bulk;
+pub mod comment;
+pub mod dependency;
+pub mod label;
+pub mod note;
+pub mod pagination;
+pub mod realtime;
+pub mod session;
+pub mod status;
 pub mod task;
 pub mod user;
+pub mod webhook;
+pub mod workflow;
 
-pub use task::{Task, TaskInput, TaskPriority, TaskQuery, TaskStatus};
+pub use activity::{TaskEvent, TaskEventType};
+pub use assignment::TaskAssignment;
+pub use attachment::TaskAttachment;
+pub use bulk::{BulkMode, BulkOperation, BulkOperationResult, BulkRequest, BulkResponse};
+pub use comment::{TaskComment, TaskCommentInput, TaskCommentUpdate};
+pub use dependency::{DependencyLink, TaskDependency, TaskDependencyInput, TaskDetail};
+pub use label::{Label, LabelInput, LabelMatch, TaskLabel};
+pub use note::{TaskNote, TaskNoteInput};
+pub use pagination::{Page, SortOrder};
+pub use session::Session;
+pub use status::{StatusCategory, StatusDefinition, StatusInput};
+pub use task::{
+    SearchMatch, Task, TaskInput, TaskListItem, TaskPatch, TaskPriority, TaskQuery, TaskRole,
+    TaskScope, TaskSortField, TaskStatus,
+};
 pub use user::{User, UserInput};
+pub use webhook::{Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookInput};
+pub use workflow::{AvailableTransition, Workflow, WorkflowInput, WorkflowTransition};
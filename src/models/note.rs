@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A note attached to a task.
+///
+/// Notes are an append-only work log that both the task owner and the assignee
+/// can add to.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskNote {
+    /// Unique identifier for the note.
+    pub id: Uuid,
+    /// The task the note belongs to.
+    pub task_id: Uuid,
+    /// The user who wrote the note.
+    pub user_id: i32,
+    /// The note text.
+    pub body: String,
+    /// Timestamp of when the note was added.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Input structure for adding a note to a task.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaskNoteInput {
+    /// The note text.
+    /// Must be between 1 and 2000 characters.
+    #[validate(length(min = 1, max = 2000))]
+    pub body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_input_validation() {
+        let valid = TaskNoteInput {
+            body: "Picked this up, waiting on review".to_string(),
+        };
+        assert!(valid.validate().is_ok());
+
+        let empty = TaskNoteInput {
+            body: "".to_string(),
+        };
+        assert!(empty.validate().is_err());
+
+        let too_long = TaskNoteInput {
+            body: "a".repeat(2001),
+        };
+        assert!(too_long.validate().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Default number of items returned per page when `limit` is not provided.
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+/// Upper bound for the `limit` query parameter.
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+/// Direction in which a listing is sorted.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Ascending order (oldest/lowest first).
+    Asc,
+    /// Descending order (newest/highest first).
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// Returns the SQL keyword for this direction.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    /// Returns the comparison operator that selects rows *after* a cursor in this direction.
+    pub fn after_operator(self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+}
+
+/// A page of results returned by cursor-paginated listing endpoints.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// The items on this page.
+    pub items: Vec<T>,
+    /// Opaque cursor to pass as `cursor` to fetch the next page, if any.
+    pub next_cursor: Option<String>,
+    /// Whether more items exist after this page.
+    pub has_more: bool,
+}
+
+/// Clamps a requested page size into `1..=MAX_PAGE_LIMIT`, falling back to `DEFAULT_PAGE_LIMIT`.
+pub fn clamp_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+}
+
+/// Encodes a cursor payload as an opaque, URL-safe string.
+pub fn encode_cursor<C: Serialize>(cursor: &C) -> Result<String, AppError> {
+    let json = serde_json::to_vec(cursor)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode cursor: {}", e)))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+/// Decodes an opaque cursor string produced by `encode_cursor`.
+///
+/// Returns `AppError::BadRequest` if the cursor is malformed.
+pub fn decode_cursor<C: DeserializeOwned>(cursor: &str) -> Result<C, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| AppError::BadRequest("Invalid cursor".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestCursor {
+        v: Option<String>,
+        id: i32,
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = TestCursor {
+            v: Some("2025-01-01T00:00:00Z".to_string()),
+            id: 42,
+        };
+        let encoded = encode_cursor(&cursor).unwrap();
+        assert!(!encoded.contains('='));
+        let decoded: TestCursor = decode_cursor(&encoded).unwrap();
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn test_decode_invalid_cursor() {
+        assert!(matches!(
+            decode_cursor::<TestCursor>("not a cursor!"),
+            Err(AppError::BadRequest(_))
+        ));
+        let not_json = URL_SAFE_NO_PAD.encode("hello");
+        assert!(decode_cursor::<TestCursor>(&not_json).is_err());
+    }
+
+    #[test]
+    fn test_clamp_limit() {
+        assert_eq!(clamp_limit(None), DEFAULT_PAGE_LIMIT);
+        assert_eq!(clamp_limit(Some(0)), 1);
+        assert_eq!(clamp_limit(Some(10)), 10);
+        assert_eq!(clamp_limit(Some(10_000)), MAX_PAGE_LIMIT);
+    }
+}
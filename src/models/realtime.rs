@@ -0,0 +1,232 @@
+use crate::models::{Task, TaskEvent, TaskPriority, TaskScope, TaskStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Maximum number of task IDs one WebSocket connection may subscribe to.
+pub const MAX_SUBSCRIBED_TASKS: usize = 500;
+/// Maximum number of filters one WebSocket connection may subscribe to.
+pub const MAX_SUBSCRIBED_FILTERS: usize = 20;
+
+/// The fields of a task that subscription filters match against, as of an event.
+///
+/// For deletions this is the task as it was just before it was deleted.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskState {
+    /// The task's owner.
+    pub user_id: i32,
+    /// The task's status.
+    pub status: TaskStatus,
+    /// The task's priority.
+    pub priority: Option<TaskPriority>,
+    /// The task's assignee.
+    pub assigned_to: Option<i32>,
+    /// The task's parent.
+    pub parent_id: Option<Uuid>,
+}
+
+impl From<&Task> for TaskState {
+    fn from(task: &Task) -> Self {
+        Self {
+            user_id: task.user_id,
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            assigned_to: task.assigned_to,
+            parent_id: task.parent_id,
+        }
+    }
+}
+
+/// Selects the task events a WebSocket connection receives. Every set field must match.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TaskFilter {
+    /// `owned` or `assigned`, relative to the connected user.
+    pub scope: Option<TaskScope>,
+    /// Match tasks with this status.
+    pub status: Option<TaskStatus>,
+    /// Match tasks with this priority.
+    pub priority: Option<TaskPriority>,
+    /// Match tasks assigned to this user.
+    pub assigned_to: Option<i32>,
+    /// Match direct subtasks of this task.
+    pub parent_id: Option<Uuid>,
+}
+
+impl TaskFilter {
+    /// Returns `true` if a task in `state` matches the filter for `user_id`.
+    pub fn matches(&self, user_id: i32, state: &TaskState) -> bool {
+        let in_scope = match self.scope.unwrap_or_default() {
+            TaskScope::Owned => state.user_id == user_id,
+            TaskScope::Assigned => state.assigned_to == Some(user_id),
+            TaskScope::All => true,
+        };
+        in_scope
+            && self.status.as_ref().is_none_or(|s| *s == state.status)
+            && self
+                .priority
+                .as_ref()
+                .is_none_or(|p| state.priority.as_ref() == Some(p))
+            && self
+                .assigned_to
+                .is_none_or(|a| state.assigned_to == Some(a))
+            && self.parent_id.is_none_or(|p| state.parent_id == Some(p))
+    }
+}
+
+/// The task IDs and filters a WebSocket connection is subscribed to.
+///
+/// An empty subscription receives events for every task the user can see.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct Subscription {
+    /// Tasks subscribed to by ID.
+    pub task_ids: BTreeSet<Uuid>,
+    /// Filters subscribed to.
+    pub filters: Vec<TaskFilter>,
+}
+
+impl Subscription {
+    /// Returns `true` if `user_id` should receive an event on `task_id` in `state`.
+    ///
+    /// Callers must check separately that the user can see the task.
+    pub fn matches(&self, user_id: i32, task_id: Uuid, state: &TaskState) -> bool {
+        (self.task_ids.is_empty() && self.filters.is_empty())
+            || self.task_ids.contains(&task_id)
+            || self.filters.iter().any(|f| f.matches(user_id, state))
+    }
+}
+
+/// A message sent by a WebSocket client.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ClientMessage {
+    /// Adds task IDs and/or a filter to the subscription.
+    Subscribe {
+        /// Tasks to receive events for.
+        #[serde(default)]
+        task_ids: Vec<Uuid>,
+        /// A filter to receive matching events for.
+        filter: Option<TaskFilter>,
+    },
+    /// Removes the given task IDs from the subscription, or clears it entirely
+    /// if `task_ids` is omitted.
+    Unsubscribe {
+        /// Tasks to stop receiving events for.
+        task_ids: Option<Vec<Uuid>>,
+    },
+}
+
+/// A message sent to a WebSocket client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage<'a> {
+    /// A task the user can see was created, updated, assigned, unassigned, deleted,
+    /// restored, archived or unarchived.
+    TaskEvent {
+        /// The activity log entry describing the change.
+        event: &'a TaskEvent,
+        /// The task's current state, or `None` once it has been deleted or is no
+        /// longer visible to the user.
+        task: Option<&'a Task>,
+    },
+    /// Acknowledges a subscription change with the resulting subscription.
+    Subscribed {
+        /// The connection's subscription after the change.
+        subscription: &'a Subscription,
+    },
+    /// The connection fell behind and `skipped` events were dropped; clients should
+    /// refetch the tasks they display.
+    Lagged {
+        /// Number of events dropped.
+        skipped: u64,
+    },
+    /// A client message could not be processed.
+    Error {
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> TaskState {
+        TaskState {
+            user_id: 1,
+            status: TaskStatus::new("in_progress"),
+            priority: Some(TaskPriority::High),
+            assigned_to: Some(2),
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn test_task_filter_matches() {
+        let state = state();
+        assert!(TaskFilter::default().matches(1, &state));
+
+        let filter = TaskFilter {
+            scope: Some(TaskScope::Assigned),
+            status: Some(TaskStatus::new("in_progress")),
+            ..Default::default()
+        };
+        assert!(filter.matches(2, &state));
+        assert!(!filter.matches(1, &state));
+
+        let filter = TaskFilter {
+            priority: Some(TaskPriority::Low),
+            ..Default::default()
+        };
+        assert!(!filter.matches(1, &state));
+
+        let filter = TaskFilter {
+            parent_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(1, &state));
+    }
+
+    #[test]
+    fn test_subscription_matches() {
+        let state = state();
+        let task_id = Uuid::new_v4();
+        assert!(Subscription::default().matches(1, task_id, &state));
+
+        let mut subscription = Subscription::default();
+        subscription.task_ids.insert(Uuid::new_v4());
+        assert!(!subscription.matches(1, task_id, &state));
+        subscription.task_ids.insert(task_id);
+        assert!(subscription.matches(1, task_id, &state));
+
+        let subscription = Subscription {
+            task_ids: BTreeSet::new(),
+            filters: vec![TaskFilter {
+                status: Some(TaskStatus::new("in_progress")),
+                ..Default::default()
+            }],
+        };
+        assert!(subscription.matches(1, task_id, &state));
+    }
+
+    #[test]
+    fn test_client_message_parsing() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"subscribe","filter":{"status":"done"}}"#).unwrap();
+        assert!(matches!(
+            message,
+            ClientMessage::Subscribe { task_ids, filter: Some(_) } if task_ids.is_empty()
+        ));
+
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"unsubscribe"}"#).unwrap();
+        assert!(matches!(
+            message,
+            ClientMessage::Unsubscribe { task_ids: None }
+        ));
+
+        assert!(serde_json::from_str::<ClientMessage>(
+            r#"{"type":"subscribe","filter":{"colour":"red"}}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A place a user is signed in: one login or registration and the tokens
+/// refreshed from it.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Session {
+    /// Unique identifier for the session, carried by its access tokens as `sid`.
+    pub id: Uuid,
+    /// The `User-Agent` header sent at login, if any.
+    pub user_agent: Option<String>,
+    /// The client's IP address at login, taken from `Forwarded` or `X-Forwarded-For`
+    /// when present, if known.
+    pub ip_address: Option<String>,
+    /// Timestamp of the login or registration that started the session.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp of the session's last refresh or authenticated request. Requests
+    /// made within the revocation cache period of the previous one may not update it.
+    pub last_seen_at: DateTime<Utc>,
+    /// When the session ends unless it is refreshed.
+    pub expires_at: DateTime<Utc>,
+    /// Whether the request listing the sessions was made with this session.
+    pub current: bool,
+}
@@ -0,0 +1,118 @@
+use crate::models::TaskStatus;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::{Validate, ValidationError};
+
+/// What a status means for the rest of the application.
+/// Corresponds to the `status_category` SQL enum.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "status_category", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum StatusCategory {
+    /// Work has not started.
+    Todo,
+    /// Work is under way, including review.
+    Active,
+    /// Work is finished. Tasks in these statuses count as completed.
+    Done,
+}
+
+impl StatusCategory {
+    /// Returns `true` if moving a task into a status of this category requires all
+    /// of its blockers to be in a `Done` status first.
+    pub fn requires_unblocked(self) -> bool {
+        matches!(self, StatusCategory::Active | StatusCategory::Done)
+    }
+}
+
+/// The statuses every user starts with, which were the only statuses before they
+/// became configurable.
+pub const DEFAULT_STATUSES: [(&str, StatusCategory); 4] = [
+    ("todo", StatusCategory::Todo),
+    ("in_progress", StatusCategory::Active),
+    ("review", StatusCategory::Active),
+    ("done", StatusCategory::Done),
+];
+
+/// Returns the category of `name` if it is one of the `DEFAULT_STATUSES`.
+///
+/// Used to keep accepting the default names from users who renamed or removed them.
+pub fn legacy_category(name: &str) -> Option<StatusCategory> {
+    DEFAULT_STATUSES
+        .iter()
+        .find(|(legacy, _)| *legacy == name)
+        .map(|(_, category)| *category)
+}
+
+/// One of a user's task statuses.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct StatusDefinition {
+    /// The status name, unique per user. Tasks store it in their `status` field.
+    pub name: TaskStatus,
+    /// What the status means: not started, under way or finished.
+    pub category: StatusCategory,
+    /// Position of the status in listings, boards and `sort=status`, lowest first.
+    pub sort_order: i32,
+}
+
+/// Input structure for creating or updating a status.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct StatusInput {
+    /// The status name.
+    /// Must be between 1 and 50 characters of lowercase letters, digits and
+    /// underscores, starting with a letter.
+    #[validate(length(min = 1, max = 50), custom = "validate_status_name")]
+    pub name: String,
+    /// The status category.
+    pub category: StatusCategory,
+    /// Position of the status. New statuses go last by default, and updates keep
+    /// the current position unless it is given.
+    pub sort_order: Option<i32>,
+}
+
+fn validate_status_name(name: &str) -> Result<(), ValidationError> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ValidationError::new("status_name"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_category_requires_unblocked() {
+        assert!(!StatusCategory::Todo.requires_unblocked());
+        assert!(StatusCategory::Active.requires_unblocked());
+        assert!(StatusCategory::Done.requires_unblocked());
+    }
+
+    #[test]
+    fn test_legacy_category() {
+        assert_eq!(legacy_category("todo"), Some(StatusCategory::Todo));
+        assert_eq!(legacy_category("in_progress"), Some(StatusCategory::Active));
+        assert_eq!(legacy_category("review"), Some(StatusCategory::Active));
+        assert_eq!(legacy_category("done"), Some(StatusCategory::Done));
+        assert_eq!(legacy_category("qa"), None);
+    }
+
+    #[test]
+    fn test_status_input_validation() {
+        let input = |name: &str| StatusInput {
+            name: name.to_string(),
+            category: StatusCategory::Active,
+            sort_order: None,
+        };
+        assert!(input("qa").validate().is_ok());
+        assert!(input("waiting_on_2nd_review").validate().is_ok());
+        assert!(input("").validate().is_err());
+        assert!(input("QA").validate().is_err());
+        assert!(input("2nd_review").validate().is_err());
+        assert!(input("in progress").validate().is_err());
+        assert!(input(&"a".repeat(51)).validate().is_err());
+    }
+}
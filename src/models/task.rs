@@ -1,5 +1,7 @@
+use crate::models::label::{LabelMatch, TaskLabel};
+use crate::models::pagination::SortOrder;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
@@ -20,20 +22,112 @@ pub enum TaskPriority {
     Urgent,
 }
 
-/// Represents the status of a task.
-/// Corresponds to the `task_status` SQL enum.
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, sqlx::Type)]
-#[sqlx(type_name = "task_status", rename_all = "snake_case")]
+/// The name of a task's status.
+///
+/// Statuses are defined per user in the `task_statuses` table (see
+/// `StatusDefinition`), so any name deserializes; it is checked against the task
+/// owner's statuses when the task is written.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+#[serde(transparent)]
+pub struct TaskStatus(String);
+
+impl TaskPriority {
+    /// Returns the SQL enum label for this priority.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+            TaskPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl TaskStatus {
+    /// Creates a status name.
+    pub fn new(name: impl Into<String>) -> Self {
+        TaskStatus(name.into())
+    }
+
+    /// Returns the status name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The `sort_order` of a task's status, as an SQL expression on `tasks`.
+pub const STATUS_ORDER: &str = "(SELECT s.sort_order FROM task_statuses s \
+     WHERE s.user_id = tasks.user_id AND s.name = tasks.status)";
+
+/// Fields by which task listings can be sorted.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
-pub enum TaskStatus {
-    /// Task is yet to be started.
-    Todo,
-    /// Task is currently being worked on.
-    InProgress,
-    /// Task is completed and under review.
-    Review,
-    /// Task is completed.
-    Done,
+pub enum TaskSortField {
+    /// Sort by creation timestamp (default).
+    #[default]
+    CreatedAt,
+    /// Sort by last update timestamp.
+    UpdatedAt,
+    /// Sort by due date. Tasks without a due date sort last.
+    DueDate,
+    /// Sort by priority (`low` < `medium` < `high` < `urgent`). Tasks without a priority sort last.
+    Priority,
+    /// Sort by the `sort_order` of the task owner's status, which is
+    /// `todo` < `in_progress` < `review` < `done` for the default statuses.
+    Status,
+    /// Sort by full-text search rank. Only valid together with a full-text `search`;
+    /// this is the default sort whenever such a search is given.
+    Relevance,
+}
+
+impl TaskSortField {
+    /// Returns the `tasks` column, or for `Status` the expression, backing this sort field.
+    ///
+    /// `Relevance` is computed per query rather than stored, so it has no column
+    /// and callers must build the ranking expression themselves.
+    pub fn column(self) -> Option<&'static str> {
+        match self {
+            TaskSortField::CreatedAt => Some("created_at"),
+            TaskSortField::UpdatedAt => Some("updated_at"),
+            TaskSortField::DueDate => Some("due_date"),
+            TaskSortField::Priority => Some("priority"),
+            TaskSortField::Status => Some(STATUS_ORDER),
+            TaskSortField::Relevance => None,
+        }
+    }
+
+    /// Returns the SQL type used to cast a cursor value back into a comparable column value.
+    pub fn sql_type(self) -> &'static str {
+        match self {
+            TaskSortField::CreatedAt | TaskSortField::UpdatedAt | TaskSortField::DueDate => {
+                "timestamptz"
+            }
+            TaskSortField::Priority => "task_priority",
+            TaskSortField::Status => "integer",
+            TaskSortField::Relevance => "real",
+        }
+    }
+
+    /// Extracts this field's value from a listed task as a string suitable for a cursor.
+    /// Returns `None` when the underlying value is `NULL`.
+    pub fn cursor_value(self, item: &TaskListItem) -> Option<String> {
+        let task = &item.task;
+        match self {
+            TaskSortField::CreatedAt => Some(task.created_at.to_rfc3339()),
+            TaskSortField::UpdatedAt => Some(task.updated_at.to_rfc3339()),
+            TaskSortField::DueDate => task.due_date.map(|d| d.to_rfc3339()),
+            TaskSortField::Priority => task.priority.as_ref().map(|p| p.as_str().to_string()),
+            TaskSortField::Status => item.status_order.map(|o| o.to_string()),
+            TaskSortField::Relevance => item.rank.map(|r| r.to_string()),
+        }
+    }
 }
 
 /// Input structure for creating or updating a task.
@@ -56,8 +150,97 @@ pub struct TaskInput {
     /// Optional due date for the task.
     pub due_date: Option<DateTime<Utc>>,
 
-    /// The current status of the task.
+    /// The current status of the task: one of the owner's statuses, or one of the
+    /// default status names, which fall back to the owner's first status of the
+    /// same category if the owner no longer has them.
     pub status: TaskStatus,
+
+    /// Optional parent task, making this task a subtask of it.
+    pub parent_id: Option<Uuid>,
+}
+
+/// Partial update for a task with JSON Merge Patch (RFC 7396) semantics.
+///
+/// Each field distinguishes three cases: absent (`None`, leave the column
+/// unchanged), `null` (`Some(None)`, clear the column) and a value
+/// (`Some(Some(v))`, set the column). `title` and `status` are not nullable,
+/// so `null` is rejected for them.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TaskPatch {
+    /// New title. Must be between 1 and 200 characters.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<Option<String>>,
+
+    /// New description, or `null` to clear it. Maximum length of 1000 characters.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[validate(length(max = 1000))]
+    pub description: Option<Option<String>>,
+
+    /// New priority, or `null` to clear it.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub priority: Option<Option<TaskPriority>>,
+
+    /// New status.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub status: Option<Option<TaskStatus>>,
+
+    /// New due date, or `null` to clear it.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub due_date: Option<Option<DateTime<Utc>>>,
+
+    /// New assignee's user ID, or `null` to unassign the task.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub assigned_to: Option<Option<i32>>,
+
+    /// New parent task, or `null` to make the task top-level.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+impl TaskPatch {
+    /// Returns `true` if the patch does not touch any field.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.priority.is_none()
+            && self.status.is_none()
+            && self.due_date.is_none()
+            && self.assigned_to.is_none()
+            && self.parent_id.is_none()
+    }
+
+    /// Returns `true` if the patch touches no field other than `status`.
+    pub fn is_status_only(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.priority.is_none()
+            && self.due_date.is_none()
+            && self.assigned_to.is_none()
+            && self.parent_id.is_none()
+    }
+
+    /// Returns the name of the first non-nullable field set to `null`, if any.
+    pub fn null_required_field(&self) -> Option<&'static str> {
+        if matches!(self.title, Some(None)) {
+            Some("title")
+        } else if matches!(self.status, Some(None)) {
+            Some("status")
+        } else {
+            None
+        }
+    }
+}
+
+/// Deserializes a field that is present in the input (including as `null`) into `Some`.
+/// Combined with `#[serde(default)]`, absent fields become `None`.
+fn deserialize_present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
 
 /// Represents a task entity as stored in the database and returned by the API.
@@ -83,21 +266,152 @@ pub struct Task {
     pub user_id: i32,
     /// Identifier of the user to whom the task is assigned (optional).
     pub assigned_to: Option<i32>,
+    /// Identifier of the parent task, if this task is a subtask.
+    pub parent_id: Option<Uuid>,
+    /// Timestamp of when the task was moved to the trash, if it is there.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Timestamp of when the task last reached `done`, while it is done.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Timestamp of when the task was archived, if it is archived.
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Number of direct subtasks.
+    pub subtasks_total: i64,
+    /// Number of direct subtasks whose status is `done`.
+    pub subtasks_done: i64,
+    /// Number of comments on the task, replies included.
+    pub comment_count: i64,
+    /// Labels attached to the task, ordered by name.
+    #[sqlx(json)]
+    pub labels: Vec<TaskLabel>,
+}
+
+/// Which tasks a listing covers, relative to the authenticated user.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskScope {
+    /// Tasks the user owns.
+    Owned,
+    /// Tasks assigned to the user.
+    Assigned,
+    /// Tasks the user owns or is assigned to (default).
+    #[default]
+    All,
+}
+
+/// The authenticated user's relationship to a task they can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRole {
+    /// The user owns the task and has full edit and delete rights.
+    Owner,
+    /// The task is assigned to the user, who may change its status and add notes.
+    Assignee,
 }
 
 /// Represents query parameters for filtering tasks when listing them.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
+    /// Which tasks to list: `owned`, `assigned` or `all` (default).
+    pub scope: Option<TaskScope>,
     /// Filter tasks by status.
     pub status: Option<TaskStatus>,
     /// Filter tasks by priority.
     pub priority: Option<TaskPriority>,
     /// Filter tasks by assignee's user ID.
     pub assigned_to: Option<i32>,
-    /// Filter tasks by creator's user ID. (Note: listing tasks is already scoped to the authenticated user).
+    /// Filter tasks by parent task, listing its direct subtasks.
+    pub parent_id: Option<Uuid>,
+    /// Comma-separated label names; lists tasks carrying these labels.
+    pub labels: Option<String>,
+    /// Whether tasks need `any` (default) or `all` of the `labels`.
+    pub labels_match: Option<LabelMatch>,
+    /// Filter tasks by owner's user ID. Only useful with `scope=assigned` or `scope=all`,
+    /// as listings never include tasks the authenticated user neither owns nor is assigned to.
     pub user_id: Option<i32>,
-    /// Search term to filter tasks by title or description (case-insensitive).
+    /// Search term to filter tasks by title or description.
+    /// Interpreted as a `websearch_to_tsquery` expression unless `match=substring`.
     pub search: Option<String>,
+    /// How `search` is matched. Defaults to full-text search.
+    #[serde(rename = "match")]
+    pub match_mode: Option<SearchMatch>,
+    /// Maximum number of tasks to return (1-100, defaults to 50).
+    pub limit: Option<i64>,
+    /// Opaque cursor from a previous page's `next_cursor`.
+    pub cursor: Option<String>,
+    /// Field to sort by. Defaults to `created_at`.
+    pub sort: Option<TaskSortField>,
+    /// Sort direction. Defaults to `desc`.
+    pub order: Option<SortOrder>,
+    /// Whether to list archived tasks too. Defaults to `false`.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Matching strategies for the `search` query parameter.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMatch {
+    /// Postgres full-text search (`websearch_to_tsquery`), ranked by `ts_rank`. Uses the
+    /// `idx_tasks_search` GIN index.
+    #[default]
+    Fulltext,
+    /// Case-insensitive substring match (`ILIKE '%term%'`) on title and description.
+    Substring,
+}
+
+/// A task as returned by the listing endpoint.
+///
+/// Serializes as a plain `Task` with two extra fields that are only present
+/// for full-text searches.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaskListItem {
+    /// The task itself.
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    /// Full-text search rank (`ts_rank`) of the task for the current search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+    /// Highlighted snippet (`ts_headline`) of the matching text, with matches
+    /// wrapped in `<mark>`/`</mark>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headline: Option<String>,
+    /// The `sort_order` of the task's status, used for `sort=status` cursors.
+    #[serde(skip)]
+    pub status_order: Option<i32>,
+}
+
+/// Keyset cursor for task listings.
+///
+/// Encodes the sort key and `id` of the last task on a page, along with the
+/// sort settings it was produced for, so the next page can resume after it.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TaskCursor {
+    /// Sort field the cursor was produced for.
+    pub sort: TaskSortField,
+    /// Sort direction the cursor was produced for.
+    pub order: SortOrder,
+    /// Sort key of the last task on the page (`None` if the column was `NULL`).
+    pub value: Option<String>,
+    /// ID of the last task on the page, used as a tie-breaker.
+    pub id: Uuid,
+}
+
+/// Query parameters for paging through the trash.
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    /// Page size, between 1 and 100. Defaults to 50.
+    pub limit: Option<i64>,
+    /// The `next_cursor` value from a previous page.
+    pub cursor: Option<String>,
+}
+
+/// Keyset cursor for trash listings, which are ordered by deletion time.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TrashCursor {
+    /// When the last task on the page was deleted.
+    pub deleted_at: DateTime<Utc>,
+    /// ID of the last task on the page, used as a tie-breaker.
+    pub id: Uuid,
 }
 
 /// Request body for assigning a task to a user.
@@ -107,10 +421,18 @@ pub struct AssignTaskRequest {
     pub assignee_id: i32,
 }
 
+/// Request body for changing only a task's status.
+/// Available to both the task owner and its assignee.
+#[derive(Debug, Deserialize)]
+pub struct TaskStatusUpdate {
+    /// The new status of the task.
+    pub status: TaskStatus,
+}
+
 impl Task {
     /// Creates a new `Task` instance from `TaskInput` and the creator's `user_id`.
     /// Sets `created_at`, `updated_at` to the current time, and `id` to a new UUID.
-    /// `assigned_to` is initialized to `None`.
+    /// `assigned_to` is initialized to `None` and the subtask counts to zero.
     pub fn new(input: TaskInput, user_id_param: i32) -> Self {
         let now = Utc::now();
         Self {
@@ -124,8 +446,21 @@ impl Task {
             updated_at: now,
             user_id: user_id_param,
             assigned_to: None,
+            parent_id: input.parent_id,
+            deleted_at: None,
+            completed_at: None,
+            archived_at: None,
+            subtasks_total: 0,
+            subtasks_done: 0,
+            comment_count: 0,
+            labels: Vec::new(),
         }
     }
+
+    /// Returns `true` if `user_id` owns the task or is assigned to it.
+    pub fn is_visible_to(&self, user_id: i32) -> bool {
+        self.user_id == user_id || self.assigned_to == Some(user_id)
+    }
 }
 
 #[cfg(test)]
@@ -138,8 +473,9 @@ mod tests {
             title: "Test Task".to_string(),
             description: Some("Test Description".to_string()),
             priority: Some(TaskPriority::High),
-            status: TaskStatus::Todo,
+            status: TaskStatus::new("todo"),
             due_date: Some(Utc::now()),
+            parent_id: None,
         };
 
         let task = Task::new(input, 1);
@@ -154,8 +490,9 @@ mod tests {
             title: "Valid Task".to_string(),
             description: Some("Valid Description".to_string()),
             priority: Some(TaskPriority::High),
-            status: TaskStatus::Todo,
+            status: TaskStatus::new("todo"),
             due_date: Some(Utc::now()),
+            parent_id: None,
         };
         assert!(valid_input.validate().is_ok());
 
@@ -163,9 +500,54 @@ mod tests {
             title: "".to_string(), // Empty title
             description: Some("Valid Description".to_string()),
             priority: Some(TaskPriority::High),
-            status: TaskStatus::Todo,
+            status: TaskStatus::new("todo"),
             due_date: Some(Utc::now()),
+            parent_id: None,
         };
         assert!(invalid_input.validate().is_err());
     }
+
+    #[test]
+    fn test_task_patch_distinguishes_absent_and_null() {
+        let patch: TaskPatch =
+            serde_json::from_str(r#"{"description": null, "priority": "high", "status": "done"}"#)
+                .unwrap();
+        assert!(patch.title.is_none());
+        assert_eq!(patch.description, Some(None));
+        assert_eq!(patch.priority, Some(Some(TaskPriority::High)));
+        assert_eq!(patch.status, Some(Some(TaskStatus::new("done"))));
+        assert!(patch.due_date.is_none());
+        assert!(!patch.is_empty());
+        assert!(!patch.is_status_only());
+        assert!(patch.null_required_field().is_none());
+
+        let status_only: TaskPatch = serde_json::from_str(r#"{"status": "review"}"#).unwrap();
+        assert!(status_only.is_status_only());
+
+        let empty: TaskPatch = serde_json::from_str("{}").unwrap();
+        assert!(empty.is_empty());
+
+        let null_title: TaskPatch = serde_json::from_str(r#"{"title": null}"#).unwrap();
+        assert_eq!(null_title.null_required_field(), Some("title"));
+
+        assert!(serde_json::from_str::<TaskPatch>(r#"{"user_id": 5}"#).is_err());
+    }
+
+    #[test]
+    fn test_task_patch_validation() {
+        let empty_title: TaskPatch = serde_json::from_str(r#"{"title": ""}"#).unwrap();
+        assert!(empty_title.validate().is_err());
+
+        let long_description = TaskPatch {
+            description: Some(Some("b".repeat(1001))),
+            ..Default::default()
+        };
+        assert!(long_description.validate().is_err());
+
+        let cleared_description = TaskPatch {
+            description: Some(None),
+            ..Default::default()
+        };
+        assert!(cleared_description.validate().is_ok());
+    }
 }
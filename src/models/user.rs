@@ -15,6 +15,8 @@ pub struct User {
     pub email: String,
     /// Timestamp of when the user account was created.
     pub created_at: DateTime<Utc>,
+    /// Timestamp of when the user verified their email address, if they have.
+    pub email_verified_at: Option<DateTime<Utc>>,
 }
 
 /// Input structure for creating a new user (registration).
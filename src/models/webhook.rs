@@ -0,0 +1,184 @@
+use crate::models::TaskEventType;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// A URL a user has registered to receive task events.
+///
+/// A webhook receives the events its owner would see on the event stream, for the
+/// event types it subscribes to. It is disabled automatically after too many
+/// consecutive failed deliveries, and re-enabled by updating it with `active: true`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Webhook {
+    /// Unique identifier for the webhook.
+    pub id: Uuid,
+    /// The user who registered the webhook.
+    pub user_id: i32,
+    /// The URL events are POSTed to.
+    pub url: String,
+    /// The event types delivered to the webhook.
+    pub event_types: Vec<TaskEventType>,
+    /// Whether events are delivered. `false` once the webhook has been disabled.
+    pub active: bool,
+    /// Failed delivery attempts since the last successful one.
+    pub consecutive_failures: i32,
+    /// When the webhook was disabled automatically, if it was.
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Timestamp of when the webhook was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp of when the webhook was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A newly created webhook, along with the secret its deliveries are signed with.
+///
+/// The secret is only ever returned here.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookWithSecret {
+    /// The webhook.
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key of the HMAC-SHA256 signature sent in the `X-TaskForge-Signature` header.
+    pub secret: String,
+}
+
+/// Input structure for creating or updating a webhook.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct WebhookInput {
+    /// The `http` or `https` URL to POST events to.
+    #[validate(url, length(max = 2000), custom = "validate_webhook_url")]
+    pub url: String,
+    /// The event types to deliver. Must not be empty.
+    #[validate(length(min = 1))]
+    pub event_types: Vec<TaskEventType>,
+    /// Whether to deliver events. Defaults to `true`; setting it on a disabled
+    /// webhook re-enables it.
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn validate_webhook_url(url: &str) -> Result<(), ValidationError> {
+    let lower = url.to_ascii_lowercase();
+    if !lower.starts_with("http://") && !lower.starts_with("https://") {
+        return Err(ValidationError::new("webhook_url_scheme"));
+    }
+    Ok(())
+}
+
+/// State of a webhook delivery.
+/// Corresponds to the `webhook_delivery_status` SQL enum.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its first attempt or a retry.
+    Pending,
+    /// The receiver answered with a 2xx status.
+    Succeeded,
+    /// Every attempt failed; the delivery will not be retried automatically.
+    Failed,
+}
+
+/// An entry in a webhook's delivery log.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct WebhookDelivery {
+    /// Unique, increasing identifier for the delivery. Sent as `X-TaskForge-Delivery`.
+    pub id: i64,
+    /// The webhook the event is delivered to.
+    pub webhook_id: Uuid,
+    /// The activity log entry being delivered.
+    pub event_id: i64,
+    /// The event's type. Sent as `X-TaskForge-Event`.
+    pub event_type: TaskEventType,
+    /// The JSON body sent on every attempt: `{ "event": TaskEvent, "task": Task|null }`.
+    pub payload: serde_json::Value,
+    /// Whether the delivery is pending, succeeded or failed.
+    pub status: WebhookDeliveryStatus,
+    /// Number of attempts made so far.
+    pub attempts: i32,
+    /// When the next attempt is due, while the delivery is pending.
+    pub next_attempt_at: DateTime<Utc>,
+    /// When the last attempt was made.
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    /// HTTP status of the last response, if the receiver answered.
+    pub response_status: Option<i32>,
+    /// Why the last attempt failed.
+    pub last_error: Option<String>,
+    /// The delivery this one manually redelivers, if any.
+    pub redelivery_of: Option<i64>,
+    /// Timestamp of when the delivery was queued.
+    pub created_at: DateTime<Utc>,
+    /// When the receiver accepted the delivery.
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for paging through a webhook's delivery log.
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    /// Page size, between 1 and 100. Defaults to 50.
+    pub limit: Option<i64>,
+    /// The `next_cursor` value from a previous page.
+    pub cursor: Option<String>,
+    /// Only list deliveries in this state.
+    pub status: Option<WebhookDeliveryStatus>,
+}
+
+/// Keyset cursor for delivery listings: the `id` of the last delivery on a page.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WebhookDeliveryCursor {
+    /// ID of the last delivery on the page.
+    pub id: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(url: &str, event_types: Vec<TaskEventType>) -> WebhookInput {
+        WebhookInput {
+            url: url.to_string(),
+            event_types,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_webhook_input_validation() {
+        assert!(
+            input("https://ci.example.com/hook", vec![TaskEventType::Updated])
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            input("http://127.0.0.1:9000/hook", vec![TaskEventType::Created])
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            input("ftp://example.com/hook", vec![TaskEventType::Created])
+                .validate()
+                .is_err()
+        );
+        assert!(input("not a url", vec![TaskEventType::Created])
+            .validate()
+            .is_err());
+        assert!(input("https://example.com/hook", vec![])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_webhook_input_defaults_to_active() {
+        let input: WebhookInput =
+            serde_json::from_str(r#"{"url":"https://example.com","event_types":["deleted"]}"#)
+                .unwrap();
+        assert!(input.active);
+        assert_eq!(input.event_types, vec![TaskEventType::Deleted]);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::models::TaskStatus;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::{Validate, ValidationError};
+
+/// A status change allowed by a workflow, with the rules that apply to it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct WorkflowTransition {
+    /// The status the task is in.
+    #[sqlx(rename = "from_status")]
+    pub from: TaskStatus,
+    /// The status the task may move to.
+    #[sqlx(rename = "to_status")]
+    pub to: TaskStatus,
+    /// Whether the task must have an assignee to make this transition.
+    #[serde(default)]
+    pub requires_assignee: bool,
+    /// Whether only the task's owner, acting as reviewer, may make this transition.
+    /// The assignee of a task cannot approve their own work unless they also own it.
+    #[serde(default)]
+    pub requires_reviewer: bool,
+}
+
+/// The status workflow that applies to a user's tasks.
+///
+/// Users who have not defined a workflow get the default one, in which any of
+/// their statuses can move to any other without rules.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Workflow {
+    /// `false` while the user is on the default workflow.
+    pub custom: bool,
+    /// The allowed transitions, ordered by the `sort_order` of their `from` and `to` statuses.
+    pub transitions: Vec<WorkflowTransition>,
+}
+
+impl Workflow {
+    /// The workflow used when a user has not defined one: each of `statuses` can
+    /// move to every other, with no rules.
+    pub fn default_workflow(statuses: &[TaskStatus]) -> Self {
+        let mut transitions = Vec::new();
+        for from in statuses {
+            for to in statuses {
+                if from != to {
+                    transitions.push(WorkflowTransition {
+                        from: from.clone(),
+                        to: to.clone(),
+                        requires_assignee: false,
+                        requires_reviewer: false,
+                    });
+                }
+            }
+        }
+        Workflow {
+            custom: false,
+            transitions,
+        }
+    }
+
+    /// Returns the transition from `from` to `to`, if the workflow allows it.
+    pub fn transition(&self, from: &TaskStatus, to: &TaskStatus) -> Option<&WorkflowTransition> {
+        self.transitions
+            .iter()
+            .find(|t| t.from == *from && t.to == *to)
+    }
+
+    /// Returns the transitions out of `from`.
+    pub fn transitions_from<'a>(
+        &'a self,
+        from: &'a TaskStatus,
+    ) -> impl Iterator<Item = &'a WorkflowTransition> {
+        self.transitions.iter().filter(move |t| t.from == *from)
+    }
+}
+
+/// Input structure for defining a workflow, replacing the user's current one.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct WorkflowInput {
+    /// The allowed transitions. Must not be empty, and must not repeat a transition
+    /// or move a status to itself.
+    #[validate(length(min = 1), custom = "validate_transitions")]
+    pub transitions: Vec<WorkflowTransition>,
+}
+
+fn validate_transitions(transitions: &[WorkflowTransition]) -> Result<(), ValidationError> {
+    for (i, transition) in transitions.iter().enumerate() {
+        if transition.from == transition.to {
+            return Err(ValidationError::new("workflow_transition_to_same_status"));
+        }
+        if transitions[..i]
+            .iter()
+            .any(|t| t.from == transition.from && t.to == transition.to)
+        {
+            return Err(ValidationError::new("workflow_transition_duplicate"));
+        }
+    }
+    Ok(())
+}
+
+/// A transition out of a task's current status, as offered to a particular user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvailableTransition {
+    /// The transition and its rules.
+    #[serde(flatten)]
+    pub transition: WorkflowTransition,
+    /// Whether the user can make the transition right now.
+    pub allowed: bool,
+    /// Why the transition is not allowed, if it is not.
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(from: TaskStatus, to: TaskStatus) -> WorkflowTransition {
+        WorkflowTransition {
+            from,
+            to,
+            requires_assignee: false,
+            requires_reviewer: false,
+        }
+    }
+
+    #[test]
+    fn test_default_workflow_allows_every_change() {
+        let statuses = ["todo", "in_progress", "review", "done"].map(TaskStatus::new);
+        let workflow = Workflow::default_workflow(&statuses);
+        assert!(!workflow.custom);
+        assert_eq!(workflow.transitions.len(), 12);
+        assert!(workflow
+            .transition(&TaskStatus::new("done"), &TaskStatus::new("todo"))
+            .is_some());
+        assert!(workflow
+            .transition(&TaskStatus::new("done"), &TaskStatus::new("done"))
+            .is_none());
+        assert_eq!(
+            workflow
+                .transitions_from(&TaskStatus::new("review"))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_workflow_input_validation() {
+        let input = |transitions| WorkflowInput { transitions };
+        assert!(input(vec![
+            transition(TaskStatus::new("todo"), TaskStatus::new("in_progress")),
+            transition(TaskStatus::new("in_progress"), TaskStatus::new("done")),
+        ])
+        .validate()
+        .is_ok());
+        assert!(input(vec![]).validate().is_err());
+        assert!(input(vec![transition(
+            TaskStatus::new("todo"),
+            TaskStatus::new("todo")
+        )])
+        .validate()
+        .is_err());
+        assert!(input(vec![
+            transition(TaskStatus::new("todo"), TaskStatus::new("done")),
+            transition(TaskStatus::new("todo"), TaskStatus::new("done")),
+        ])
+        .validate()
+        .is_err());
+
+        let parsed: WorkflowTransition =
+            serde_json::from_str(r#"{"from":"review","to":"done","requires_reviewer":true}"#)
+                .unwrap();
+        assert!(parsed.requires_reviewer && !parsed.requires_assignee);
+    }
+}
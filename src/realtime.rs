@@ -0,0 +1,217 @@
+//! # Real-time Task Updates
+//!
+//! Task changes reach WebSocket clients in two hops:
+//! 1. `notify` is called by `routes::activity::record_event` in the transaction that
+//!    changes a task. It sends a small `TaskNotification` on the `task_events`
+//!    Postgres channel, which Postgres delivers only if the transaction commits.
+//! 2. Every server instance runs `run_listener`, which `LISTEN`s on that channel,
+//!    loads the event and the task's current state, and publishes a `TaskUpdate` to
+//!    its `TaskBroadcaster`. Each WebSocket connection (`routes::ws`) filters the
+//!    updates down to those its user can see and has subscribed to, as does each
+//!    event stream (`routes::events`).
+//!
+//! Because the fan-out goes through Postgres, a change made through any instance
+//! reaches the clients connected to all of them.
+//!
+//! Event stream clients that reconnect replay what they missed from `task_event_log`,
+//! which `run_event_log_pruner` keeps down to `Config::event_log_retention_hours`.
+
+use crate::{
+    error::AppError,
+    models::{realtime::TaskState, Task, TaskEvent},
+    routes::tasks::TASK_COLUMNS,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, PgConnection, PgPool};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Postgres channel task notifications are sent on.
+pub const TASK_EVENTS_CHANNEL: &str = "task_events";
+
+/// Number of updates a slow connection may fall behind before it starts missing them.
+const BROADCAST_CAPACITY: usize = 256;
+
+/// How long `run_listener` waits before reconnecting after an error.
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How often `run_event_log_pruner` removes expired entries.
+const EVENT_LOG_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Payload of a notification on `TASK_EVENTS_CHANNEL`.
+///
+/// Postgres limits payloads to 8000 bytes, so only the event's ID is sent along
+/// with what is needed to route it; listeners load the event itself.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskNotification {
+    /// ID of the `task_events` row.
+    pub event_id: i64,
+    /// The task that changed.
+    pub task_id: Uuid,
+    /// Users who could see the task before or after the change.
+    pub audience: Vec<i32>,
+    /// The task's state after the change, or before it for deletions.
+    pub state: TaskState,
+}
+
+/// A task change, ready to be delivered to WebSocket connections.
+#[derive(Debug)]
+pub struct TaskUpdate {
+    /// Users who may receive the update.
+    pub audience: Vec<i32>,
+    /// The task's state that subscription filters match against.
+    pub state: TaskState,
+    /// The activity log entry describing the change.
+    pub event: TaskEvent,
+    /// The task's current state, or `None` if it no longer exists.
+    pub task: Option<Task>,
+}
+
+impl TaskUpdate {
+    /// Returns the task's current state if `user_id` can still see it.
+    ///
+    /// Users who just lost access, such as a previous assignee, receive the event
+    /// without the task.
+    pub fn task_for(&self, user_id: i32) -> Option<&Task> {
+        self.task
+            .as_ref()
+            .filter(|task| task.is_visible_to(user_id))
+    }
+}
+
+/// Distributes task updates to the WebSocket connections of one server instance.
+pub struct TaskBroadcaster {
+    sender: broadcast::Sender<Arc<TaskUpdate>>,
+}
+
+impl TaskBroadcaster {
+    /// Creates a broadcaster with no connections.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+        Self { sender }
+    }
+
+    /// Returns a receiver for updates published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<TaskUpdate>> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an update to every current receiver.
+    pub fn publish(&self, update: TaskUpdate) {
+        // Sending only fails when nobody is connected, which is fine.
+        let _ = self.sender.send(Arc::new(update));
+    }
+
+    /// Returns `true` if any connection is listening.
+    pub fn has_receivers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+}
+
+impl Default for TaskBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queues a notification for `TASK_EVENTS_CHANNEL`. It is delivered when the
+/// surrounding transaction commits, and dropped if it rolls back.
+pub async fn notify(
+    conn: &mut PgConnection,
+    notification: &TaskNotification,
+) -> Result<(), AppError> {
+    let payload = serde_json::to_string(notification).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to encode notification: {}", e))
+    })?;
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(TASK_EVENTS_CHANNEL)
+        .bind(payload)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Loads the event and task behind a notification and publishes them.
+async fn publish_notification(
+    pool: &PgPool,
+    broadcaster: &TaskBroadcaster,
+    notification: TaskNotification,
+) -> Result<(), AppError> {
+    let event = sqlx::query_as::<_, TaskEvent>(
+        "SELECT id, task_id, actor_id, event_type, changes, created_at
+         FROM task_events WHERE id = $1",
+    )
+    .bind(notification.event_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(event) = event else {
+        return Ok(());
+    };
+    let task = sqlx::query_as::<_, Task>(&format!(
+        "SELECT {} FROM tasks WHERE id = $1 AND deleted_at IS NULL",
+        TASK_COLUMNS
+    ))
+    .bind(notification.task_id)
+    .fetch_optional(pool)
+    .await?;
+
+    broadcaster.publish(TaskUpdate {
+        audience: notification.audience,
+        state: notification.state,
+        event,
+        task,
+    });
+    Ok(())
+}
+
+/// Listens on `TASK_EVENTS_CHANNEL` and publishes each notification, until an error.
+async fn listen(pool: &PgPool, broadcaster: &TaskBroadcaster) -> Result<(), AppError> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(TASK_EVENTS_CHANNEL).await?;
+    loop {
+        let notification = listener.recv().await?;
+        if !broadcaster.has_receivers() {
+            continue;
+        }
+        match serde_json::from_str::<TaskNotification>(notification.payload()) {
+            Ok(notification) => publish_notification(pool, broadcaster, notification).await?,
+            Err(e) => log::warn!("Ignoring malformed task notification: {}", e),
+        }
+    }
+}
+
+/// Runs `listen` until the server stops, reconnecting after errors.
+pub async fn run_listener(pool: PgPool, broadcaster: Arc<TaskBroadcaster>) {
+    loop {
+        if let Err(e) = listen(&pool, &broadcaster).await {
+            log::error!("Task notification listener failed: {}", e);
+        }
+        actix_web::rt::time::sleep(LISTENER_RETRY_DELAY).await;
+    }
+}
+
+/// Removes `task_event_log` entries older than `retention_hours` and returns how
+/// many were removed. The events themselves stay in the activity log.
+pub async fn prune_event_log(pool: &PgPool, retention_hours: u32) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        "DELETE FROM task_event_log WHERE created_at < now() - make_interval(hours => $1)",
+    )
+    .bind(retention_hours as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Runs `prune_event_log` every `EVENT_LOG_PRUNE_INTERVAL` until the server stops.
+pub async fn run_event_log_pruner(pool: PgPool, retention_hours: u32) {
+    let mut interval = actix_web::rt::time::interval(EVENT_LOG_PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match prune_event_log(&pool, retention_hours).await {
+            Ok(0) => {}
+            Ok(removed) => log::info!("Pruned {} expired task event log entries", removed),
+            Err(e) => log::error!("Task event log pruning failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{
+        activity::{ActivityCursor, ActivityQuery},
+        pagination::{clamp_limit, decode_cursor, encode_cursor},
+        realtime::TaskState,
+        Page, Task, TaskEvent, TaskEventType,
+    },
+    realtime::{self, TaskNotification},
+    routes::tasks::{find_visible_task, touch_embedding_tasks},
+    webhooks,
+};
+use actix_web::{get, web, HttpResponse, Responder};
+use serde_json::{json, Map, Value};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Task fields whose changes are recorded in the activity log.
+const TRACKED_FIELDS: [&str; 8] = [
+    "title",
+    "description",
+    "priority",
+    "status",
+    "due_date",
+    "assigned_to",
+    "parent_id",
+    "archived_at",
+];
+
+/// Builds the `changes` object of an event: `{ "field": { "old": …, "new": … } }`
+/// for each tracked field that differs between `before` and `after`.
+///
+/// A missing side counts as all-`null`, so a creation lists the fields that were
+/// set and a deletion lists the fields the task had.
+pub(crate) fn task_diff(before: Option<&Task>, after: Option<&Task>) -> Map<String, Value> {
+    let fields = |task: Option<&Task>| match task.map(serde_json::to_value) {
+        Some(Ok(Value::Object(fields))) => fields,
+        _ => Map::new(),
+    };
+    let (before, after) = (fields(before), fields(after));
+
+    let mut changes = Map::new();
+    for field in TRACKED_FIELDS {
+        let old = before.get(field).cloned().unwrap_or(Value::Null);
+        let new = after.get(field).cloned().unwrap_or(Value::Null);
+        if old != new {
+            changes.insert(field.to_string(), json!({ "old": old, "new": new }));
+        }
+    }
+    changes
+}
+
+/// Appends an entry to a task's activity log.
+///
+/// `before` and `after` are the task as it was before and after the change; pass
+/// `None` for the side that does not exist on creation or deletion. Edits that
+/// leave every tracked field unchanged are not recorded. `actor_id` is `None` for
+/// changes the server makes on its own, such as auto-archiving.
+///
+/// Call this in the same transaction as the change, after locking the task with
+/// `lock_task`, so `before` cannot be overtaken by a concurrent write. The entry
+/// is also kept in `task_event_log` for event stream clients that reconnect, queued
+/// for the webhooks that subscribe to it, and announced to live clients through
+/// `realtime::notify` once the transaction commits. The `ETag`s of the parents and
+/// dependency neighbours that embed the task's details are refreshed as well.
+pub(crate) async fn record_event(
+    conn: &mut PgConnection,
+    actor_id: Option<i32>,
+    event_type: TaskEventType,
+    before: Option<&Task>,
+    after: Option<&Task>,
+) -> Result<(), AppError> {
+    let Some(task) = after.or(before) else {
+        return Ok(());
+    };
+    touch_embedding_tasks(conn, before, after).await?;
+    let changes = task_diff(before, after);
+    if changes.is_empty()
+        && !matches!(
+            event_type,
+            TaskEventType::Created | TaskEventType::Deleted | TaskEventType::Restored
+        )
+    {
+        return Ok(());
+    }
+
+    let event = sqlx::query_as::<_, TaskEvent>(
+        "INSERT INTO task_events (task_id, owner_id, actor_id, event_type, changes)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, task_id, actor_id, event_type, changes, created_at",
+    )
+    .bind(task.id)
+    .bind(task.user_id)
+    .bind(actor_id)
+    .bind(event_type)
+    .bind(Value::Object(changes))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Everyone who could see the task before or after the change hears about it.
+    let mut audience = vec![task.user_id];
+    for assignee in [before, after]
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.assigned_to)
+    {
+        if !audience.contains(&assignee) {
+            audience.push(assignee);
+        }
+    }
+    sqlx::query("INSERT INTO task_event_log (event_id, audience) VALUES ($1, $2)")
+        .bind(event.id)
+        .bind(&audience)
+        .execute(&mut *conn)
+        .await?;
+    webhooks::enqueue_deliveries(conn, &event, after, &audience).await?;
+    realtime::notify(
+        conn,
+        &TaskNotification {
+            event_id: event.id,
+            task_id: task.id,
+            audience,
+            state: TaskState::from(task),
+        },
+    )
+    .await
+}
+
+/// Lists the activity log of a task, newest first.
+///
+/// Available to the task's owner and its assignee. Once a task is deleted, the
+/// user who owned it at the time can still read its log.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Query Parameters:
+/// - `limit` (optional): Page size, between 1 and 100. Defaults to 50.
+/// - `cursor` (optional): The `next_cursor` value from a previous page.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON object `{ "items": [TaskEvent], "next_cursor": string|null, "has_more": bool }`.
+/// - `400 Bad Request`: If the cursor is malformed.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task is not visible to the authenticated user, or was
+///   deleted and not owned by them.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/activity")]
+pub async fn get_task_activity(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    query_params: web::Query<ActivityQuery>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let task_id = task_id.into_inner();
+    match find_visible_task(&**pool, task_id, user_id.0).await {
+        Ok(_) => {}
+        Err(AppError::NotFound(msg)) => {
+            let (deleted_by_owner,): (bool,) = sqlx::query_as(
+                "SELECT NOT EXISTS (SELECT 1 FROM tasks WHERE id = $1 AND deleted_at IS NULL)
+                    AND EXISTS (SELECT 1 FROM task_events
+                                WHERE task_id = $1 AND owner_id = $2 AND event_type = 'deleted')",
+            )
+            .bind(task_id)
+            .bind(user_id.0)
+            .fetch_one(&**pool)
+            .await?;
+            if !deleted_by_owner {
+                return Err(AppError::NotFound(msg));
+            }
+        }
+        Err(e) => return Err(e),
+    }
+
+    let limit = clamp_limit(query_params.limit);
+    let after_id = match &query_params.cursor {
+        Some(cursor) => Some(decode_cursor::<ActivityCursor>(cursor)?.id),
+        None => None,
+    };
+
+    let mut events = sqlx::query_as::<_, TaskEvent>(
+        "SELECT id, task_id, actor_id, event_type, changes, created_at
+         FROM task_events
+         WHERE task_id = $1 AND ($2::bigint IS NULL OR id < $2)
+         ORDER BY id DESC LIMIT $3",
+    )
+    .bind(task_id)
+    .bind(after_id)
+    .bind(limit + 1)
+    .fetch_all(&**pool)
+    .await?;
+
+    let has_more = events.len() as i64 > limit;
+    events.truncate(limit as usize);
+    let next_cursor = match events.last() {
+        Some(last) if has_more => Some(encode_cursor(&ActivityCursor { id: last.id })?),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(Page {
+        items: events,
+        next_cursor,
+        has_more,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TaskInput, TaskPriority, TaskStatus};
+
+    fn task(title: &str, status: TaskStatus) -> Task {
+        Task::new(
+            TaskInput {
+                title: title.to_string(),
+                description: None,
+                priority: Some(TaskPriority::Low),
+                status,
+                due_date: None,
+                parent_id: None,
+            },
+            1,
+        )
+    }
+
+    #[test]
+    fn test_task_diff_lists_only_changed_fields() {
+        let before = task("Draft", TaskStatus::new("todo"));
+        let mut after = task("Final", TaskStatus::new("done"));
+        after.priority = before.priority.clone();
+        after.assigned_to = Some(7);
+
+        let changes = task_diff(Some(&before), Some(&after));
+        assert_eq!(
+            Value::Object(changes),
+            json!({
+                "title": { "old": "Draft", "new": "Final" },
+                "status": { "old": "todo", "new": "done" },
+                "assigned_to": { "old": null, "new": 7 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_task_diff_on_create_and_delete() {
+        let created = task("New", TaskStatus::new("todo"));
+
+        let changes = task_diff(None, Some(&created));
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes["title"], json!({ "old": null, "new": "New" }));
+        assert!(!changes.contains_key("description"));
+
+        let changes = task_diff(Some(&created), None);
+        assert_eq!(changes["priority"], json!({ "old": "low", "new": null }));
+        assert!(task_diff(Some(&created), Some(&created)).is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{Task, TaskEventType},
+    routes::{
+        activity::record_event,
+        tasks::{
+            check_if_match, find_owned_task, lock_task, task_etag, write_miss_error, TASK_COLUMNS,
+        },
+    },
+};
+use actix_web::{http::header::ETag, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Archives or unarchives a task owned by `owner_id`, recording the change in its
+/// activity log.
+async fn set_archived(
+    req: &HttpRequest,
+    pool: &PgPool,
+    task_id: Uuid,
+    owner_id: i32,
+    archive: bool,
+) -> Result<HttpResponse, AppError> {
+    let task = find_owned_task(pool, task_id, owner_id).await?;
+    let expected_version = check_if_match(req, &task)?;
+
+    let mut tx = pool.begin().await?;
+    let before = lock_task(&mut tx, task_id).await?;
+    if before.archived_at.is_some() == archive {
+        return Err(AppError::Conflict(if archive {
+            "Task is already archived".into()
+        } else {
+            "Task is not archived".into()
+        }));
+    }
+    let updated_task = sqlx::query_as::<_, Task>(&format!(
+        "UPDATE tasks SET archived_at = CASE WHEN $4 THEN now() END
+         WHERE id = $1 AND user_id = $2 AND ($3::timestamptz IS NULL OR updated_at = $3)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
+    .bind(task_id)
+    .bind(owner_id)
+    .bind(expected_version)
+    .bind(archive)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| write_miss_error(expected_version))?;
+
+    let event_type = if archive {
+        TaskEventType::Archived
+    } else {
+        TaskEventType::Unarchived
+    };
+    record_event(
+        &mut tx,
+        Some(owner_id),
+        event_type,
+        Some(&before),
+        Some(&updated_task),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&updated_task)))
+        .json(updated_task))
+}
+
+/// Archives a task, hiding it from `GET /api/tasks` unless `include_archived=true`.
+///
+/// Archiving is independent of the task's status, and archived tasks can still be
+/// fetched, edited and commented on. The authenticated user must be the owner of the task.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object with `archived_at` set.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the task is already archived.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/archive")]
+pub async fn archive_task(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    set_archived(&req, &pool, task_id.into_inner(), user_id.0, true).await
+}
+
+/// Takes a task out of the archive, listing it in `GET /api/tasks` again.
+///
+/// The authenticated user must be the owner of the task. The task is not
+/// auto-archived again until it is next completed.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object with `archived_at` cleared.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the task is not archived.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/unarchive")]
+pub async fn unarchive_task(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    set_archived(&req, &pool, task_id.into_inner(), user_id.0, false).await
+}
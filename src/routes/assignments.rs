@@ -0,0 +1,89 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId, config::Config, error::AppError, models::TaskAssignment,
+    routes::tasks::find_visible_task,
+};
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Checks that tasks can be assigned to `assignee_id`.
+///
+/// The assignee must be an existing user, and with `restrict_unverified_accounts`
+/// on, one who has verified their email address.
+pub(crate) async fn ensure_assignable<'e, E: PgExecutor<'e>>(
+    executor: E,
+    config: &Config,
+    assignee_id: i32,
+) -> Result<(), AppError> {
+    let verified: Option<bool> =
+        sqlx::query_scalar("SELECT email_verified_at IS NOT NULL FROM users WHERE id = $1")
+            .bind(assignee_id)
+            .fetch_optional(executor)
+            .await?;
+    match verified {
+        None => Err(AppError::BadRequest("Assignee user not found".into())),
+        Some(false) if config.restrict_unverified_accounts => Err(AppError::Conflict(
+            "Tasks cannot be assigned to users who have not verified their email address".into(),
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Appends an entry to a task's assignment history if the assignee changed.
+///
+/// Lock the task with `lock_task` first so `previous_assignee_id` cannot be
+/// overtaken by a concurrent reassignment.
+pub(crate) async fn record_assignment(
+    conn: &mut PgConnection,
+    task_id: Uuid,
+    assigned_by: i32,
+    previous_assignee_id: Option<i32>,
+    assignee_id: Option<i32>,
+) -> Result<(), AppError> {
+    if previous_assignee_id == assignee_id {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO task_assignments (task_id, assigned_by, previous_assignee_id, assignee_id)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(task_id)
+    .bind(assigned_by)
+    .bind(previous_assignee_id)
+    .bind(assignee_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Lists the assignment history of a task, oldest first.
+///
+/// Available to the task's owner and its assignee.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `TaskAssignment` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/assignments")]
+pub async fn get_task_assignments(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let assignments = sqlx::query_as::<_, TaskAssignment>(
+        "SELECT id, task_id, assigned_by, previous_assignee_id, assignee_id, assigned_at
+         FROM task_assignments WHERE task_id = $1 ORDER BY assigned_at, id",
+    )
+    .bind(task.id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(assignments))
+}
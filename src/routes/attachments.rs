@@ -0,0 +1,476 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    config::Config,
+    error::AppError,
+    models::{TaskAttachment, TaskRole},
+    routes::tasks::find_visible_task,
+    storage::StorageBackend,
+};
+use actix_multipart::Multipart;
+use actix_web::{
+    delete, get,
+    http::{
+        header::{self, ContentDisposition, DispositionParam, DispositionType},
+        StatusCode,
+    },
+    post, web, HttpRequest, HttpResponse, Responder,
+};
+use futures::{StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::{cell::RefCell, rc::Rc};
+use uuid::Uuid;
+
+/// Columns selected for a `TaskAttachment`.
+const ATTACHMENT_COLUMNS: &str =
+    "id, task_id, uploaded_by, filename, content_type, size_bytes, sha256, storage_key, created_at";
+
+/// Longest accepted file name, matching the `filename` column.
+const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Which part of an attachment a download should return.
+#[derive(Debug, PartialEq, Eq)]
+enum RangeRequest {
+    /// The whole file, either because no range was asked for or because the
+    /// `Range` header is one this endpoint ignores (multiple ranges, bad syntax).
+    Full,
+    /// The bytes from `start` to `end`, inclusive.
+    Partial { start: u64, end: u64 },
+    /// A range that lies entirely outside the file.
+    Unsatisfiable,
+}
+
+/// Interprets a `Range` header (RFC 9110, section 14.2) against a file of `size` bytes.
+///
+/// Supports a single `bytes=start-end`, `bytes=start-` or `bytes=-suffix` range.
+fn parse_range(header: Option<&str>, size: u64) -> RangeRequest {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((first, last)) = spec.split_once('-') else {
+        return RangeRequest::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+
+    if first.is_empty() {
+        return match last.parse::<u64>() {
+            Ok(0) => RangeRequest::Unsatisfiable,
+            Ok(_) if size == 0 => RangeRequest::Unsatisfiable,
+            Ok(suffix) => RangeRequest::Partial {
+                start: size.saturating_sub(suffix),
+                end: size - 1,
+            },
+            Err(_) => RangeRequest::Full,
+        };
+    }
+
+    let Ok(start) = first.parse::<u64>() else {
+        return RangeRequest::Full;
+    };
+    let end = if last.is_empty() {
+        u64::MAX
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return RangeRequest::Full,
+        }
+    };
+    if start >= size {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Partial {
+        start,
+        end: end.min(size - 1),
+    }
+}
+
+/// The `ETag` of an attachment: its content digest, which never changes.
+fn attachment_etag(attachment: &TaskAttachment) -> String {
+    format!("\"{}\"", attachment.sha256)
+}
+
+/// Strips any directory part from an uploaded file name.
+fn sanitize_filename(name: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Loads an attachment of a task, or returns `AppError::NotFound`.
+async fn find_task_attachment(
+    pool: &PgPool,
+    task_id: Uuid,
+    attachment_id: Uuid,
+) -> Result<TaskAttachment, AppError> {
+    sqlx::query_as::<_, TaskAttachment>(&format!(
+        "SELECT {} FROM task_attachments WHERE id = $1 AND task_id = $2",
+        ATTACHMENT_COLUMNS
+    ))
+    .bind(attachment_id)
+    .bind(task_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Attachment not found".into()))
+}
+
+/// Total size of the attachments `user_id` has uploaded, in bytes.
+async fn quota_used<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+) -> Result<u64, AppError> {
+    let (used,): (i64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(size_bytes), 0)::BIGINT FROM task_attachments WHERE uploaded_by = $1",
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await?;
+    Ok(used as u64)
+}
+
+/// Lists the attachments of a task, oldest first.
+///
+/// Available to the task's owner and its assignee.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `TaskAttachment` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/attachments")]
+pub async fn get_task_attachments(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let attachments = sqlx::query_as::<_, TaskAttachment>(&format!(
+        "SELECT {} FROM task_attachments WHERE task_id = $1 ORDER BY created_at, id",
+        ATTACHMENT_COLUMNS
+    ))
+    .bind(task.id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(attachments))
+}
+
+/// Uploads a file to a task.
+///
+/// Available to the task's owner and its assignee. The request is a
+/// `multipart/form-data` body whose `file` field holds the file; other fields are
+/// ignored. The contents are streamed to the storage backend while their size and
+/// SHA-256 digest are computed.
+///
+/// Uploads larger than `MAX_ATTACHMENT_SIZE`, or that would take the uploader past
+/// `ATTACHMENT_QUOTA_PER_USER`, are rejected. The quota is checked again under a
+/// per-user lock before the attachment is recorded, so concurrent uploads cannot
+/// exceed it together.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `TaskAttachment` object as JSON.
+/// - `400 Bad Request`: If the body has no `file` field, is not valid multipart, or the file name is too long.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `413 Payload Too Large`: If the file exceeds the size limit or the user's quota.
+/// - `500 Internal Server Error`: For database, storage or other unexpected errors.
+#[post("/{id}/attachments")]
+pub async fn upload_attachment(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+    task_id: web::Path<Uuid>,
+    mut payload: Multipart,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let quota_left = config
+        .attachment_quota_per_user
+        .saturating_sub(quota_used(&**pool, user_id.0).await?);
+    let quota_error = || AppError::PayloadTooLarge("Attachment quota exceeded".into());
+    if quota_left == 0 {
+        return Err(quota_error());
+    }
+    let max_size = config.max_attachment_size;
+    let limit = max_size.min(quota_left);
+    let limit_error = move || {
+        if limit < max_size {
+            quota_error()
+        } else {
+            AppError::PayloadTooLarge(format!("Attachments are limited to {} bytes", limit))
+        }
+    };
+
+    let multipart_error = |e: actix_multipart::MultipartError| {
+        AppError::BadRequest(format!("Invalid multipart body: {}", e))
+    };
+    let field = loop {
+        match payload.try_next().await.map_err(multipart_error)? {
+            Some(field) if field.name() == Some("file") => break field,
+            Some(_) => continue,
+            None => return Err(AppError::BadRequest("Missing `file` field".into())),
+        }
+    };
+
+    let filename = field
+        .content_disposition()
+        .and_then(|cd| cd.get_filename())
+        .map(sanitize_filename)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "upload".to_string());
+    if filename.chars().count() > MAX_FILENAME_LENGTH {
+        return Err(AppError::BadRequest("File name is too long".into()));
+    }
+    let content_type = field
+        .content_type()
+        .map(|mime| mime.to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    // Hash and count the contents as they stream through to the backend.
+    let progress = Rc::new(RefCell::new((Sha256::new(), 0u64)));
+    let body = {
+        let progress = progress.clone();
+        field
+            .map(move |chunk| {
+                let chunk = chunk.map_err(multipart_error)?;
+                let mut progress = progress.borrow_mut();
+                progress.1 += chunk.len() as u64;
+                if progress.1 > limit {
+                    return Err(limit_error());
+                }
+                progress.0.update(&chunk);
+                Ok(chunk)
+            })
+            .boxed_local()
+    };
+
+    let attachment_id = Uuid::new_v4();
+    let storage_key = format!("attachments/{}", attachment_id);
+    storage.put(&storage_key, body).await?;
+    let (hasher, size) = Rc::try_unwrap(progress)
+        .map(RefCell::into_inner)
+        .unwrap_or_else(|shared| shared.replace((Sha256::new(), 0)));
+    let sha256 = hex::encode(hasher.finalize());
+
+    let recorded = async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('task_attachments'), $1)")
+            .bind(user_id.0)
+            .execute(&mut *tx)
+            .await?;
+        if quota_used(&mut *tx, user_id.0).await? + size > config.attachment_quota_per_user {
+            return Err(quota_error());
+        }
+        let attachment = sqlx::query_as::<_, TaskAttachment>(&format!(
+            "INSERT INTO task_attachments
+                 (id, task_id, uploaded_by, filename, content_type, size_bytes, sha256, storage_key)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING {}",
+            ATTACHMENT_COLUMNS
+        ))
+        .bind(attachment_id)
+        .bind(task.id)
+        .bind(user_id.0)
+        .bind(&filename)
+        .bind(&content_type)
+        .bind(size as i64)
+        .bind(&sha256)
+        .bind(&storage_key)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(attachment)
+    }
+    .await;
+
+    match recorded {
+        Ok(attachment) => Ok(HttpResponse::Created().json(attachment)),
+        Err(e) => {
+            if let Err(cleanup) = storage.delete(&storage_key).await {
+                log::warn!(
+                    "Failed to remove unrecorded upload {}: {}",
+                    storage_key,
+                    cleanup
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Downloads the contents of an attachment.
+///
+/// Available to the task's owner and its assignee. Supports a single-range
+/// `Range` header (`bytes=0-499`, `bytes=500-`, `bytes=-500`), answered with
+/// `206 Partial Content`, and `If-Range` against the attachment's `ETag`.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `attachment_id`: The UUID of the attachment.
+///
+/// ## Responses:
+/// - `200 OK`: The whole file, with its `Content-Type`, `Content-Disposition` and `ETag`.
+/// - `206 Partial Content`: The requested range, with a `Content-Range` header.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task or attachment does not exist or is not visible to the authenticated user.
+/// - `416 Range Not Satisfiable`: If the requested range starts past the end of the file.
+/// - `500 Internal Server Error`: For database, storage or other unexpected errors.
+#[get("/{id}/attachments/{attachment_id}")]
+pub async fn download_attachment(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+    user_id: AuthenticatedUserId,
+) -> Result<HttpResponse, AppError> {
+    let (task_id, attachment_id) = path.into_inner();
+    let (task, _role) = find_visible_task(&**pool, task_id, user_id.0).await?;
+    let attachment = find_task_attachment(&pool, task.id, attachment_id).await?;
+    let size = attachment.size_bytes as u64;
+    let etag = attachment_etag(&attachment);
+
+    let header_str = |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let range = match header_str(header::IF_RANGE) {
+        Some(if_range) if if_range != etag => RangeRequest::Full,
+        _ => parse_range(header_str(header::RANGE), size),
+    };
+
+    let (status, start, end) = match range {
+        RangeRequest::Full => (StatusCode::OK, 0, size.saturating_sub(1)),
+        RangeRequest::Partial { start, end } => (StatusCode::PARTIAL_CONTENT, start, end),
+        RangeRequest::Unsatisfiable => {
+            return Ok(HttpResponse::RangeNotSatisfiable()
+                .insert_header((header::CONTENT_RANGE, format!("bytes */{}", size)))
+                .finish());
+        }
+    };
+    let len = if size == 0 { 0 } else { end - start + 1 };
+
+    let body = storage.get(&attachment.storage_key, start, len).await?;
+    let mut response = HttpResponse::build(status);
+    response
+        .insert_header((header::CONTENT_TYPE, attachment.content_type.as_str()))
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(attachment.filename.clone())],
+        })
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header((header::ETAG, etag))
+        .no_chunking(len);
+    if status == StatusCode::PARTIAL_CONTENT {
+        response.insert_header((
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, size),
+        ));
+    }
+    Ok(response.streaming(body))
+}
+
+/// Deletes an attachment and its stored contents.
+///
+/// The uploader and the task's owner may delete it.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `attachment_id`: The UUID of the attachment.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful deletion.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is neither the uploader nor the task's owner.
+/// - `404 Not Found`: If the task or attachment does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}/attachments/{attachment_id}")]
+pub async fn delete_attachment(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    path: web::Path<(Uuid, Uuid)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task_id, attachment_id) = path.into_inner();
+    let (task, role) = find_visible_task(&**pool, task_id, user_id.0).await?;
+    let attachment = find_task_attachment(&pool, task.id, attachment_id).await?;
+    if attachment.uploaded_by != user_id.0 && role != TaskRole::Owner {
+        return Err(AppError::Forbidden(
+            "Only the uploader or the task owner can delete this attachment".into(),
+        ));
+    }
+
+    // Deleting the row queues the object in `storage_orphans`; remove it right
+    // away and leave it to the sweeper only if that fails.
+    sqlx::query("DELETE FROM task_attachments WHERE id = $1")
+        .bind(attachment.id)
+        .execute(&**pool)
+        .await?;
+    match storage.delete(&attachment.storage_key).await {
+        Ok(()) => {
+            sqlx::query("DELETE FROM storage_orphans WHERE storage_key = $1")
+                .bind(&attachment.storage_key)
+                .execute(&**pool)
+                .await?;
+        }
+        Err(e) => log::warn!(
+            "Failed to remove attachment object {}: {}",
+            attachment.storage_key,
+            e
+        ),
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let partial = |start, end| RangeRequest::Partial { start, end };
+
+        assert_eq!(parse_range(None, 100), RangeRequest::Full);
+        assert_eq!(parse_range(Some("bytes=0-9"), 100), partial(0, 9));
+        assert_eq!(parse_range(Some("bytes=90-"), 100), partial(90, 99));
+        assert_eq!(parse_range(Some("bytes=90-500"), 100), partial(90, 99));
+        assert_eq!(parse_range(Some("bytes=-10"), 100), partial(90, 99));
+        assert_eq!(parse_range(Some("bytes=-500"), 100), partial(0, 99));
+
+        assert_eq!(
+            parse_range(Some("bytes=100-"), 100),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-0"), 100),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-5"), 0),
+            RangeRequest::Unsatisfiable
+        );
+
+        // Ranges this endpoint does not serve fall back to the whole file.
+        assert_eq!(parse_range(Some("bytes=0-1,5-6"), 100), RangeRequest::Full);
+        assert_eq!(parse_range(Some("bytes=9-0"), 100), RangeRequest::Full);
+        assert_eq!(parse_range(Some("items=0-9"), 100), RangeRequest::Full);
+        assert_eq!(parse_range(Some("bytes=abc"), 100), RangeRequest::Full);
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\notes.txt"), "notes.txt");
+        assert_eq!(sanitize_filename("dir/"), "");
+    }
+}
@@ -1,13 +1,98 @@
 use crate::{
     auth::{
-        generate_token, hash_password, verify_password, AuthResponse, LoginRequest, RegisterRequest,
+        extractors::{AuthenticatedClaims, AuthenticatedUserId},
+        generate_token, hash_password, issue_email_verification_token, issue_password_reset_token,
+        issue_refresh_token, redeem_password_reset_token,
+        revocation::revoke_all_tokens,
+        rotate_refresh_token,
+        session::{create_session, ClientInfo},
+        verify_email, verify_password, AuthResponse, ForgotPasswordRequest, LoginRequest,
+        RefreshRequest, RegisterRequest, ResetPasswordRequest, VerifyEmailRequest,
+        TOKEN_REVOCATIONS,
     },
+    config::Config,
     error::AppError,
+    mail::{queue_email, Email},
+    models::Session,
 };
-use actix_web::{post, web, HttpResponse, Responder};
-use sqlx::PgPool;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
 use validator::Validate;
 
+/// Builds the `AuthResponse` for `user_id` around `refresh_token`, with a new access
+/// token for `session_id` carrying the user's current token generation.
+async fn auth_response(
+    pool: &PgPool,
+    config: &Config,
+    user_id: i32,
+    session_id: Uuid,
+    refresh_token: String,
+) -> Result<AuthResponse, AppError> {
+    let (generation, email_verified): (i32, bool) = sqlx::query_as(
+        "SELECT token_generation, email_verified_at IS NOT NULL FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(AuthResponse {
+        token: generate_token(user_id, session_id, generation, config.access_token_ttl())?,
+        expires_in: config.access_token_ttl().num_seconds(),
+        refresh_token,
+        user_id,
+        email_verified,
+    })
+}
+
+/// Records a new session for `user_id`, signed in from `req`, and issues its
+/// access token and first refresh token.
+async fn start_session(
+    pool: &PgPool,
+    config: &Config,
+    req: &HttpRequest,
+    user_id: i32,
+) -> Result<AuthResponse, AppError> {
+    let expires_at = Utc::now() + config.refresh_token_ttl();
+    let mut tx = pool.begin().await?;
+    let session_id = create_session(
+        &mut *tx,
+        user_id,
+        &ClientInfo::from_request(req, &config.trusted_proxies),
+        expires_at,
+    )
+    .await?;
+    let refresh_token = issue_refresh_token(&mut *tx, user_id, session_id, expires_at).await?;
+    tx.commit().await?;
+
+    auth_response(pool, config, user_id, session_id, refresh_token).await
+}
+
+/// Issues an email verification token for `user_id` and queues the email carrying
+/// its link to `email`.
+async fn send_verification_email(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: i32,
+    email: &str,
+    username: &str,
+) -> Result<(), AppError> {
+    let expires_at = Utc::now() + config.email_verification_ttl();
+    let token = issue_email_verification_token(&mut *conn, user_id, expires_at).await?;
+    let email = Email {
+        to: email.to_string(),
+        subject: "Verify your TaskForge email address".to_string(),
+        body: format!(
+            "Hi {},\n\n\
+             Please confirm that this is your email address by opening this link within {} hours:\n\n\
+             {}/verify-email?token={}\n\n\
+             If you did not create a TaskForge account, you can ignore this email.\n",
+            username, config.email_verification_ttl_hours, config.app_url, token
+        ),
+    };
+    queue_email(&mut *conn, &email).await
+}
+
 /// Registers a new user.
 ///
 /// This endpoint handles user registration. It expects a JSON payload with
@@ -17,12 +102,13 @@ use validator::Validate;
 /// 1. Validates the input data (`RegisterRequest`).
 /// 2. Checks if a user with the given email already exists.
 /// 3. Hashes the provided password.
-/// 4. Inserts the new user into the database.
-/// 5. Generates a JWT authentication token for the new user.
+/// 4. Inserts the new user into the database, with their email not yet verified,
+///    and queues an email with a verification link.
+/// 5. Records a session and generates its access token and refresh token.
 ///
 /// ## Responses:
 /// - `201 Created`: On successful registration, returns an `AuthResponse`
-///   containing the JWT token and user ID.
+///   containing the access token, refresh token and user ID.
 /// - `400 Bad Request`: If the email is already registered or for other
 ///   general request issues.
 /// - `422 Unprocessable Entity`: If input validation fails (e.g., invalid email format,
@@ -30,7 +116,9 @@ use validator::Validate;
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[post("/register")]
 pub async fn register(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     register_data: web::Json<RegisterRequest>,
 ) -> Result<impl Responder, AppError> {
     // Validate input
@@ -48,23 +136,30 @@ pub async fn register(
     // Hash password
     let password_hash = hash_password(&register_data.password)?;
 
-    // Insert new user
+    // Insert new user and send the verification link
+    let mut tx = pool.begin().await?;
     let user = sqlx::query!(
         "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id",
         register_data.username,
         register_data.email,
         password_hash
     )
-    .fetch_one(&**pool)
+    .fetch_one(&mut *tx)
     .await?;
+    send_verification_email(
+        &mut tx,
+        &config,
+        user.id,
+        &register_data.email,
+        &register_data.username,
+    )
+    .await?;
+    tx.commit().await?;
 
-    // Generate token
-    let token = generate_token(user.id)?;
+    // Generate tokens
+    let response = start_session(&pool, &config, &req, user.id).await?;
 
-    Ok(HttpResponse::Created().json(AuthResponse {
-        token,
-        user_id: user.id,
-    }))
+    Ok(HttpResponse::Created().json(response))
 }
 
 /// Logs in an existing user.
@@ -76,17 +171,20 @@ pub async fn register(
 /// 1. Validates the input data (`LoginRequest`).
 /// 2. Retrieves the user from the database based on the email.
 /// 3. Verifies the provided password against the stored hash.
-/// 4. If authentication is successful, generates a JWT authentication token.
+/// 4. If authentication is successful, records a session and generates its access
+///    token and refresh token.
 ///
 /// ## Responses:
 /// - `200 OK`: On successful login, returns an `AuthResponse` containing
-///   the JWT token and user ID.
+///   the access token, refresh token and user ID.
 /// - `401 Unauthorized`: If credentials (email or password) are invalid.
 /// - `422 Unprocessable Entity`: If input validation fails (e.g., invalid email format).
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[post("/login")]
 pub async fn login(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     login_data: web::Json<LoginRequest>,
 ) -> Result<impl Responder, AppError> {
     // Validate input
@@ -104,12 +202,9 @@ pub async fn login(
         Some(user) => {
             // Verify password
             if verify_password(&login_data.password, &user.password_hash)? {
-                // Generate token
-                let token = generate_token(user.id)?;
-                Ok(HttpResponse::Ok().json(AuthResponse {
-                    token,
-                    user_id: user.id,
-                }))
+                // Generate tokens
+                let response = start_session(&pool, &config, &req, user.id).await?;
+                Ok(HttpResponse::Ok().json(response))
             } else {
                 Err(AppError::Unauthorized("Invalid credentials".into()))
             }
@@ -118,6 +213,306 @@ pub async fn login(
     }
 }
 
+/// Exchanges a refresh token for a new access token and refresh token.
+///
+/// The presented refresh token is used up: the response carries its replacement, in
+/// the same session, which is extended by `REFRESH_TOKEN_TTL_DAYS`. Presenting a
+/// refresh token that was already exchanged revokes its whole session, so a stolen
+/// token stops working for both the thief and the legitimate client, who has to log
+/// in again.
+///
+/// ## Request Body:
+/// A JSON object matching `RefreshRequest`:
+///   ```json
+///   { "refresh_token": "..." }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns an `AuthResponse` with the new access token and refresh token.
+/// - `400 Bad Request`: If the body is not a valid `RefreshRequest`.
+/// - `401 Unauthorized`: If the refresh token is unknown, expired, revoked or was
+///   already used.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/refresh")]
+pub async fn refresh(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    refresh_data: web::Json<RefreshRequest>,
+) -> Result<impl Responder, AppError> {
+    let rotated = rotate_refresh_token(
+        &pool,
+        &refresh_data.refresh_token,
+        config.refresh_token_ttl(),
+    )
+    .await?;
+
+    let response = auth_response(
+        &pool,
+        &config,
+        rotated.user_id,
+        rotated.session_id,
+        rotated.token,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Logs out the authenticated session.
+///
+/// The session the request's access token was issued for is revoked at once, with
+/// all of its access and refresh tokens. Other sessions of the user are not affected.
+///
+/// ## Responses:
+/// - `204 No Content`: The session is logged out.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/logout")]
+pub async fn logout(
+    pool: web::Data<PgPool>,
+    claims: AuthenticatedClaims,
+) -> Result<impl Responder, AppError> {
+    TOKEN_REVOCATIONS.revoke(&pool, &claims.0).await?;
+    TOKEN_REVOCATIONS
+        .revoke_session(&pool, claims.0.sub, claims.0.sid)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Logs out every session of the authenticated user, on every device.
+///
+/// Bumps the user's token generation, which revokes every access token issued so
+/// far, and revokes all of the user's sessions and refresh tokens. The user has to
+/// log in again everywhere, including with the client making this request.
+///
+/// ## Responses:
+/// - `204 No Content`: Every session is logged out.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/logout-all")]
+pub async fn logout_all(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    TOKEN_REVOCATIONS.revoke_all(&pool, user_id.0).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lists the sessions the authenticated user is signed in with.
+///
+/// Logged-out, revoked and expired sessions are left out.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `Session` objects, most recently seen first.
+///   The session of the request's own access token has `current` set.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/sessions")]
+pub async fn get_sessions(
+    pool: web::Data<PgPool>,
+    claims: AuthenticatedClaims,
+) -> Result<impl Responder, AppError> {
+    let sessions = sqlx::query_as::<_, Session>(
+        "SELECT id, user_agent, ip_address, created_at, last_seen_at, expires_at,
+                id = $2 AS current
+         FROM sessions
+         WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > now()
+         ORDER BY last_seen_at DESC, created_at DESC",
+    )
+    .bind(claims.0.sub)
+    .bind(claims.0.sid)
+    .fetch_all(&**pool)
+    .await?;
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// Revokes one of the authenticated user's sessions, signing that device out.
+///
+/// The session's access tokens are rejected by `AuthMiddleware` from the next request
+/// on, and its refresh tokens stop working. Revoking the current session logs out.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the session.
+///
+/// ## Responses:
+/// - `204 No Content`: The session is revoked.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the user has no such session, or it was already revoked.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/sessions/{id}")]
+pub async fn delete_session(
+    pool: web::Data<PgPool>,
+    session_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    if !TOKEN_REVOCATIONS
+        .revoke_session(&pool, user_id.0, session_id.into_inner())
+        .await?
+    {
+        return Err(AppError::NotFound("Session not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Builds the email carrying the password reset link for `token`.
+fn password_reset_email(config: &Config, to: String, username: &str, token: &str) -> Email {
+    Email {
+        to,
+        subject: "Reset your TaskForge password".to_string(),
+        body: format!(
+            "Hi {},\n\n\
+             Someone asked to reset the password of your TaskForge account. To choose a new \
+             password, open this link within {} minutes:\n\n\
+             {}/reset-password?token={}\n\n\
+             If you did not ask for this, you can ignore this email; your password stays the same.\n",
+            username, config.password_reset_ttl_minutes, config.app_url, token
+        ),
+    }
+}
+
+/// Starts a password reset.
+///
+/// If an account uses the given email, a single-use reset link valid for
+/// `PASSWORD_RESET_TTL_MINUTES` is emailed to it. The response is the same either
+/// way, so it does not reveal whether the email is registered, and the email is
+/// sent in the background by the mail worker (see `crate::mail`).
+///
+/// ## Request Body:
+/// A JSON object matching `ForgotPasswordRequest`:
+///   ```json
+///   { "email": "user@example.com" }
+///   ```
+///
+/// ## Responses:
+/// - `202 Accepted`: The request was accepted, whether or not the email is registered.
+/// - `422 Unprocessable Entity`: If the email is not a valid email address.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/password/forgot")]
+pub async fn forgot_password(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    forgot_data: web::Json<ForgotPasswordRequest>,
+) -> Result<impl Responder, AppError> {
+    forgot_data.validate()?;
+
+    let mut tx = pool.begin().await?;
+    let user: Option<(i32, String)> =
+        sqlx::query_as("SELECT id, username FROM users WHERE email = $1")
+            .bind(&forgot_data.email)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if let Some((user_id, username)) = user {
+        let expires_at = Utc::now() + config.password_reset_ttl();
+        let token = issue_password_reset_token(&mut *tx, user_id, expires_at).await?;
+        let email =
+            password_reset_email(&config, forgot_data.into_inner().email, &username, &token);
+        queue_email(&mut *tx, &email).await?;
+    }
+    tx.commit().await?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Sets a new password with the token from a password reset email.
+///
+/// The token, and every other reset token of the user, is used up. Every session
+/// of the user is logged out, as with `POST /api/auth/logout-all`, so they have to
+/// log in again with the new password everywhere.
+///
+/// ## Request Body:
+/// A JSON object matching `ResetPasswordRequest`:
+///   ```json
+///   { "token": "...", "password": "new password" }
+///   ```
+///
+/// ## Responses:
+/// - `204 No Content`: The password was changed.
+/// - `400 Bad Request`: If the token is unknown, expired or already used.
+/// - `422 Unprocessable Entity`: If the new password is too short.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/password/reset")]
+pub async fn reset_password(
+    pool: web::Data<PgPool>,
+    reset_data: web::Json<ResetPasswordRequest>,
+) -> Result<impl Responder, AppError> {
+    reset_data.validate()?;
+    let password_hash = hash_password(&reset_data.password)?;
+
+    let mut tx = pool.begin().await?;
+    let user_id = redeem_password_reset_token(&mut tx, &reset_data.token)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Invalid or expired reset token".into()))?;
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(&password_hash)
+        .execute(&mut *tx)
+        .await?;
+    revoke_all_tokens(&mut tx, user_id).await?;
+    tx.commit().await?;
+    TOKEN_REVOCATIONS.user_revoked(user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Verifies the email address of an account with the token from a verification email.
+///
+/// The token works without being logged in, since the link may be opened on another
+/// device. The token, and every other verification token of the user, is used up.
+///
+/// ## Request Body:
+/// A JSON object matching `VerifyEmailRequest`:
+///   ```json
+///   { "token": "..." }
+///   ```
+///
+/// ## Responses:
+/// - `204 No Content`: The email address is verified.
+/// - `400 Bad Request`: If the token is unknown, expired or already used.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/email/verify")]
+pub async fn verify_email_address(
+    pool: web::Data<PgPool>,
+    verify_data: web::Json<VerifyEmailRequest>,
+) -> Result<impl Responder, AppError> {
+    let mut tx = pool.begin().await?;
+    verify_email(&mut tx, &verify_data.token)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Invalid or expired verification token".into()))?;
+    tx.commit().await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Sends the authenticated user a new email verification link.
+///
+/// Links sent earlier keep working until they expire.
+///
+/// ## Responses:
+/// - `202 Accepted`: The email is queued.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `409 Conflict`: If the user's email address is already verified.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/email/resend")]
+pub async fn resend_verification_email(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let mut tx = pool.begin().await?;
+    let (email, username, verified): (String, String, bool) = sqlx::query_as(
+        "SELECT email, username, email_verified_at IS NOT NULL FROM users WHERE id = $1",
+    )
+    .bind(user_id.0)
+    .fetch_one(&mut *tx)
+    .await?;
+    if verified {
+        return Err(AppError::Conflict(
+            "Email address is already verified".into(),
+        ));
+    }
+    send_verification_email(&mut tx, &config, user_id.0, &email, &username).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
 #[cfg(test)]
 mod tests {
     // Cleaned up imports for pure DTO validation tests
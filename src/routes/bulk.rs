@@ -0,0 +1,260 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    config::Config,
+    error::AppError,
+    models::{
+        BulkMode, BulkOperation, BulkOperationResult, BulkRequest, BulkResponse, Task,
+        TaskEventType, TaskRole,
+    },
+    routes::{
+        activity::record_event,
+        assignments::{ensure_assignable, record_assignment},
+        subtasks::{lock_hierarchy, validate_parent},
+        tasks::{
+            check_status_change, find_owned_task, find_visible_task, insert_task, lock_task,
+            TASK_COLUMNS,
+        },
+    },
+};
+use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
+use sqlx::{Connection, PgConnection, PgPool};
+use validator::Validate;
+
+/// Applies several task operations in one request and one database transaction.
+///
+/// Each operation gets the same validation and ownership checks as the
+/// corresponding single-task endpoint (`POST /api/tasks`, `PUT /api/tasks/{id}/status`,
+/// `POST /api/tasks/{id}/assign`, `DELETE /api/tasks/{id}`), and runs in its own
+/// savepoint so one failure does not abort the others.
+///
+/// In `atomic` mode (the default) the transaction is rolled back if any operation
+/// fails. In `partial` mode successful operations are committed regardless.
+///
+/// ## Request Body:
+/// A JSON object matching `BulkRequest`:
+///   ```json
+///   {
+///     "mode": "partial",
+///     "operations": [
+///       { "op": "create", "title": "Write docs", "status": "todo" },
+///       { "op": "update", "id": "…", "status": "done", "priority": "low" },
+///       { "op": "assign", "id": "…", "assignee_id": 2 },
+///       { "op": "delete", "id": "…" }
+///     ]
+///   }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns a `BulkResponse` with one result per operation. In `partial`
+///   mode this is returned even if some operations failed.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `422 Unprocessable Entity`: If the request has no operations or more than 100,
+///   or if an `atomic` request was rolled back; the body is then a `BulkResponse`
+///   with `committed: false`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/bulk")]
+pub async fn bulk_tasks(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    bulk_data: web::Json<BulkRequest>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    bulk_data.validate()?;
+    let BulkRequest { mode, operations } = bulk_data.into_inner();
+
+    let mut tx = pool.begin().await?;
+    // Creating subtasks takes the hierarchy lock, which must come before any task
+    // row lock an earlier operation would take.
+    if operations
+        .iter()
+        .any(|op| matches!(op, BulkOperation::Create(task) if task.parent_id.is_some()))
+    {
+        lock_hierarchy(&mut tx, user_id.0).await?;
+    }
+    let mut results = Vec::with_capacity(operations.len());
+    for (index, operation) in operations.into_iter().enumerate() {
+        let mut savepoint = tx.begin().await?;
+        let result = match apply_operation(&mut savepoint, &config, user_id.0, operation).await {
+            Ok((status, task)) => {
+                savepoint.commit().await?;
+                BulkOperationResult {
+                    index,
+                    status: status.as_u16(),
+                    task,
+                    error: None,
+                }
+            }
+            Err(error) => {
+                savepoint.rollback().await?;
+                failed_result(index, error)?
+            }
+        };
+        results.push(result);
+    }
+
+    let failed = results.iter().any(|result| result.error.is_some());
+    if mode == BulkMode::Atomic && failed {
+        tx.rollback().await?;
+        return Ok(HttpResponse::UnprocessableEntity().json(BulkResponse {
+            mode,
+            committed: false,
+            results,
+        }));
+    }
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(BulkResponse {
+        mode,
+        committed: true,
+        results,
+    }))
+}
+
+/// Turns the error of a failed operation into its per-item result.
+///
+/// Server-side errors are not specific to one operation, so they abort the whole
+/// request instead.
+fn failed_result(index: usize, error: AppError) -> Result<BulkOperationResult, AppError> {
+    let message = match &error {
+        AppError::Unauthorized(msg)
+        | AppError::BadRequest(msg)
+        | AppError::Forbidden(msg)
+        | AppError::NotFound(msg)
+        | AppError::Conflict(msg)
+        | AppError::InvalidTransition(msg)
+        | AppError::PreconditionFailed(msg)
+        | AppError::PayloadTooLarge(msg)
+        | AppError::ValidationError(msg) => msg.clone(),
+        AppError::InternalServerError(_) | AppError::DatabaseError(_) => return Err(error),
+    };
+    Ok(BulkOperationResult {
+        index,
+        status: actix_web::ResponseError::error_response(&error)
+            .status()
+            .as_u16(),
+        task: None,
+        error: Some(message),
+    })
+}
+
+/// Applies one bulk operation, returning the status and task the single-task
+/// endpoint would have responded with.
+async fn apply_operation(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: i32,
+    operation: BulkOperation,
+) -> Result<(StatusCode, Option<Task>), AppError> {
+    match operation {
+        BulkOperation::Create(task_data) => {
+            task_data.validate()?;
+            if let Some(parent_id) = task_data.parent_id {
+                validate_parent(conn, None, parent_id, user_id, config.max_task_depth).await?;
+            }
+            let task = insert_task(&mut *conn, task_data, user_id).await?;
+            record_event(
+                conn,
+                Some(user_id),
+                TaskEventType::Created,
+                None,
+                Some(&task),
+            )
+            .await?;
+            Ok((StatusCode::CREATED, Some(task)))
+        }
+        BulkOperation::Update {
+            id,
+            status,
+            priority,
+        } => {
+            if status.is_none() && priority.is_none() {
+                return Err(AppError::ValidationError(
+                    "update: at least one of status or priority is required".into(),
+                ));
+            }
+            let (task, role) = find_visible_task(&mut *conn, id, user_id).await?;
+            if role == TaskRole::Assignee && priority.is_some() {
+                return Err(AppError::Forbidden(
+                    "Assignees can only change the status of a task".into(),
+                ));
+            }
+            let before = lock_task(conn, task.id).await?;
+            let status = match status {
+                Some(status) => Some(
+                    check_status_change(conn, &before, &status, before.assigned_to, user_id)
+                        .await?,
+                ),
+                None => None,
+            };
+            let task = sqlx::query_as::<_, Task>(&format!(
+                "UPDATE tasks SET status = COALESCE($1, status), priority = COALESCE($2, priority)
+                 WHERE id = $3
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(status.map(|s| s.name))
+            .bind(priority)
+            .bind(task.id)
+            .fetch_one(&mut *conn)
+            .await?;
+            record_event(
+                conn,
+                Some(user_id),
+                TaskEventType::Updated,
+                Some(&before),
+                Some(&task),
+            )
+            .await?;
+            Ok((StatusCode::OK, Some(task)))
+        }
+        BulkOperation::Assign { id, assignee_id } => {
+            let task = find_owned_task(&mut *conn, id, user_id).await?;
+            ensure_assignable(&mut *conn, config, assignee_id).await?;
+
+            let before = lock_task(conn, task.id).await?;
+            let task = sqlx::query_as::<_, Task>(&format!(
+                "UPDATE tasks SET assigned_to = $1 WHERE id = $2
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(assignee_id)
+            .bind(task.id)
+            .fetch_one(&mut *conn)
+            .await?;
+            record_assignment(
+                conn,
+                task.id,
+                user_id,
+                before.assigned_to,
+                Some(assignee_id),
+            )
+            .await?;
+            record_event(
+                conn,
+                Some(user_id),
+                TaskEventType::Assigned,
+                Some(&before),
+                Some(&task),
+            )
+            .await?;
+            Ok((StatusCode::OK, Some(task)))
+        }
+        BulkOperation::Delete { id } => {
+            let task = find_owned_task(&mut *conn, id, user_id).await?;
+            let before = lock_task(conn, task.id).await?;
+            sqlx::query("UPDATE tasks SET deleted_at = now() WHERE id = $1")
+                .bind(task.id)
+                .execute(&mut *conn)
+                .await?;
+            record_event(
+                conn,
+                Some(user_id),
+                TaskEventType::Deleted,
+                Some(&before),
+                None,
+            )
+            .await?;
+            Ok((StatusCode::NO_CONTENT, None))
+        }
+    }
+}
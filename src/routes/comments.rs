@@ -0,0 +1,203 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{TaskComment, TaskCommentInput, TaskCommentUpdate, TaskRole},
+    routes::tasks::{find_visible_task, touch_tasks},
+};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Loads a comment on `task_id`, or returns `AppError::NotFound`.
+async fn find_task_comment(
+    pool: &PgPool,
+    task_id: Uuid,
+    comment_id: Uuid,
+) -> Result<TaskComment, AppError> {
+    sqlx::query_as::<_, TaskComment>(
+        "SELECT id, task_id, parent_id, user_id, body, created_at, edited_at
+         FROM task_comments WHERE id = $1 AND task_id = $2",
+    )
+    .bind(comment_id)
+    .bind(task_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Comment not found".into()))
+}
+
+/// Lists the comments on a task, oldest first.
+///
+/// Replies are returned in the same flat list; clients rebuild the threads from
+/// each comment's `parent_id`. Available to the task's owner and its assignee.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `TaskComment` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/comments")]
+pub async fn get_task_comments(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let comments = sqlx::query_as::<_, TaskComment>(
+        "SELECT id, task_id, parent_id, user_id, body, created_at, edited_at
+         FROM task_comments WHERE task_id = $1 ORDER BY created_at, id",
+    )
+    .bind(task.id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(comments))
+}
+
+/// Posts a comment on a task, or a reply to one of its comments.
+///
+/// Available to the task's owner and its assignee.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskCommentInput`:
+///   ```json
+///   { "body": "Agreed, let's split it up", "parent_id": "uuid-of-comment" }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `TaskComment` object as JSON.
+/// - `400 Bad Request`: If `parent_id` is not a comment on this task.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `422 Unprocessable Entity`: If the comment body is empty or too long.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/comments")]
+pub async fn create_task_comment(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    comment_data: web::Json<TaskCommentInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    comment_data.validate()?;
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    if let Some(parent_id) = comment_data.parent_id {
+        find_task_comment(&pool, task.id, parent_id)
+            .await
+            .map_err(|_| AppError::BadRequest("Parent comment not found".into()))?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let comment = sqlx::query_as::<_, TaskComment>(
+        "INSERT INTO task_comments (task_id, parent_id, user_id, body) VALUES ($1, $2, $3, $4)
+         RETURNING id, task_id, parent_id, user_id, body, created_at, edited_at",
+    )
+    .bind(task.id)
+    .bind(comment_data.parent_id)
+    .bind(user_id.0)
+    .bind(&comment_data.body)
+    .fetch_one(&mut *tx)
+    .await?;
+    // The task's comment count changed, so refresh its ETag.
+    touch_tasks(&mut *tx, &[task.id]).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(comment))
+}
+
+/// Edits the text of a comment and stamps its `edited_at`.
+///
+/// Only the comment's author may edit it, and only while they can still see the task.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `comment_id`: The UUID of the comment.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskCommentUpdate`:
+///   ```json
+///   { "body": "Agreed, let's split it into two tasks" }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `TaskComment` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user did not write the comment.
+/// - `404 Not Found`: If the task or comment does not exist or is not visible to the authenticated user.
+/// - `422 Unprocessable Entity`: If the comment body is empty or too long.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("/{id}/comments/{comment_id}")]
+pub async fn update_task_comment(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    comment_data: web::Json<TaskCommentUpdate>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    comment_data.validate()?;
+    let (task_id, comment_id) = path.into_inner();
+    let (task, _role) = find_visible_task(&**pool, task_id, user_id.0).await?;
+    let comment = find_task_comment(&pool, task.id, comment_id).await?;
+    if comment.user_id != user_id.0 {
+        return Err(AppError::Forbidden(
+            "Only the author can edit this comment".into(),
+        ));
+    }
+
+    let comment = sqlx::query_as::<_, TaskComment>(
+        "UPDATE task_comments SET body = $1, edited_at = now() WHERE id = $2
+         RETURNING id, task_id, parent_id, user_id, body, created_at, edited_at",
+    )
+    .bind(&comment_data.body)
+    .bind(comment.id)
+    .fetch_one(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+/// Deletes a comment together with its replies.
+///
+/// The comment's author and the task's owner may delete it.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `comment_id`: The UUID of the comment.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful deletion.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is neither the author nor the task's owner.
+/// - `404 Not Found`: If the task or comment does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}/comments/{comment_id}")]
+pub async fn delete_task_comment(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task_id, comment_id) = path.into_inner();
+    let (task, role) = find_visible_task(&**pool, task_id, user_id.0).await?;
+    let comment = find_task_comment(&pool, task.id, comment_id).await?;
+    if comment.user_id != user_id.0 && role != TaskRole::Owner {
+        return Err(AppError::Forbidden(
+            "Only the author or the task owner can delete this comment".into(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM task_comments WHERE id = $1")
+        .bind(comment.id)
+        .execute(&mut *tx)
+        .await?;
+    touch_tasks(&mut *tx, &[task.id]).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,202 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{DependencyLink, StatusDefinition, Task, TaskDependency, TaskDependencyInput},
+    routes::tasks::{find_owned_task, find_visible_task, touch_tasks},
+};
+use actix_web::{delete, post, web, HttpResponse, Responder};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Checks that `task` may move to `new_status` given its blockers.
+///
+/// Moving into a status whose category `requires_unblocked` is rejected with
+/// `AppError::Conflict` while any blocker is not in a `done` status. Blockers in the
+/// trash are ignored. Keeping the current status is always allowed.
+pub(crate) async fn ensure_unblocked<'e, E: PgExecutor<'e>>(
+    executor: E,
+    task: &Task,
+    new_status: &StatusDefinition,
+) -> Result<(), AppError> {
+    if new_status.name == task.status || !new_status.category.requires_unblocked() {
+        return Ok(());
+    }
+
+    let (open_blockers,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM task_dependencies d
+         JOIN tasks blocker ON blocker.id = d.blocker_id
+         JOIN task_statuses s ON s.user_id = blocker.user_id AND s.name = blocker.status
+         WHERE d.blocked_id = $1 AND s.category <> 'done' AND blocker.deleted_at IS NULL",
+    )
+    .bind(task.id)
+    .fetch_one(executor)
+    .await?;
+
+    if open_blockers > 0 {
+        return Err(AppError::Conflict(format!(
+            "Task is blocked by {} unfinished task(s) and cannot move to {}",
+            open_blockers, new_status.name
+        )));
+    }
+    Ok(())
+}
+
+/// Loads the upstream (`blocked_by`) and downstream (`blocks`) dependencies of a
+/// task, limited to linked tasks visible to `user_id`.
+pub(crate) async fn load_dependency_links(
+    pool: &PgPool,
+    task_id: Uuid,
+    user_id: i32,
+) -> Result<(Vec<DependencyLink>, Vec<DependencyLink>), AppError> {
+    let blocked_by = sqlx::query_as::<_, DependencyLink>(
+        "SELECT t.id, t.title, t.status FROM task_dependencies d
+         JOIN tasks t ON t.id = d.blocker_id
+         WHERE d.blocked_id = $1 AND (t.user_id = $2 OR t.assigned_to = $2)
+           AND t.deleted_at IS NULL
+         ORDER BY d.created_at, t.id",
+    )
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let blocks = sqlx::query_as::<_, DependencyLink>(
+        "SELECT t.id, t.title, t.status FROM task_dependencies d
+         JOIN tasks t ON t.id = d.blocked_id
+         WHERE d.blocker_id = $1 AND (t.user_id = $2 OR t.assigned_to = $2)
+           AND t.deleted_at IS NULL
+         ORDER BY d.created_at, t.id",
+    )
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((blocked_by, blocks))
+}
+
+/// Adds a blocker to a task.
+///
+/// The authenticated user must own the blocked task (the one in the path) and be
+/// able to see the blocker. Edges that would close a cycle, including a task
+/// blocking itself, are rejected.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task to block.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskDependencyInput`:
+///   ```json
+///   { "blocker_id": "…" }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `TaskDependency` object as JSON.
+/// - `400 Bad Request`: If the blocker does not exist or is not visible, or the edge would create a cycle.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the dependency already exists.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/dependencies")]
+pub async fn add_dependency(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    dependency_data: web::Json<TaskDependencyInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let blocked = find_owned_task(&**pool, task_id.into_inner(), user_id.0).await?;
+    let blocker_id = dependency_data.blocker_id;
+    if blocker_id == blocked.id {
+        return Err(AppError::BadRequest("A task cannot block itself".into()));
+    }
+    find_visible_task(&**pool, blocker_id, user_id.0)
+        .await
+        .map_err(|e| match e {
+            AppError::NotFound(_) => AppError::BadRequest("Blocker task not found".into()),
+            other => other,
+        })?;
+
+    // Serialize edge inserts so two concurrent requests cannot each add one half of a cycle.
+    let mut tx = pool.begin().await?;
+    sqlx::query("LOCK TABLE task_dependencies IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+
+    // The new edge closes a cycle if the blocker is already downstream of the blocked task.
+    let (creates_cycle,): (bool,) = sqlx::query_as(
+        "WITH RECURSIVE downstream (id) AS (
+             SELECT blocked_id FROM task_dependencies WHERE blocker_id = $1
+             UNION
+             SELECT d.blocked_id FROM task_dependencies d JOIN downstream ON d.blocker_id = downstream.id
+         )
+         SELECT EXISTS (SELECT 1 FROM downstream WHERE id = $2)",
+    )
+    .bind(blocked.id)
+    .bind(blocker_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if creates_cycle {
+        return Err(AppError::BadRequest(
+            "Dependency would create a cycle".into(),
+        ));
+    }
+
+    let dependency = sqlx::query_as::<_, TaskDependency>(
+        "INSERT INTO task_dependencies (blocker_id, blocked_id, created_by) VALUES ($1, $2, $3)
+         ON CONFLICT DO NOTHING
+         RETURNING blocker_id, blocked_id, created_by, created_at",
+    )
+    .bind(blocker_id)
+    .bind(blocked.id)
+    .bind(user_id.0)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Dependency already exists".into()))?;
+
+    // Both tasks' dependency lists changed, so refresh their ETags.
+    touch_tasks(&mut *tx, &[blocker_id, blocked.id]).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(dependency))
+}
+
+/// Removes a blocker from a task.
+///
+/// The authenticated user must own the blocked task.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the blocked task.
+/// - `blocker_id`: The UUID of the blocker to remove.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful removal.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task is not visible to the authenticated user, or the dependency does not exist.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}/dependencies/{blocker_id}")]
+pub async fn remove_dependency(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task_id, blocker_id) = path.into_inner();
+    let blocked = find_owned_task(&**pool, task_id, user_id.0).await?;
+
+    let mut tx = pool.begin().await?;
+    let result =
+        sqlx::query("DELETE FROM task_dependencies WHERE blocker_id = $1 AND blocked_id = $2")
+            .bind(blocker_id)
+            .bind(blocked.id)
+            .execute(&mut *tx)
+            .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Dependency not found".into()));
+    }
+
+    touch_tasks(&mut *tx, &[blocker_id, blocked.id]).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
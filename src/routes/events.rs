@@ -0,0 +1,277 @@
+use crate::{
+    auth::{extractors::AuthenticatedClaims, token::Claims, TOKEN_REVOCATIONS},
+    error::AppError,
+    models::{realtime::ServerMessage, Task, TaskEvent},
+    realtime::{TaskBroadcaster, TaskUpdate},
+    routes::tasks::TASK_COLUMNS,
+};
+use actix_web::{
+    get,
+    http::header::{CacheControl, CacheDirective},
+    web::{self, Bytes},
+    HttpRequest, HttpResponse, Responder,
+};
+use futures::{stream, StreamExt};
+use sqlx::PgPool;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Most events replayed to a reconnecting client. Clients further behind get a
+/// `reset` event instead.
+const MAX_REPLAY_EVENTS: i64 = 1000;
+
+/// How often a comment is sent to keep idle connections open through proxies.
+/// The token the stream was opened with is checked for revocation as often.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Reconnection delay suggested to clients, in milliseconds.
+const RETRY_MILLIS: u32 = 3000;
+
+/// Formats one Server-Sent Events frame.
+fn sse_frame(id: Option<i64>, event: Option<&str>, data: &str) -> String {
+    let mut frame = String::new();
+    if let Some(id) = id {
+        frame.push_str(&format!("id: {}\n", id));
+    }
+    if let Some(event) = event {
+        frame.push_str(&format!("event: {}\n", event));
+    }
+    for line in data.lines() {
+        frame.push_str(&format!("data: {}\n", line));
+    }
+    frame.push('\n');
+    frame
+}
+
+/// Formats a task event as a frame whose `id` is the event's ID.
+fn task_event_frame(event: &TaskEvent, task: Option<&Task>) -> String {
+    let data = serde_json::to_string(&ServerMessage::TaskEvent { event, task })
+        .expect("server messages serialize to JSON");
+    sse_frame(Some(event.id), None, &data)
+}
+
+/// Formats a `reset` frame, telling the client it may have missed events and
+/// should refetch the tasks it displays. `resume_from` becomes the client's new
+/// `Last-Event-ID`, if given.
+fn reset_frame(resume_from: Option<i64>, reason: &str) -> String {
+    sse_frame(
+        resume_from,
+        Some("reset"),
+        &serde_json::json!({ "reason": reason }).to_string(),
+    )
+}
+
+/// Builds the frames that bring a client resuming after `last_event_id` up to date.
+async fn replay_frames(
+    pool: &PgPool,
+    user_id: i32,
+    last_event_id: i64,
+    replayed: &mut HashSet<i64>,
+) -> Result<String, AppError> {
+    let events = sqlx::query_as::<_, TaskEvent>(
+        "SELECT e.id, e.task_id, e.actor_id, e.event_type, e.changes, e.created_at
+         FROM task_event_log l JOIN task_events e ON e.id = l.event_id
+         WHERE $1 = ANY(l.audience) AND l.event_id > $2
+         ORDER BY l.event_id LIMIT $3",
+    )
+    .bind(user_id)
+    .bind(last_event_id)
+    .bind(MAX_REPLAY_EVENTS + 1)
+    .fetch_all(pool)
+    .await?;
+
+    // Events after `last_event_id` that have already been pruned from the log may
+    // have concerned this user, and too long a backlog is not worth replaying.
+    let (pruned,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM task_events
+                        WHERE id > $1
+                          AND id < COALESCE((SELECT MIN(event_id) FROM task_event_log),
+                                            (SELECT MAX(id) + 1 FROM task_events)))",
+    )
+    .bind(last_event_id)
+    .fetch_one(pool)
+    .await?;
+    if pruned || events.len() as i64 > MAX_REPLAY_EVENTS {
+        let (latest,): (i64,) = sqlx::query_as("SELECT COALESCE(MAX(id), 0) FROM task_events")
+            .fetch_one(pool)
+            .await?;
+        return Ok(reset_frame(Some(latest), "Too many missed events"));
+    }
+
+    let task_ids: Vec<Uuid> = events.iter().map(|e| e.task_id).collect();
+    let tasks = sqlx::query_as::<_, Task>(&format!(
+        "SELECT {} FROM tasks WHERE id = ANY($1) AND deleted_at IS NULL",
+        TASK_COLUMNS
+    ))
+    .bind(&task_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut frames = String::new();
+    for event in &events {
+        let task = tasks
+            .iter()
+            .find(|t| t.id == event.task_id)
+            .filter(|t| t.is_visible_to(user_id));
+        frames.push_str(&task_event_frame(event, task));
+        replayed.insert(event.id);
+    }
+    Ok(frames)
+}
+
+/// Streams live updates for the user of `claims`, skipping events that were already
+/// replayed.
+///
+/// The stream ends when the token expires or is found revoked, like a WebSocket
+/// session does; the client then has to reconnect with a fresh token.
+fn live_frames(
+    pool: PgPool,
+    updates: broadcast::Receiver<Arc<TaskUpdate>>,
+    claims: Claims,
+    replayed: HashSet<i64>,
+) -> impl futures::Stream<Item = Result<Bytes, AppError>> {
+    let now = actix_web::rt::time::Instant::now();
+    let keep_alive =
+        actix_web::rt::time::interval_at(now + KEEP_ALIVE_INTERVAL, KEEP_ALIVE_INTERVAL);
+    let expires_in = (claims.exp as i64 - chrono::Utc::now().timestamp()).max(0) as u64;
+    let expiry = now + Duration::from_secs(expires_in);
+
+    stream::unfold(
+        (updates, keep_alive, replayed),
+        move |(mut updates, mut keep_alive, mut replayed)| {
+            let pool = pool.clone();
+            let claims = claims.clone();
+            async move {
+                let user_id = claims.sub;
+                let frame = loop {
+                    tokio::select! {
+                        _ = actix_web::rt::time::sleep_until(expiry) => return None,
+                        _ = keep_alive.tick() => {
+                            if TOKEN_REVOCATIONS.check(&pool, &claims).await.is_err() {
+                                return None;
+                            }
+                            break ": keep-alive\n\n".to_string()
+                        }
+                        update = updates.recv() => match update {
+                            Ok(update) => {
+                                if !update.audience.contains(&user_id) || replayed.remove(&update.event.id) {
+                                    continue;
+                                }
+                                break task_event_frame(&update.event, update.task_for(user_id));
+                            }
+                            Err(RecvError::Lagged(_)) => {
+                                break reset_frame(None, "The connection fell behind")
+                            }
+                            Err(RecvError::Closed) => return None,
+                        },
+                    }
+                };
+                Some((Ok(Bytes::from(frame)), (updates, keep_alive, replayed)))
+            }
+        },
+    )
+}
+
+/// Streams task events as Server-Sent Events (`text/event-stream`).
+///
+/// A lightweight alternative to `GET /api/ws` for clients behind proxies that
+/// break WebSockets. Each event is sent as a frame whose `id` is the event's ID and
+/// whose `data` is the same `task_event` JSON message the WebSocket sends, for every
+/// task the user can see that is created, updated, assigned, unassigned, deleted,
+/// restored, archived or unarchived.
+///
+/// Clients that reconnect with `Last-Event-ID` (as `EventSource` does automatically)
+/// first receive the events they missed, as long as those are still in the event log
+/// (see `EVENT_LOG_RETENTION_HOURS`). If events may have been lost, the stream sends
+/// an `event: reset` frame instead, and the client should refetch its tasks.
+///
+/// The stream ends when the access token expires or is revoked, which is checked
+/// with every keep-alive. Clients reconnect with a fresh token and their
+/// `Last-Event-ID` to pick up where they left off.
+///
+/// ## Request Headers:
+/// - `Last-Event-ID` (optional): The `id` of the last event the client received.
+///
+/// ## Responses:
+/// - `200 OK`: An open `text/event-stream`.
+/// - `400 Bad Request`: If `Last-Event-ID` is not an event ID.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/events")]
+pub async fn task_event_stream(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    broadcaster: web::Data<TaskBroadcaster>,
+    claims: AuthenticatedClaims,
+) -> Result<impl Responder, AppError> {
+    let claims = claims.0;
+    let last_event_id = match req.headers().get("Last-Event-ID") {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .ok_or_else(|| AppError::BadRequest("Invalid Last-Event-ID".into()))?,
+        ),
+        None => None,
+    };
+
+    // Subscribe before reading the log, so events committed in between are not lost.
+    let updates = broadcaster.subscribe();
+    let mut head = format!("retry: {}\n\n", RETRY_MILLIS);
+    let mut replayed = HashSet::new();
+    if let Some(last_event_id) = last_event_id {
+        head.push_str(&replay_frames(&pool, claims.sub, last_event_id, &mut replayed).await?);
+    }
+
+    let body = stream::once(async move { Ok(Bytes::from(head)) }).chain(live_frames(
+        pool.get_ref().clone(),
+        updates,
+        claims,
+        replayed,
+    ));
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        // Ask nginx-style proxies not to buffer the stream.
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_frame_format() {
+        assert_eq!(
+            sse_frame(Some(42), None, r#"{"type":"task_event"}"#),
+            "id: 42\ndata: {\"type\":\"task_event\"}\n\n"
+        );
+        assert_eq!(
+            sse_frame(None, Some("reset"), "line one\nline two"),
+            "event: reset\ndata: line one\ndata: line two\n\n"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_live_frames_end_when_the_token_expires() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        // Keep the sender alive, so the stream cannot end because the channel closed.
+        let (_sender, updates) = broadcast::channel(16);
+        let claims = Claims {
+            sub: 1,
+            exp: chrono::Utc::now().timestamp() as usize + 1,
+            jti: Uuid::new_v4(),
+            sid: Uuid::new_v4(),
+            generation: 0,
+        };
+
+        let frames = live_frames(pool, updates, claims, HashSet::new()).collect::<Vec<_>>();
+        let frames = actix_web::rt::time::timeout(Duration::from_secs(5), frames)
+            .await
+            .expect("the stream outlived its token");
+        assert!(frames.is_empty());
+    }
+}
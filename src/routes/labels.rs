@@ -0,0 +1,272 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{Label, LabelInput, Task},
+    routes::tasks::{find_owned_task, touch_tasks, TASK_COLUMNS},
+};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Refreshes the `ETag`s of the tasks carrying label `label_id` of `user_id`.
+async fn touch_labelled_tasks(
+    conn: &mut PgConnection,
+    label_id: Uuid,
+    user_id: i32,
+) -> Result<(), AppError> {
+    let task_ids = sqlx::query_scalar::<_, Uuid>(
+        "SELECT tl.task_id FROM task_labels tl
+         JOIN labels l ON l.id = tl.label_id
+         WHERE tl.label_id = $1 AND l.user_id = $2",
+    )
+    .bind(label_id)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    touch_tasks(&mut *conn, &task_ids).await
+}
+
+/// Loads a label owned by `user_id`, or returns `AppError::NotFound`.
+async fn find_owned_label(pool: &PgPool, label_id: Uuid, user_id: i32) -> Result<Label, AppError> {
+    sqlx::query_as::<_, Label>(
+        "SELECT id, user_id, name, color, created_at FROM labels WHERE id = $1 AND user_id = $2",
+    )
+    .bind(label_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Label not found".into()))
+}
+
+/// Lists the authenticated user's labels, ordered by name.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `Label` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("")]
+pub async fn get_labels(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let labels = sqlx::query_as::<_, Label>(
+        "SELECT id, user_id, name, color, created_at FROM labels WHERE user_id = $1 ORDER BY name",
+    )
+    .bind(user_id.0)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(labels))
+}
+
+/// Creates a label for the authenticated user.
+///
+/// ## Request Body:
+/// A JSON object matching `LabelInput`:
+///   ```json
+///   { "name": "backend", "color": "#1f6feb" }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `Label` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `409 Conflict`: If the user already has a label with this name.
+/// - `422 Unprocessable Entity`: If the name or colour is invalid.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("")]
+pub async fn create_label(
+    pool: web::Data<PgPool>,
+    label_data: web::Json<LabelInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    label_data.validate()?;
+
+    let label = sqlx::query_as::<_, Label>(
+        "INSERT INTO labels (user_id, name, color) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, name) DO NOTHING
+         RETURNING id, user_id, name, color, created_at",
+    )
+    .bind(user_id.0)
+    .bind(&label_data.name)
+    .bind(&label_data.color)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or_else(|| AppError::Conflict("A label with this name already exists".into()))?;
+
+    Ok(HttpResponse::Created().json(label))
+}
+
+/// Renames or recolours one of the authenticated user's labels.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the label.
+///
+/// ## Request Body:
+/// A JSON object matching `LabelInput`.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Label` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the label does not exist or belongs to another user.
+/// - `409 Conflict`: If the user already has another label with this name.
+/// - `422 Unprocessable Entity`: If the name or colour is invalid.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("/{id}")]
+pub async fn update_label(
+    pool: web::Data<PgPool>,
+    label_id: web::Path<Uuid>,
+    label_data: web::Json<LabelInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    label_data.validate()?;
+    let label = find_owned_label(&pool, label_id.into_inner(), user_id.0).await?;
+
+    let (name_taken,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM labels WHERE user_id = $1 AND name = $2 AND id <> $3)",
+    )
+    .bind(user_id.0)
+    .bind(&label_data.name)
+    .bind(label.id)
+    .fetch_one(&**pool)
+    .await?;
+    if name_taken {
+        return Err(AppError::Conflict(
+            "A label with this name already exists".into(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let label = sqlx::query_as::<_, Label>(
+        "UPDATE labels SET name = $1, color = $2 WHERE id = $3
+         RETURNING id, user_id, name, color, created_at",
+    )
+    .bind(&label_data.name)
+    .bind(&label_data.color)
+    .bind(label.id)
+    .fetch_one(&mut *tx)
+    .await?;
+    // Tasks embed the label's name and color, so refresh their ETags.
+    touch_labelled_tasks(&mut tx, label.id, user_id.0).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(label))
+}
+
+/// Deletes one of the authenticated user's labels and detaches it from all tasks.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the label.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful deletion.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the label does not exist or belongs to another user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}")]
+pub async fn delete_label(
+    pool: web::Data<PgPool>,
+    label_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let label_id = label_id.into_inner();
+    let mut tx = pool.begin().await?;
+    // Touch the tasks before the delete cascades to their `task_labels` rows.
+    touch_labelled_tasks(&mut tx, label_id, user_id.0).await?;
+    let result = sqlx::query("DELETE FROM labels WHERE id = $1 AND user_id = $2")
+        .bind(label_id)
+        .bind(user_id.0)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Label not found".into()));
+    }
+    tx.commit().await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Attaches one of the authenticated user's labels to a task they own.
+///
+/// Attaching a label that is already on the task is a no-op.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `label_id`: The UUID of the label.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task or the label does not exist or is not the user's.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/labels/{label_id}")]
+pub async fn add_task_label(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task_id, label_id) = path.into_inner();
+    let task = find_owned_task(&**pool, task_id, user_id.0).await?;
+    let label = find_owned_label(&pool, label_id, user_id.0).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO task_labels (task_id, label_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+    )
+    .bind(task.id)
+    .bind(label.id)
+    .execute(&mut *tx)
+    .await?;
+    // The task's labels changed, so refresh its ETag.
+    touch_tasks(&mut *tx, &[task.id]).await?;
+    let task =
+        sqlx::query_as::<_, Task>(&format!("SELECT {} FROM tasks WHERE id = $1", TASK_COLUMNS))
+            .bind(task.id)
+            .fetch_one(&mut *tx)
+            .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(task))
+}
+
+/// Detaches a label from a task the authenticated user owns.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+/// - `label_id`: The UUID of the label.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible, or the label is not on the task.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}/labels/{label_id}")]
+pub async fn remove_task_label(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task_id, label_id) = path.into_inner();
+    let task = find_owned_task(&**pool, task_id, user_id.0).await?;
+
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query("DELETE FROM task_labels WHERE task_id = $1 AND label_id = $2")
+        .bind(task.id)
+        .bind(label_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Label is not on this task".into()));
+    }
+    touch_tasks(&mut *tx, &[task.id]).await?;
+    let task =
+        sqlx::query_as::<_, Task>(&format!("SELECT {} FROM tasks WHERE id = $1", TASK_COLUMNS))
+            .bind(task.id)
+            .fetch_one(&mut *tx)
+            .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(task))
+}
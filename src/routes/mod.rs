@@ -5,15 +5,51 @@
 //! with an Actix Web App.
 //!
 //! It organizes API routes into submodules for better structure:
-//! - `auth`: Handles user authentication (registration, login) under `/api/auth`.
+//! - `auth`: Handles user authentication (registration, login, token refresh, logout,
+//!   password reset, email verification) and lists and revokes sessions under `/api/auth`.
 //! - `tasks`: Manages task creation, retrieval, updates, and deletion under `/api/tasks`.
+//! - `notes`: Manages the notes owners and assignees add to a task under `/api/tasks/{id}/notes`.
+//! - `comments`: Manages threaded discussions on a task under `/api/tasks/{id}/comments`.
+//! - `attachments`: Uploads, lists, downloads and deletes files attached to a task under `/api/tasks/{id}/attachments`.
+//! - `subtasks`: Validates task hierarchies and lists subtasks under `/api/tasks/{id}/subtasks`.
+//! - `dependencies`: Manages "blocks" relationships between tasks under `/api/tasks/{id}/dependencies`.
+//! - `labels`: Manages a user's labels under `/api/labels` and attaches them to tasks under `/api/tasks/{id}/labels`.
+//! - `events`: Streams task events as Server-Sent Events under `/api/tasks/events`.
+//! - `ws`: Pushes task events to WebSocket clients at `/api/ws`.
+//! - `trash`: Lists deleted tasks under `/api/tasks/trash` and restores them under `/api/tasks/{id}/restore`.
+//! - `archive`: Archives and unarchives tasks under `/api/tasks/{id}/archive` and `/api/tasks/{id}/unarchive`.
+//! - `bulk`: Applies batches of task operations in one transaction under `/api/tasks/bulk`.
+//! - `activity`: Records task changes and lists a task's activity log under `/api/tasks/{id}/activity`.
+//! - `assignments`: Records and lists a task's assignment history under `/api/tasks/{id}/assignments`.
+//! - `webhooks`: Manages a user's webhooks and their delivery logs under `/api/webhooks`.
+//! - `statuses`: Manages a user's task statuses under `/api/statuses` and resolves the
+//!   status of task writes against them.
+//! - `workflow`: Manages a user's status workflow under `/api/workflow`, lists a task's
+//!   available transitions under `/api/tasks/{id}/transitions`, and enforces the workflow
+//!   on status changes.
 //!
 //! Health check routes (from the `health` submodule) are typically registered separately
 //! at the application root.
 
+pub mod activity;
+pub mod archive;
+pub mod assignments;
+pub mod attachments;
 pub mod auth;
+pub mod bulk;
+pub mod comments;
+pub mod dependencies;
+pub mod events;
 pub mod health;
+pub mod labels;
+pub mod notes;
+pub mod statuses;
+pub mod subtasks;
 pub mod tasks;
+pub mod trash;
+pub mod webhooks;
+pub mod workflow;
+pub mod ws;
 
 use actix_web::web;
 
@@ -28,18 +64,85 @@ use actix_web::web;
 /// * `cfg` - A mutable reference to Actix Web's `ServiceConfig` to which
 ///   the routes will be added.
 pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/auth")
-            .service(auth::login)
-            .service(auth::register),
-    )
-    .service(
-        web::scope("/tasks")
-            .service(tasks::get_tasks)
-            .service(tasks::create_task)
-            .service(tasks::get_task)
-            .service(tasks::update_task)
-            .service(tasks::delete_task)
-            .service(tasks::assign_task),
-    );
+    cfg.service(ws::task_updates)
+        .service(
+            web::scope("/auth")
+                .service(auth::login)
+                .service(auth::register)
+                .service(auth::refresh)
+                .service(auth::logout)
+                .service(auth::logout_all)
+                .service(auth::get_sessions)
+                .service(auth::delete_session)
+                .service(auth::forgot_password)
+                .service(auth::reset_password)
+                .service(auth::verify_email_address)
+                .service(auth::resend_verification_email),
+        )
+        .service(
+            web::scope("/tasks")
+                .service(tasks::get_tasks)
+                .service(bulk::bulk_tasks)
+                .service(events::task_event_stream)
+                .service(trash::get_trash)
+                .service(tasks::create_task)
+                .service(tasks::get_task)
+                .service(tasks::update_task)
+                .service(tasks::patch_task)
+                .service(tasks::update_task_status)
+                .service(tasks::delete_task)
+                .service(tasks::assign_task)
+                .service(tasks::unassign_task)
+                .service(trash::restore_task)
+                .service(archive::archive_task)
+                .service(archive::unarchive_task)
+                .service(workflow::get_task_transitions)
+                .service(assignments::get_task_assignments)
+                .service(activity::get_task_activity)
+                .service(subtasks::get_subtasks)
+                .service(dependencies::add_dependency)
+                .service(dependencies::remove_dependency)
+                .service(labels::add_task_label)
+                .service(labels::remove_task_label)
+                .service(notes::get_task_notes)
+                .service(notes::create_task_note)
+                .service(comments::get_task_comments)
+                .service(comments::create_task_comment)
+                .service(comments::update_task_comment)
+                .service(comments::delete_task_comment)
+                .service(attachments::get_task_attachments)
+                .service(attachments::upload_attachment)
+                .service(attachments::download_attachment)
+                .service(attachments::delete_attachment),
+        )
+        .service(
+            web::scope("/labels")
+                .service(labels::get_labels)
+                .service(labels::create_label)
+                .service(labels::update_label)
+                .service(labels::delete_label),
+        )
+        .service(
+            web::scope("/webhooks")
+                .service(webhooks::get_webhooks)
+                .service(webhooks::create_webhook)
+                .service(webhooks::get_webhook)
+                .service(webhooks::update_webhook)
+                .service(webhooks::delete_webhook)
+                .service(webhooks::get_webhook_deliveries)
+                .service(webhooks::redeliver_webhook_delivery),
+        )
+        .service(
+            web::scope("/statuses")
+                .service(statuses::get_statuses)
+                .service(statuses::create_status)
+                .service(statuses::update_status)
+                .service(statuses::delete_status),
+        )
+        .service(
+            web::scope("/workflow")
+                .service(workflow::get_workflow)
+                .service(workflow::update_workflow)
+                .service(workflow::reset_workflow),
+        );
 }
@@ -0,0 +1,84 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{TaskNote, TaskNoteInput},
+    routes::tasks::find_visible_task,
+};
+use actix_web::{get, post, web, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Lists the notes on a task, oldest first.
+///
+/// Available to the task's owner and its assignee.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `TaskNote` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/notes")]
+pub async fn get_task_notes(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let notes = sqlx::query_as::<_, TaskNote>(
+        "SELECT id, task_id, user_id, body, created_at FROM task_notes
+         WHERE task_id = $1 ORDER BY created_at, id",
+    )
+    .bind(task.id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(notes))
+}
+
+/// Adds a note to a task.
+///
+/// Available to the task's owner and its assignee. Notes cannot be edited or
+/// removed once added.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskNoteInput`:
+///   ```json
+///   { "body": "Blocked on the staging deploy" }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `TaskNote` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `422 Unprocessable Entity`: If the note body is empty or too long.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/notes")]
+pub async fn create_task_note(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    note_data: web::Json<TaskNoteInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    note_data.validate()?;
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let note = sqlx::query_as::<_, TaskNote>(
+        "INSERT INTO task_notes (task_id, user_id, body) VALUES ($1, $2, $3)
+         RETURNING id, task_id, user_id, body, created_at",
+    )
+    .bind(task.id)
+    .bind(user_id.0)
+    .bind(&note_data.body)
+    .fetch_one(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Created().json(note))
+}
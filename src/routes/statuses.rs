@@ -0,0 +1,274 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{status::legacy_category, StatusCategory, StatusDefinition, StatusInput, TaskStatus},
+    routes::tasks::{embedding_tasks, touch_tasks},
+};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Loads `user_id`'s statuses, ordered by `sort_order` and name.
+pub(crate) async fn load_statuses<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+) -> Result<Vec<StatusDefinition>, AppError> {
+    let statuses = sqlx::query_as::<_, StatusDefinition>(
+        "SELECT name, category, sort_order FROM task_statuses
+         WHERE user_id = $1 ORDER BY sort_order, name",
+    )
+    .bind(user_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(statuses)
+}
+
+/// Resolves `status` against the statuses of `user_id`, who owns the task being written.
+///
+/// One of the default status names (`todo`, `in_progress`, `review`, `done`) that
+/// the user has renamed or removed resolves to the user's first status of the same
+/// category, so clients written against the fixed statuses keep working. Names that
+/// match neither are rejected with `AppError::BadRequest`.
+pub(crate) async fn resolve_status<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    status: &TaskStatus,
+) -> Result<StatusDefinition, AppError> {
+    sqlx::query_as::<_, StatusDefinition>(
+        "SELECT name, category, sort_order FROM task_statuses
+         WHERE user_id = $1 AND (name = $2 OR category = $3)
+         ORDER BY name = $2 DESC, sort_order, name
+         LIMIT 1",
+    )
+    .bind(user_id)
+    .bind(status)
+    .bind(legacy_category(status.as_str()))
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| AppError::BadRequest(format!("Unknown status '{}'", status)))
+}
+
+/// Lists the authenticated user's statuses, which apply to every task they own.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `StatusDefinition` objects, ordered by
+///   `sort_order` and name.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("")]
+pub async fn get_statuses(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let statuses = load_statuses(&**pool, user_id.0).await?;
+    Ok(HttpResponse::Ok().json(statuses))
+}
+
+/// Creates a status for the authenticated user.
+///
+/// ## Request Body:
+/// A JSON object matching `StatusInput`:
+///   ```json
+///   { "name": "qa", "category": "active", "sort_order": 3 }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `StatusDefinition` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `409 Conflict`: If the user already has a status with this name.
+/// - `422 Unprocessable Entity`: If the name is invalid.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("")]
+pub async fn create_status(
+    pool: web::Data<PgPool>,
+    status_data: web::Json<StatusInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    status_data.validate()?;
+
+    let status = sqlx::query_as::<_, StatusDefinition>(
+        "INSERT INTO task_statuses (user_id, name, category, sort_order)
+         VALUES ($1, $2, $3, COALESCE($4, (SELECT COALESCE(MAX(sort_order) + 1, 0)
+                                          FROM task_statuses WHERE user_id = $1)))
+         ON CONFLICT (user_id, name) DO NOTHING
+         RETURNING name, category, sort_order",
+    )
+    .bind(user_id.0)
+    .bind(&status_data.name)
+    .bind(status_data.category)
+    .bind(status_data.sort_order)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or_else(|| AppError::Conflict("A status with this name already exists".into()))?;
+
+    Ok(HttpResponse::Created().json(status))
+}
+
+/// Renames, recategorises or moves one of the authenticated user's statuses.
+///
+/// Renaming a status renames it on every task and workflow transition that uses it.
+/// Moving a status into or out of the `done` category sets or clears the
+/// `completed_at` of its tasks.
+///
+/// ## Path Parameters:
+/// - `name`: The current name of the status.
+///
+/// ## Request Body:
+/// A JSON object matching `StatusInput`. `sort_order` is kept if omitted.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `StatusDefinition` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the user has no status with this name.
+/// - `409 Conflict`: If the user already has another status with the new name.
+/// - `422 Unprocessable Entity`: If the new name is invalid.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("/{name}")]
+pub async fn update_status(
+    pool: web::Data<PgPool>,
+    name: web::Path<String>,
+    status_data: web::Json<StatusInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    status_data.validate()?;
+    let name = name.into_inner();
+
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query_as::<_, StatusDefinition>(
+        "SELECT name, category, sort_order FROM task_statuses
+         WHERE user_id = $1 AND name = $2 FOR UPDATE",
+    )
+    .bind(user_id.0)
+    .bind(&name)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Status not found".into()))?;
+
+    if status_data.name != name {
+        let (name_taken,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM task_statuses WHERE user_id = $1 AND name = $2)",
+        )
+        .bind(user_id.0)
+        .bind(&status_data.name)
+        .fetch_one(&mut *tx)
+        .await?;
+        if name_taken {
+            return Err(AppError::Conflict(
+                "A status with this name already exists".into(),
+            ));
+        }
+    }
+
+    let status = sqlx::query_as::<_, StatusDefinition>(
+        "UPDATE task_statuses SET name = $3, category = $4, sort_order = COALESCE($5, sort_order)
+         WHERE user_id = $1 AND name = $2
+         RETURNING name, category, sort_order",
+    )
+    .bind(user_id.0)
+    .bind(&name)
+    .bind(&status_data.name)
+    .bind(status_data.category)
+    .bind(status_data.sort_order)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let was_done = current.category == StatusCategory::Done;
+    if was_done != (status.category == StatusCategory::Done) {
+        sqlx::query(
+            "UPDATE tasks SET completed_at = CASE WHEN $3 THEN now() END
+             WHERE user_id = $1 AND status = $2",
+        )
+        .bind(user_id.0)
+        .bind(&status.name)
+        .bind(!was_done)
+        .execute(&mut *tx)
+        .await?;
+    }
+    if status.name != current.name || status.category != current.category {
+        // Parents count done subtasks and dependency links show status names, so
+        // refresh the ETags of the tasks embedding those in this status.
+        let task_ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM tasks WHERE user_id = $1 AND status = $2",
+        )
+        .bind(user_id.0)
+        .bind(&status.name)
+        .fetch_all(&mut *tx)
+        .await?;
+        let embedding = embedding_tasks(&mut tx, &task_ids).await?;
+        touch_tasks(&mut *tx, &embedding).await?;
+    }
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(status))
+}
+
+/// Deletes one of the authenticated user's statuses.
+///
+/// Statuses still used by a workflow transition are kept, since dropping the
+/// transitions could empty the workflow and with it lift every restriction.
+///
+/// ## Path Parameters:
+/// - `name`: The name of the status.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful deletion.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the user has no status with this name.
+/// - `409 Conflict`: If any of the user's tasks, including those in the trash, is in this status.
+///   Also if a transition of the user's workflow moves into or out of it.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{name}")]
+pub async fn delete_status(
+    pool: web::Data<PgPool>,
+    name: web::Path<String>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let name = name.into_inner();
+
+    // Locking the status keeps tasks from moving into it until it is gone.
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT 1 FROM task_statuses WHERE user_id = $1 AND name = $2 FOR UPDATE")
+        .bind(user_id.0)
+        .bind(&name)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Status not found".into()))?;
+
+    let (in_use,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM tasks WHERE user_id = $1 AND status = $2")
+            .bind(user_id.0)
+            .bind(&name)
+            .fetch_one(&mut *tx)
+            .await?;
+    if in_use > 0 {
+        return Err(AppError::Conflict(format!(
+            "Status is used by {} task(s); move them to another status first",
+            in_use
+        )));
+    }
+
+    let (transitions,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM workflow_transitions
+         WHERE user_id = $1 AND (from_status = $2 OR to_status = $2)",
+    )
+    .bind(user_id.0)
+    .bind(&name)
+    .fetch_one(&mut *tx)
+    .await?;
+    if transitions > 0 {
+        return Err(AppError::Conflict(format!(
+            "Status is used by {} workflow transition(s); remove them from your workflow first",
+            transitions
+        )));
+    }
+
+    sqlx::query("DELETE FROM task_statuses WHERE user_id = $1 AND name = $2")
+        .bind(user_id.0)
+        .bind(&name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
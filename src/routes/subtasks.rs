@@ -0,0 +1,145 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::Task,
+    routes::tasks::{find_visible_task, TASK_COLUMNS},
+};
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Serializes changes to the task hierarchy of `user_id` until the transaction ends.
+///
+/// Writes that change a parent take this before `lock_task`, so the lock is always
+/// acquired ahead of any task row lock. Taking it again in the same transaction is
+/// a no-op.
+pub(crate) async fn lock_hierarchy(conn: &mut PgConnection, user_id: i32) -> Result<(), AppError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('task_hierarchy'), $1)")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Checks that `parent_id` can become the parent of a task owned by `user_id`.
+///
+/// `task_id` is the task being moved, or `None` for a task that is being created.
+/// The parent must be owned by the same user, must not be the task itself or one of
+/// its descendants, and the resulting hierarchy (the parent's ancestors plus the
+/// task's own subtree) must not be deeper than `max_depth` levels.
+///
+/// Call this in the write transaction that sets the parent. It takes the owner's
+/// `lock_hierarchy` lock, so concurrent moves are checked one after the other and
+/// cannot together create a cycle or exceed the depth limit.
+///
+/// Returns `AppError::BadRequest` describing the first rule that is violated.
+pub(crate) async fn validate_parent(
+    conn: &mut PgConnection,
+    task_id: Option<Uuid>,
+    parent_id: Uuid,
+    user_id: i32,
+    max_depth: u32,
+) -> Result<(), AppError> {
+    let max_depth = max_depth as i32;
+
+    lock_hierarchy(conn, user_id).await?;
+
+    let parent: Option<(Uuid,)> = sqlx::query_as(
+        "SELECT id FROM tasks WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+    )
+    .bind(parent_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if parent.is_none() {
+        return Err(AppError::BadRequest("Parent task not found".into()));
+    }
+
+    // Walk up from the parent. The walk stops one level past the limit, which is
+    // enough to reject the move and keeps the recursion bounded.
+    let (parent_depth, creates_cycle): (i32, bool) = sqlx::query_as(
+        "WITH RECURSIVE ancestors (id, parent_id, depth) AS (
+             SELECT id, parent_id, 1 FROM tasks WHERE id = $1
+             UNION ALL
+             SELECT t.id, t.parent_id, a.depth + 1
+             FROM tasks t JOIN ancestors a ON t.id = a.parent_id
+             WHERE a.depth <= $3
+         )
+         SELECT MAX(depth), COALESCE(BOOL_OR(id = $2::uuid), FALSE) FROM ancestors",
+    )
+    .bind(parent_id)
+    .bind(task_id)
+    .bind(max_depth)
+    .fetch_one(&mut *conn)
+    .await?;
+    if creates_cycle {
+        return Err(AppError::BadRequest(
+            "A task cannot be a subtask of itself or of its own subtasks".into(),
+        ));
+    }
+
+    let subtree_height: i32 = match task_id {
+        Some(task_id) => {
+            let (height,): (Option<i32>,) = sqlx::query_as(
+                "WITH RECURSIVE descendants (id, level) AS (
+                     SELECT id, 1 FROM tasks WHERE id = $1
+                     UNION ALL
+                     SELECT t.id, d.level + 1
+                     FROM tasks t JOIN descendants d ON t.parent_id = d.id
+                     WHERE d.level <= $2
+                 )
+                 SELECT MAX(level) FROM descendants",
+            )
+            .bind(task_id)
+            .bind(max_depth)
+            .fetch_one(&mut *conn)
+            .await?;
+            height.unwrap_or(1)
+        }
+        None => 1,
+    };
+
+    if parent_depth + subtree_height > max_depth {
+        return Err(AppError::BadRequest(format!(
+            "Task hierarchy cannot be deeper than {} levels",
+            max_depth
+        )));
+    }
+    Ok(())
+}
+
+/// Lists the direct subtasks of a task, oldest first.
+///
+/// Available to the task's owner and its assignee. Only subtasks the authenticated
+/// user can see are listed; the parent's `subtasks_total` and `subtasks_done`
+/// counts cover all of them.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the parent task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `Task` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/subtasks")]
+pub async fn get_subtasks(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+
+    let subtasks = sqlx::query_as::<_, Task>(&format!(
+        "SELECT {} FROM tasks
+         WHERE parent_id = $1 AND (user_id = $2 OR assigned_to = $2) AND deleted_at IS NULL
+         ORDER BY created_at, id",
+        TASK_COLUMNS
+    ))
+    .bind(task.id)
+    .bind(user_id.0)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(subtasks))
+}
@@ -1,29 +1,101 @@
 use crate::{
     auth::extractors::AuthenticatedUserId,
+    config::Config,
     error::AppError,
-    models::{Task, TaskInput, TaskQuery},
+    models::{
+        label::parse_label_names,
+        pagination::{clamp_limit, decode_cursor, encode_cursor},
+        task::TaskCursor,
+        task::TaskStatusUpdate,
+        task::STATUS_ORDER,
+        LabelMatch, Page, SearchMatch, StatusDefinition, Task, TaskDetail, TaskEventType,
+        TaskInput, TaskListItem, TaskPatch, TaskQuery, TaskRole, TaskScope, TaskSortField,
+        TaskStatus,
+    },
+    routes::{
+        activity::record_event,
+        assignments::{ensure_assignable, record_assignment},
+        dependencies::{ensure_unblocked, load_dependency_links},
+        statuses::resolve_status,
+        subtasks::{lock_hierarchy, validate_parent},
+        workflow::ensure_transition_allowed,
+    },
 };
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
-use sqlx::PgPool;
+use actix_web::{
+    delete, get,
+    http::header::{self, ETag, EntityTag, Header, IfMatch, IfNoneMatch},
+    patch, post, put, web, HttpRequest, HttpResponse, Responder,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 // use log; // Keep or remove, eprintln! will be used for now
 
-/// Retrieves a list of tasks for the authenticated user.
+/// Columns selected for a `Task`, including its subtask progress counts, comment count and labels.
+/// Subtasks in the trash are not counted, and subtasks count as done while their
+/// status is in the `done` category.
 ///
-/// This endpoint fetches tasks owned by the authenticated user. It supports
-/// filtering by `status`, `priority`, `assigned_to` (user ID), and a `search` term
-/// which looks for matches in task titles and descriptions.
-/// Tasks are ordered by creation date in descending order.
+/// Also valid in the `RETURNING` clause of statements on `tasks`. Queries must
+/// exclude trashed tasks themselves, with `deleted_at IS NULL`.
+pub(crate) const TASK_COLUMNS: &str = "id, title, description, priority, status, due_date, created_at, updated_at, user_id, assigned_to, parent_id, deleted_at, completed_at, archived_at, \
+     (SELECT COUNT(*) FROM tasks AS child WHERE child.parent_id = tasks.id AND child.deleted_at IS NULL) AS subtasks_total, \
+     (SELECT COUNT(*) FROM tasks AS child JOIN task_statuses s ON s.user_id = child.user_id AND s.name = child.status \
+      WHERE child.parent_id = tasks.id AND child.deleted_at IS NULL AND s.category = 'done') AS subtasks_done, \
+     (SELECT COUNT(*) FROM task_comments c WHERE c.task_id = tasks.id) AS comment_count, \
+     COALESCE((SELECT json_agg(json_build_object('id', l.id, 'name', l.name, 'color', l.color) ORDER BY l.name) \
+               FROM task_labels tl JOIN labels l ON l.id = tl.label_id WHERE tl.task_id = tasks.id), '[]') AS labels";
+
+/// Text that full-text search runs over.
+const SEARCH_TEXT: &str = "title || ' ' || COALESCE(description, '')";
+
+/// Document expression for full-text search. Must match the `idx_tasks_search`
+/// GIN index definition exactly for Postgres to use the index.
+const SEARCH_DOCUMENT: &str = "to_tsvector('english', title || ' ' || COALESCE(description, ''))";
+
+/// `ts_headline` options used for search snippets.
+const HEADLINE_OPTIONS: &str = "StartSel=<mark>, StopSel=</mark>, MaxFragments=2";
+
+/// Retrieves a page of tasks for the authenticated user.
+///
+/// This endpoint fetches tasks the authenticated user owns or is assigned to,
+/// narrowed with `scope`. It supports filtering by `status`, `priority`,
+/// `assigned_to` (user ID), `parent_id`, `labels`, `user_id` (owner ID), and a `search` term which
+/// looks for matches in task titles and descriptions. Archived tasks are left out
+/// unless `include_archived=true`.
+///
+/// By default `search` is a Postgres full-text query (`websearch_to_tsquery`, so
+/// quoted phrases, `or` and `-term` work) served by the `idx_tasks_search` index.
+/// Matching tasks are ordered by `ts_rank` and carry `rank` and a highlighted
+/// `headline` snippet. `match=substring` restores plain `ILIKE` matching.
+///
+/// Results are paginated with an opaque keyset cursor over the sort key and the
+/// task `id`, so pages stay stable even when many tasks share the same sort value.
+/// Tasks are ordered by creation date in descending order unless `sort`/`order` are given.
 ///
 /// ## Query Parameters:
-/// - `status` (optional): Filters tasks by their status (e.g., "todo", "inprogress", "done").
+/// - `scope` (optional): `owned`, `assigned`, or `all` (default: owned or assigned).
+/// - `status` (optional): Filters tasks by their status (e.g., "todo", "in_progress", "done").
 /// - `priority` (optional): Filters tasks by their priority (e.g., "low", "medium", "high").
 /// - `assigned_to` (optional): Filters tasks by the ID of the user they are assigned to.
-/// - `search` (optional): A string to search for in task titles and descriptions (case-insensitive).
+/// - `parent_id` (optional): Lists only the direct subtasks of the given task.
+/// - `labels` (optional): Comma-separated label names; lists tasks carrying them.
+/// - `labels_match` (optional): `any` (default) or `all` of the `labels`.
+/// - `user_id` (optional): Filters tasks by the ID of the user who owns them.
+/// - `search` (optional): A string to search for in task titles and descriptions.
+/// - `match` (optional): `fulltext` (default) or `substring` (case-insensitive `ILIKE`).
+/// - `limit` (optional): Page size, between 1 and 100. Defaults to 50.
+/// - `cursor` (optional): The `next_cursor` value from a previous page.
+/// - `sort` (optional): One of `created_at`, `updated_at`, `due_date`, `priority`, `status`
+///   (the `sort_order` of the owner's status), or `relevance` (full-text searches only, and
+///   their default).
+/// - `order` (optional): `asc` or `desc`. Defaults to `desc`. `NULL` values always sort last.
+/// - `include_archived` (optional): `true` to list archived tasks as well. Defaults to `false`.
 ///
 /// ## Responses:
-/// - `200 OK`: Returns a JSON array of `Task` objects.
+/// - `200 OK`: Returns a JSON object `{ "items": [Task], "next_cursor": string|null, "has_more": bool }`.
+/// - `400 Bad Request`: If the cursor is malformed or was issued for a different sort,
+///   or if `sort=relevance` is used without a full-text search.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[get("")]
@@ -35,15 +107,49 @@ pub async fn get_tasks(
 ) -> Result<impl Responder, AppError> {
     let authenticated_user_id = user_id.0;
 
-    // Base query to select tasks for the authenticated user.
-    // Conditions for status, priority, assigned_to, and search terms are dynamically appended.
-    let mut sql = String::from(
-        "SELECT id, title, description, priority, status, due_date, created_at, updated_at, user_id, assigned_to \
-         FROM tasks WHERE user_id = $1"
-    );
+    let match_mode = query_params.match_mode.unwrap_or_default();
+    let fulltext_search = match_mode == SearchMatch::Fulltext && query_params.search.is_some();
+    let sort = match query_params.sort {
+        Some(TaskSortField::Relevance) if !fulltext_search => {
+            return Err(AppError::BadRequest(
+                "Sorting by relevance requires a full-text search".into(),
+            ));
+        }
+        Some(sort) => sort,
+        None if fulltext_search => TaskSortField::Relevance,
+        None => TaskSortField::default(),
+    };
+    let order = query_params.order.unwrap_or_default();
+    let limit = clamp_limit(query_params.limit);
+    let cursor = query_params
+        .cursor
+        .as_deref()
+        .map(decode_cursor::<TaskCursor>)
+        .transpose()?;
+
+    if let Some(cursor) = &cursor {
+        if cursor.sort != sort || cursor.order != order {
+            return Err(AppError::BadRequest(
+                "Cursor does not match the requested sort order".into(),
+            ));
+        }
+    }
+
+    // Tasks are selected for the authenticated user ($1) according to the scope.
+    // Conditions for status, priority, assigned_to, user_id, search terms and the cursor are dynamically appended.
     let mut param_count = 2;
 
-    let mut conditions: Vec<String> = Vec::new();
+    let mut conditions: Vec<String> = vec![
+        match query_params.scope.unwrap_or_default() {
+            TaskScope::Owned => "user_id = $1".to_string(),
+            TaskScope::Assigned => "assigned_to = $1".to_string(),
+            TaskScope::All => "(user_id = $1 OR assigned_to = $1)".to_string(),
+        },
+        "deleted_at IS NULL".to_string(),
+    ];
+    if !query_params.include_archived {
+        conditions.push("archived_at IS NULL".to_string());
+    }
 
     if query_params.status.is_some() {
         conditions.push(format!("status = ${}", param_count));
@@ -57,24 +163,107 @@ pub async fn get_tasks(
         conditions.push(format!("assigned_to = ${}", param_count));
         param_count += 1;
     }
-    if query_params.search.is_some() {
-        conditions.push(format!("(title ILIKE ${}", param_count));
+    if query_params.parent_id.is_some() {
+        conditions.push(format!("parent_id = ${}", param_count));
+        param_count += 1;
+    }
+    let label_names = query_params
+        .labels
+        .as_deref()
+        .map(parse_label_names)
+        .filter(|names| !names.is_empty());
+    if label_names.is_some() {
+        let matching_labels = format!(
+            "FROM task_labels tl JOIN labels l ON l.id = tl.label_id \
+             WHERE tl.task_id = tasks.id AND l.name = ANY(${})",
+            param_count
+        );
+        conditions.push(match query_params.labels_match.unwrap_or_default() {
+            LabelMatch::Any => format!("EXISTS (SELECT 1 {})", matching_labels),
+            LabelMatch::All => format!(
+                "(SELECT COUNT(DISTINCT l.name) {}) = cardinality(${})",
+                matching_labels, param_count
+            ),
+        });
         param_count += 1;
-        conditions
-            .last_mut()
-            .unwrap()
-            .push_str(&format!(" OR description ILIKE ${})", param_count));
+    }
+    if query_params.user_id.is_some() {
+        conditions.push(format!("user_id = ${}", param_count));
         param_count += 1;
     }
 
-    if !conditions.is_empty() {
-        sql.push_str(" AND ");
-        sql.push_str(&conditions.join(" AND "));
+    // Full-text searches also produce a rank and a highlighted snippet; both
+    // reuse the same tsquery parameter.
+    let mut rank_expr = String::from("NULL::real");
+    let mut headline_expr = String::from("NULL::text");
+    if query_params.search.is_some() {
+        match match_mode {
+            SearchMatch::Fulltext => {
+                let tsquery = format!("websearch_to_tsquery('english', ${})", param_count);
+                conditions.push(format!("{} @@ {}", SEARCH_DOCUMENT, tsquery));
+                rank_expr = format!("ts_rank({}, {})", SEARCH_DOCUMENT, tsquery);
+                headline_expr = format!(
+                    "ts_headline('english', {}, {}, '{}')",
+                    SEARCH_TEXT, tsquery, HEADLINE_OPTIONS
+                );
+                param_count += 1;
+            }
+            SearchMatch::Substring => {
+                conditions.push(format!("(title ILIKE ${}", param_count));
+                param_count += 1;
+                conditions
+                    .last_mut()
+                    .unwrap()
+                    .push_str(&format!(" OR description ILIKE ${})", param_count));
+                param_count += 1;
+            }
+        }
     }
 
-    sql.push_str(" ORDER BY created_at DESC");
+    // Keyset condition: rows strictly after the cursor in (sort key, id) order,
+    // with NULL sort keys placed after every non-NULL key.
+    let sort_expr = sort
+        .column()
+        .map(str::to_string)
+        .unwrap_or_else(|| rank_expr.clone());
+    let op = order.after_operator();
+    if let Some(cursor) = &cursor {
+        if cursor.value.is_some() {
+            let value_param = format!("CAST(${} AS {})", param_count, sort.sql_type());
+            param_count += 1;
+            conditions.push(format!(
+                "({col} {op} {val} OR ({col} = {val} AND id {op} ${id}) OR {col} IS NULL)",
+                col = sort_expr,
+                op = op,
+                val = value_param,
+                id = param_count
+            ));
+        } else {
+            conditions.push(format!(
+                "({} IS NULL AND id {} ${})",
+                sort_expr, op, param_count
+            ));
+        }
+        param_count += 1;
+    }
 
-    let mut query_builder = sqlx::query_as::<_, Task>(&sql);
+    let mut sql = format!(
+        "SELECT {}, {} AS rank, {} AS headline, {} AS status_order FROM tasks WHERE {}",
+        TASK_COLUMNS,
+        rank_expr,
+        headline_expr,
+        STATUS_ORDER,
+        conditions.join(" AND ")
+    );
+
+    sql.push_str(&format!(
+        " ORDER BY {col} {dir} NULLS LAST, id {dir} LIMIT ${limit}",
+        col = sort_expr,
+        dir = order.as_sql(),
+        limit = param_count
+    ));
+
+    let mut query_builder = sqlx::query_as::<_, TaskListItem>(&sql);
 
     query_builder = query_builder.bind(authenticated_user_id);
 
@@ -87,15 +276,56 @@ pub async fn get_tasks(
     if let Some(assigned_to) = query_params.assigned_to {
         query_builder = query_builder.bind(assigned_to);
     }
+    if let Some(parent_id) = query_params.parent_id {
+        query_builder = query_builder.bind(parent_id);
+    }
+    if let Some(names) = &label_names {
+        query_builder = query_builder.bind(names);
+    }
+    if let Some(owner_id) = query_params.user_id {
+        query_builder = query_builder.bind(owner_id);
+    }
     if let Some(search) = &query_params.search {
-        let search_pattern = format!("%{}%", search);
-        query_builder = query_builder.bind(search_pattern.clone());
-        query_builder = query_builder.bind(search_pattern);
+        match match_mode {
+            SearchMatch::Fulltext => {
+                query_builder = query_builder.bind(search);
+            }
+            SearchMatch::Substring => {
+                let search_pattern = format!("%{}%", search);
+                query_builder = query_builder.bind(search_pattern.clone());
+                query_builder = query_builder.bind(search_pattern);
+            }
+        }
     }
+    if let Some(cursor) = &cursor {
+        if let Some(value) = &cursor.value {
+            query_builder = query_builder.bind(value);
+        }
+        query_builder = query_builder.bind(cursor.id);
+    }
+    // Fetch one extra row to learn whether another page exists.
+    query_builder = query_builder.bind(limit + 1);
+
+    let mut tasks = query_builder.fetch_all(&**pool).await?;
 
-    let tasks = query_builder.fetch_all(&**pool).await?;
+    let has_more = tasks.len() as i64 > limit;
+    tasks.truncate(limit as usize);
 
-    Ok(HttpResponse::Ok().json(tasks))
+    let next_cursor = match tasks.last() {
+        Some(last) if has_more => Some(encode_cursor(&TaskCursor {
+            sort,
+            order,
+            value: sort.cursor_value(last),
+            id: last.task.id,
+        })?),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(Page {
+        items: tasks,
+        next_cursor,
+        has_more,
+    }))
 }
 
 /// Creates a new task for the authenticated user.
@@ -109,18 +339,20 @@ pub async fn get_tasks(
 /// - `title`: The title of the task (required).
 /// - `description` (optional): A description of the task.
 /// - `priority` (optional): The priority of the task (e.g., "low", "medium", "high").
-/// - `status`: The status of the task (e.g., "todo", "inprogress", "done"). Defaults to "todo".
+/// - `status`: One of the user's statuses (e.g., "todo", "in_progress", "done").
 /// - `due_date` (optional): The due date for the task.
 ///
 /// ## Responses:
 /// - `201 Created`: Returns the newly created `Task` object as JSON.
-/// - `400 Bad Request`: If the input data is invalid (e.g., missing required fields in a way not caught by `validate`).
+/// - `400 Bad Request`: If the input data is invalid (e.g., missing required fields in a way not caught by `validate`),
+///   or the status is not one of the user's statuses.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
 /// - `422 Unprocessable Entity`: If input validation on `TaskInput` fails (e.g., title too short).
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[post("")]
 pub async fn create_task(
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     task_data: web::Json<TaskInput>,
     user_id: AuthenticatedUserId,
 ) -> Result<impl Responder, AppError> {
@@ -128,14 +360,53 @@ pub async fn create_task(
     task_data.validate()?;
 
     let authenticated_user_id = user_id.0;
-    let task = Task::new(task_data.into_inner(), authenticated_user_id);
+    let mut tx = pool.begin().await?;
+    if let Some(parent_id) = task_data.parent_id {
+        validate_parent(
+            &mut tx,
+            None,
+            parent_id,
+            authenticated_user_id,
+            config.max_task_depth,
+        )
+        .await?;
+    }
+    let result = insert_task(&mut tx, task_data.into_inner(), authenticated_user_id).await?;
+    record_event(
+        &mut tx,
+        Some(authenticated_user_id),
+        TaskEventType::Created,
+        None,
+        Some(&result),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(result))
+}
+
+/// Inserts a new task owned by `user_id` and returns the stored row.
+///
+/// The caller is responsible for validating `task_data` first, including its
+/// `parent_id` with `validate_parent`. The status is resolved against the
+/// user's statuses with `resolve_status`.
+pub(crate) async fn insert_task(
+    conn: &mut PgConnection,
+    task_data: TaskInput,
+    user_id: i32,
+) -> Result<Task, AppError> {
+    let mut task = Task::new(task_data, user_id);
+    task.status = resolve_status(&mut *conn, user_id, &task.status)
+        .await?
+        .name;
 
     // Insert task
-    let result = sqlx::query_as::<_, Task>(
-        "INSERT INTO tasks (id, title, description, priority, status, due_date, user_id)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)
-         RETURNING id, title, description, priority, status, due_date, created_at, updated_at, user_id, assigned_to"
-    )
+    let result = sqlx::query_as::<_, Task>(&format!(
+        "INSERT INTO tasks (id, title, description, priority, status, due_date, user_id, parent_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
     .bind(task.id)
     .bind(task.title)
     .bind(task.description)
@@ -143,59 +414,270 @@ pub async fn create_task(
     .bind(task.status)
     .bind(task.due_date)
     .bind(task.user_id)
-    .fetch_one(&**pool)
+    .bind(task.parent_id)
+    .fetch_one(&mut *conn)
     .await?;
 
-    Ok(HttpResponse::Created().json(result))
+    Ok(result)
+}
+
+/// Loads a task that is visible to `user_id`, along with the user's role on it.
+///
+/// A task is visible to its owner and to its assignee until it is moved to the
+/// trash. Tasks that do not exist, trashed tasks and tasks the user can't see all
+/// yield `AppError::NotFound`, so task IDs owned by other users are not leaked.
+pub(crate) async fn find_visible_task<'e, E: PgExecutor<'e>>(
+    executor: E,
+    task_id: Uuid,
+    user_id: i32,
+) -> Result<(Task, TaskRole), AppError> {
+    let task = sqlx::query_as::<_, Task>(&format!(
+        "SELECT {}
+         FROM tasks WHERE id = $1 AND (user_id = $2 OR assigned_to = $2) AND deleted_at IS NULL",
+        TASK_COLUMNS
+    ))
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Task not found".into()))?;
+
+    let role = if task.user_id == user_id {
+        TaskRole::Owner
+    } else {
+        TaskRole::Assignee
+    };
+    Ok((task, role))
+}
+
+/// Loads a task visible to `user_id` and checks that the user owns it.
+///
+/// Returns `AppError::NotFound` if the task is not visible at all, and
+/// `AppError::Forbidden` if the user is only its assignee.
+pub(crate) async fn find_owned_task<'e, E: PgExecutor<'e>>(
+    executor: E,
+    task_id: Uuid,
+    user_id: i32,
+) -> Result<Task, AppError> {
+    match find_visible_task(executor, task_id, user_id).await? {
+        (task, TaskRole::Owner) => Ok(task),
+        (_, TaskRole::Assignee) => Err(AppError::Forbidden(
+            "Only the task owner can perform this action".into(),
+        )),
+    }
+}
+
+/// Checks a change of `task`'s status to `status` and returns the status to store.
+///
+/// The status is resolved against the task owner's statuses with `resolve_status`,
+/// then checked with `ensure_transition_allowed` and `ensure_unblocked`.
+/// `assignee` is the task's assignee once the change is applied.
+///
+/// Call it in the write transaction with the task returned by `lock_task`, so that
+/// a concurrent status change cannot move the task out of the checked status
+/// before the new one is written.
+pub(crate) async fn check_status_change(
+    conn: &mut PgConnection,
+    task: &Task,
+    status: &TaskStatus,
+    assignee: Option<i32>,
+    actor_id: i32,
+) -> Result<StatusDefinition, AppError> {
+    let status = resolve_status(&mut *conn, task.user_id, status).await?;
+    ensure_transition_allowed(&mut *conn, task, &status.name, assignee, actor_id).await?;
+    ensure_unblocked(&mut *conn, task, &status).await?;
+    Ok(status)
+}
+
+/// Locks a task row for the rest of the transaction and returns its current state.
+///
+/// Call this before changing a task so the old values written by `record_event`
+/// and `record_assignment` cannot be overtaken by a concurrent write. Returns
+/// `AppError::NotFound` if the task has since been deleted.
+pub(crate) async fn lock_task(conn: &mut PgConnection, task_id: Uuid) -> Result<Task, AppError> {
+    sqlx::query_as::<_, Task>(&format!(
+        "SELECT {} FROM tasks WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        TASK_COLUMNS
+    ))
+    .bind(task_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Task not found".into()))
+}
+
+/// Computes the strong `ETag` of a task.
+///
+/// The tag combines the task ID with its `updated_at` timestamp, which the
+/// `update_tasks_updated_at` trigger bumps on every write, so it changes whenever
+/// the row does. Writes to data the task embeds from other rows bump it through
+/// `touch_tasks`.
+pub(crate) fn task_etag(task: &Task) -> EntityTag {
+    EntityTag::new_strong(format!(
+        "{}-{}",
+        task.id.simple(),
+        task.updated_at.timestamp_micros()
+    ))
+}
+
+/// Bumps `updated_at`, and with it the `ETag`, of the given tasks.
+///
+/// A task's representation also embeds data kept outside its row, such as its
+/// comment count, labels, subtask counts and dependency links. Writes to that data
+/// call this in their transaction so cached copies of the task are not revalidated.
+pub(crate) async fn touch_tasks<'e, E: PgExecutor<'e>>(
+    executor: E,
+    task_ids: &[Uuid],
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE tasks SET updated_at = now() WHERE id = ANY($1) AND deleted_at IS NULL")
+        .bind(task_ids)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Returns the tasks whose representation embeds details of the given tasks: their
+/// parents, through the subtask counts, and the tasks they block or are blocked by,
+/// through `blocked_by` and `blocks`.
+pub(crate) async fn embedding_tasks(
+    conn: &mut PgConnection,
+    task_ids: &[Uuid],
+) -> Result<Vec<Uuid>, AppError> {
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        "SELECT parent_id FROM tasks WHERE id = ANY($1) AND parent_id IS NOT NULL
+         UNION SELECT blocked_id FROM task_dependencies WHERE blocker_id = ANY($1)
+         UNION SELECT blocker_id FROM task_dependencies WHERE blocked_id = ANY($1)",
+    )
+    .bind(task_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(ids)
+}
+
+/// Refreshes the `ETag`s of the tasks that embed details of a task changed from
+/// `before` to `after`, including the parent it was moved away from. Called by
+/// `record_event`.
+pub(crate) async fn touch_embedding_tasks(
+    conn: &mut PgConnection,
+    before: Option<&Task>,
+    after: Option<&Task>,
+) -> Result<(), AppError> {
+    let embedded = |t: Option<&Task>| {
+        t.map(|t| {
+            (
+                t.title.clone(),
+                t.status.clone(),
+                t.parent_id,
+                t.assigned_to,
+                t.deleted_at.is_some(),
+            )
+        })
+    };
+    let Some(task) = after.or(before) else {
+        return Ok(());
+    };
+    if embedded(before) == embedded(after) {
+        return Ok(());
+    }
+    let mut ids = embedding_tasks(conn, &[task.id]).await?;
+    ids.extend(before.and_then(|t| t.parent_id));
+    touch_tasks(&mut *conn, &ids).await
+}
+
+/// Evaluates the `If-Match` header of a write request against the current task.
+///
+/// Returns the `updated_at` version the write must still apply to, so that a
+/// concurrent write landing between this check and the `UPDATE`/`DELETE` is caught
+/// as well. Returns `None` when the request carries no `If-Match` header or uses
+/// `If-Match: *`, and `AppError::PreconditionFailed` when no listed tag matches.
+pub(crate) fn check_if_match(
+    req: &HttpRequest,
+    task: &Task,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    if !req.headers().contains_key(header::IF_MATCH) {
+        return Ok(None);
+    }
+
+    let etag = task_etag(task);
+    match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => Ok(None),
+        Ok(IfMatch::Items(tags)) if tags.iter().any(|tag| tag.strong_eq(&etag)) => {
+            Ok(Some(task.updated_at))
+        }
+        _ => Err(AppError::PreconditionFailed(
+            "If-Match does not match the current task ETag".into(),
+        )),
+    }
+}
+
+/// Error for a guarded `UPDATE`/`DELETE` that matched no row.
+///
+/// With an `If-Match` version the task changed after the precondition was checked;
+/// without one it was deleted concurrently.
+pub(crate) fn write_miss_error(expected_version: Option<DateTime<Utc>>) -> AppError {
+    match expected_version {
+        Some(_) => {
+            AppError::PreconditionFailed("Task has been modified since it was last fetched".into())
+        }
+        None => AppError::NotFound("Task not found".into()),
+    }
+}
+
+/// Returns whether the `If-None-Match` header of `req` matches `etag` (weak comparison).
+fn if_none_match(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
 }
 
 /// Retrieves a specific task by its ID.
 ///
 /// This endpoint fetches a single task by its UUID.
-/// The authenticated user must be the owner of the task.
+/// The authenticated user must be the owner or the assignee of the task.
 ///
 /// ## Path Parameters:
 /// - `id`: The UUID of the task to retrieve.
 ///
 /// ## Responses:
-/// - `200 OK`: Returns the `Task` object as JSON if found and owned by the user.
+/// - `200 OK`: Returns the `Task` object as JSON if found and visible to the user, with its `ETag`,
+///   plus `blocked_by` and `blocks` listing its visible upstream and downstream dependencies.
+/// - `304 Not Modified`: If `If-None-Match` matches the task's current `ETag`.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
-/// - `404 Not Found`: If the task with the given ID does not exist or is not owned by the authenticated user.
+/// - `404 Not Found`: If the task with the given ID does not exist or is neither owned by nor assigned to the authenticated user.
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[get("/{id}")]
 pub async fn get_task(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     task_id: web::Path<Uuid>,
     user_id: AuthenticatedUserId,
 ) -> Result<impl Responder, AppError> {
-    let authenticated_user_id = user_id.0;
-    let task_uuid = task_id.into_inner();
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
 
-    let task = sqlx::query_as::<_, Task>(
-        "SELECT id, title, description, priority, status, due_date, created_at, updated_at, user_id, assigned_to 
-         FROM tasks WHERE id = $1"
-    )
-    .bind(task_uuid)
-    .fetch_optional(&**pool)
-    .await?;
-
-    match task {
-        Some(task) => {
-            if task.user_id != authenticated_user_id {
-                Err(AppError::NotFound("Task not found".into()))
-            } else {
-                Ok(HttpResponse::Ok().json(task))
-            }
-        }
-        None => Err(AppError::NotFound("Task not found".into())),
+    let etag = task_etag(&task);
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .finish());
     }
+
+    let (blocked_by, blocks) = load_dependency_links(&pool, task.id, user_id.0).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(etag))
+        .json(TaskDetail {
+            task,
+            blocked_by,
+            blocks,
+        }))
 }
 
 /// Updates an existing task.
 ///
 /// This endpoint allows an authenticated user to update a task they own.
 /// It expects a JSON payload conforming to `TaskInput` and the task's UUID in the path.
-/// Only the owner of the task can update it.
+/// Only the owner of the task can update it; assignees can change the status
+/// through `PUT /api/tasks/{id}/status` instead.
 ///
 /// ## Path Parameters:
 /// - `id`: The UUID of the task to update.
@@ -205,14 +687,23 @@ pub async fn get_task(
 /// See `create_task` for details on `TaskInput` fields.
 ///
 /// ## Responses:
-/// - `200 OK`: Returns the updated `Task` object as JSON.
+/// - `200 OK`: Returns the updated `Task` object as JSON, with its new `ETag`.
+/// - `400 Bad Request`: If the status is not one of the task owner's statuses.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
-/// - `404 Not Found`: If the task with the given ID does not exist or is not owned by the authenticated user.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task with the given ID does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the status moves into an `active` or `done` category while a blocker
+///   is not done.
+///   Also if the owner's workflow does not allow the status change, or the transition
+///   requires an assignee and the task has none.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
 /// - `422 Unprocessable Entity`: If input validation on `TaskInput` fails.
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[put("/{id}")]
 pub async fn update_task(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     task_id: web::Path<Uuid>,
     task_data: web::Json<TaskInput>,
     user_id: AuthenticatedUserId,
@@ -221,47 +712,329 @@ pub async fn update_task(
     let authenticated_user_id = user_id.0;
     let task_uuid = task_id.into_inner();
 
-    // First, verify ownership
-    let ownership_check = sqlx::query_as::<_, (i32,)>("SELECT user_id FROM tasks WHERE id = $1")
-        .bind(task_uuid)
-        .fetch_optional(&**pool)
-        .await?;
-
-    match ownership_check {
-        Some((owner_user_id,)) => {
-            if owner_user_id != authenticated_user_id {
-                return Err(AppError::NotFound(
-                    "Task not found or not owned by user".into(),
-                ));
-            }
-        }
-        None => return Err(AppError::NotFound("Task not found".into())),
-    }
+    // First, verify ownership and the client's precondition
+    let task = find_owned_task(&**pool, task_uuid, authenticated_user_id).await?;
+    let expected_version = check_if_match(&req, &task)?;
 
     // If ownership is verified, proceed with update
-    let result = sqlx::query_as::<_, Task>(
-        "UPDATE tasks 
-         SET title = $1, description = $2, priority = $3, status = $4, due_date = $5
-         WHERE id = $6 AND user_id = $7
-         RETURNING id, title, description, priority, status, due_date, created_at, updated_at, user_id, assigned_to"
+    let mut tx = pool.begin().await?;
+    if task_data.parent_id.is_some() {
+        lock_hierarchy(&mut tx, authenticated_user_id).await?;
+    }
+    let before = lock_task(&mut tx, task_uuid).await?;
+    if let Some(parent_id) = task_data
+        .parent_id
+        .filter(|&id| before.parent_id != Some(id))
+    {
+        validate_parent(
+            &mut tx,
+            Some(task_uuid),
+            parent_id,
+            authenticated_user_id,
+            config.max_task_depth,
+        )
+        .await?;
+    }
+    let status = check_status_change(
+        &mut tx,
+        &before,
+        &task_data.status,
+        before.assigned_to,
+        authenticated_user_id,
     )
+    .await?;
+    let result = sqlx::query_as::<_, Task>(&format!(
+        "UPDATE tasks 
+         SET title = $1, description = $2, priority = $3, status = $4, due_date = $5, parent_id = $9
+         WHERE id = $6 AND user_id = $7 AND ($8::timestamptz IS NULL OR updated_at = $8)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
     .bind(&task_data.title)
     .bind(&task_data.description)
     .bind(&task_data.priority)
-    .bind(&task_data.status)
+    .bind(&status.name)
     .bind(task_data.due_date)
     .bind(task_uuid)
     .bind(authenticated_user_id)
-    .fetch_one(&**pool)
+    .bind(expected_version)
+    .bind(task_data.parent_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| write_miss_error(expected_version))?;
+    record_event(
+        &mut tx,
+        Some(authenticated_user_id),
+        TaskEventType::Updated,
+        Some(&before),
+        Some(&result),
+    )
     .await?;
+    tx.commit().await?;
 
-    Ok(HttpResponse::Ok().json(result))
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&result)))
+        .json(result))
 }
 
-/// Deletes a task by its ID.
+/// Updates only the status of a task.
+///
+/// Unlike `PUT /api/tasks/{id}`, this endpoint is available to the task's assignee
+/// as well as its owner, letting assignees move work along without edit rights.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskStatusUpdate`:
+///   ```json
+///   { "status": "in_progress" }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object as JSON, with its new `ETag`.
+/// - `400 Bad Request`: If the status is not one of the task owner's statuses.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the workflow transition requires a reviewer and the authenticated
+///   user is not the task's owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the status moves into an `active` or `done` category while a blocker
+///   is not done.
+///   Also if the owner's workflow does not allow the status change, or the transition
+///   requires an assignee and the task has none.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("/{id}/status")]
+pub async fn update_task_status(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    status_data: web::Json<TaskStatusUpdate>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (task, _role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+    let expected_version = check_if_match(&req, &task)?;
+
+    let mut tx = pool.begin().await?;
+    let before = lock_task(&mut tx, task.id).await?;
+    let status = check_status_change(
+        &mut tx,
+        &before,
+        &status_data.status,
+        before.assigned_to,
+        user_id.0,
+    )
+    .await?;
+    let result = sqlx::query_as::<_, Task>(&format!(
+        "UPDATE tasks SET status = $1
+         WHERE id = $2 AND ($3::timestamptz IS NULL OR updated_at = $3)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
+    .bind(&status.name)
+    .bind(task.id)
+    .bind(expected_version)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| write_miss_error(expected_version))?;
+    record_event(
+        &mut tx,
+        Some(user_id.0),
+        TaskEventType::Updated,
+        Some(&before),
+        Some(&result),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&result)))
+        .json(result))
+}
+
+/// Partially updates a task using JSON Merge Patch (RFC 7396) semantics.
+///
+/// Only the fields present in the body are written. `null` clears nullable
+/// columns (`description`, `priority`, `due_date`, `assigned_to`); `title` and
+/// `status` cannot be cleared. The body may be sent as `application/merge-patch+json`
+/// or `application/json`.
+///
+/// The owner may patch any field. The assignee may only patch `status`.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task to update.
+///
+/// ## Request Body:
+/// A JSON object matching `TaskPatch`, for example:
+///   ```json
+///   { "status": "done", "due_date": null }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object as JSON, with its new `ETag`.
+/// - `400 Bad Request`: If the body contains unknown fields, `assigned_to` is not an existing user,
+///   or `status` is not one of the task owner's statuses.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the assignee tries to change anything other than `status`, or
+///   makes a workflow transition that requires a reviewer.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the status moves into an `active` or `done` category while a blocker
+///   is not done.
+///   Also if the owner's workflow does not allow the status change, or the transition
+///   requires an assignee and the task has none, or if `assigned_to` names a new assignee
+///   who has not verified their email address while `RESTRICT_UNVERIFIED_ACCOUNTS` is set.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
+/// - `422 Unprocessable Entity`: If validation fails or `title`/`status` is `null`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[patch("/{id}")]
+pub async fn patch_task(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    task_id: web::Path<Uuid>,
+    patch_data: web::Json<TaskPatch>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    patch_data.validate()?;
+    if let Some(field) = patch_data.null_required_field() {
+        return Err(AppError::ValidationError(format!(
+            "{}: cannot be null",
+            field
+        )));
+    }
+
+    let (task, role) = find_visible_task(&**pool, task_id.into_inner(), user_id.0).await?;
+    if role == TaskRole::Assignee && !patch_data.is_status_only() {
+        return Err(AppError::Forbidden(
+            "Assignees can only change the status of a task".into(),
+        ));
+    }
+    let expected_version = check_if_match(&req, &task)?;
+
+    if patch_data.is_empty() {
+        return Ok(HttpResponse::Ok()
+            .insert_header(ETag(task_etag(&task)))
+            .json(task));
+    }
+
+    if let Some(Some(assignee_id)) = patch_data.assigned_to {
+        if task.assigned_to != Some(assignee_id) {
+            ensure_assignable(&**pool, &config, assignee_id).await?;
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    if let Some(Some(_)) = patch_data.parent_id {
+        lock_hierarchy(&mut tx, user_id.0).await?;
+    }
+    let before = lock_task(&mut tx, task.id).await?;
+    if let Some(Some(parent_id)) = patch_data.parent_id {
+        if before.parent_id != Some(parent_id) {
+            validate_parent(
+                &mut tx,
+                Some(task.id),
+                parent_id,
+                user_id.0,
+                config.max_task_depth,
+            )
+            .await?;
+        }
+    }
+    let status = match &patch_data.status {
+        Some(Some(status)) => {
+            let assignee = patch_data.assigned_to.unwrap_or(before.assigned_to);
+            Some(check_status_change(&mut tx, &before, status, assignee, user_id.0).await?)
+        }
+        _ => None,
+    };
+
+    // Only columns present in the patch are written.
+    let mut assignments: Vec<String> = Vec::new();
+    let mut param_count = 1;
+    for (column, present) in [
+        ("title", patch_data.title.is_some()),
+        ("description", patch_data.description.is_some()),
+        ("priority", patch_data.priority.is_some()),
+        ("status", patch_data.status.is_some()),
+        ("due_date", patch_data.due_date.is_some()),
+        ("assigned_to", patch_data.assigned_to.is_some()),
+        ("parent_id", patch_data.parent_id.is_some()),
+    ] {
+        if present {
+            assignments.push(format!("{} = ${}", column, param_count));
+            param_count += 1;
+        }
+    }
+
+    let sql = format!(
+        "UPDATE tasks SET {} WHERE id = ${} AND (${}::timestamptz IS NULL OR updated_at = ${})
+         RETURNING {}",
+        assignments.join(", "),
+        param_count,
+        param_count + 1,
+        param_count + 1,
+        TASK_COLUMNS
+    );
+
+    let mut query_builder = sqlx::query_as::<_, Task>(&sql);
+    if let Some(title) = &patch_data.title {
+        query_builder = query_builder.bind(title);
+    }
+    if let Some(description) = &patch_data.description {
+        query_builder = query_builder.bind(description);
+    }
+    if let Some(priority) = &patch_data.priority {
+        query_builder = query_builder.bind(priority);
+    }
+    if let Some(status) = &status {
+        query_builder = query_builder.bind(&status.name);
+    }
+    if let Some(due_date) = patch_data.due_date {
+        query_builder = query_builder.bind(due_date);
+    }
+    if let Some(assigned_to) = patch_data.assigned_to {
+        query_builder = query_builder.bind(assigned_to);
+    }
+    if let Some(parent_id) = patch_data.parent_id {
+        query_builder = query_builder.bind(parent_id);
+    }
+    query_builder = query_builder.bind(task.id).bind(expected_version);
+
+    let result = query_builder
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| write_miss_error(expected_version))?;
+    if patch_data.assigned_to.is_some() {
+        record_assignment(
+            &mut tx,
+            task.id,
+            user_id.0,
+            before.assigned_to,
+            result.assigned_to,
+        )
+        .await?;
+    }
+    record_event(
+        &mut tx,
+        Some(user_id.0),
+        TaskEventType::Updated,
+        Some(&before),
+        Some(&result),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&result)))
+        .json(result))
+}
+
+/// Moves a task to the trash.
 ///
 /// This endpoint allows an authenticated user to delete a task they own.
-/// Only the owner of the task can delete it.
+/// Only the owner of the task can delete it. The task disappears from every other
+/// endpoint but is listed at `GET /api/tasks/trash`, where it can be restored
+/// until it is purged after `TRASH_RETENTION_DAYS`.
 ///
 /// ## Path Parameters:
 /// - `id`: The UUID of the task to delete.
@@ -269,10 +1042,13 @@ pub async fn update_task(
 /// ## Responses:
 /// - `204 No Content`: On successful deletion.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
-/// - `404 Not Found`: If the task with the given ID does not exist or is not owned by the authenticated user.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task with the given ID does not exist or is not visible to the authenticated user.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[delete("/{id}")]
 pub async fn delete_task(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     task_id: web::Path<Uuid>,
     user_id: AuthenticatedUserId,
@@ -280,19 +1056,33 @@ pub async fn delete_task(
     let authenticated_user_id = user_id.0;
     let task_uuid = task_id.into_inner();
 
-    let result = sqlx::query!(
-        "DELETE FROM tasks WHERE id = $1 AND user_id = $2",
-        task_uuid,
-        authenticated_user_id
+    let task = find_owned_task(&**pool, task_uuid, authenticated_user_id).await?;
+    let expected_version = check_if_match(&req, &task)?;
+
+    let mut tx = pool.begin().await?;
+    let before = lock_task(&mut tx, task_uuid).await?;
+    let result = sqlx::query(
+        "UPDATE tasks SET deleted_at = now()
+         WHERE id = $1 AND user_id = $2 AND ($3::timestamptz IS NULL OR updated_at = $3)",
     )
-    .execute(&**pool)
+    .bind(task_uuid)
+    .bind(authenticated_user_id)
+    .bind(expected_version)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(
-            "Task not found or not owned by user".into(),
-        ));
+        return Err(write_miss_error(expected_version));
     }
+    record_event(
+        &mut tx,
+        Some(authenticated_user_id),
+        TaskEventType::Deleted,
+        Some(&before),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -300,7 +1090,9 @@ pub async fn delete_task(
 /// Assigns a task to a specified user.
 ///
 /// The authenticated user must be the owner of the task to assign it.
-/// The assignee must be an existing user.
+/// The assignee must be an existing user, with a verified email address if
+/// `RESTRICT_UNVERIFIED_ACCOUNTS` is set. Once assigned, the task becomes
+/// visible to the assignee, who may change its status and add notes.
 ///
 /// ## Path Parameters:
 /// - `task_id`: The UUID of the task to assign.
@@ -317,11 +1109,17 @@ pub async fn delete_task(
 /// - `200 OK`: Returns the updated `Task` object with the new assignee.
 /// - `400 Bad Request`: If the `assignee_id` does not correspond to an existing user.
 /// - `401 Unauthorized`: If the request lacks a valid authentication token.
-/// - `404 Not Found`: If the task does not exist or is not owned by the authenticated user.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `409 Conflict`: If the assignee has not verified their email address and
+///   `RESTRICT_UNVERIFIED_ACCOUNTS` is set.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
 /// - `500 Internal Server Error`: For database errors or other unexpected issues.
 #[post("/{task_id}/assign")]
 pub async fn assign_task(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     task_id_path: web::Path<Uuid>,
     authenticated_user: AuthenticatedUserId,
     assignment_data: web::Json<crate::models::task::AssignTaskRequest>, // Explicit path
@@ -336,73 +1134,34 @@ pub async fn assign_task(
     );
 
     // 1. Verify task existence and ownership by the assigner
-    let task_owner_check: Option<(i32,)> =
-        sqlx::query_as("SELECT user_id FROM tasks WHERE id = $1")
-            .bind(task_uuid)
-            .fetch_optional(&**pool)
-            .await?;
+    let task = find_owned_task(&**pool, task_uuid, assigner_id).await?;
+    let expected_version = check_if_match(&req, &task)?;
 
-    match task_owner_check {
-        Some((owner_id,)) => {
-            eprintln!(
-                "[assign_task_DEBUG] Ownership check: task_uuid={} found with owner_id={}. Assigner_id={}",
-                task_uuid,
-                owner_id,
-                assigner_id
-            );
-            if owner_id != assigner_id {
-                eprintln!(
-                    "[assign_task_DEBUG] Ownership mismatch: task_uuid={} owner_id={} != assigner_id={}",
-                    task_uuid,
-                    owner_id,
-                    assigner_id
-                );
-                return Err(AppError::NotFound(
-                    "Task not found or not owned by user".into(),
-                ));
-            }
-        }
-        None => {
-            eprintln!(
-                "[assign_task_DEBUG] Task not found during ownership check: task_uuid={}",
-                task_uuid
-            );
-            return Err(AppError::NotFound("Task not found".into()));
-        }
-    }
-
-    // 2. Verify assignee_id exists as a user in the 'users' table.
-    let assignee_exists: Option<(i32,)> = sqlx::query_as("SELECT id FROM users WHERE id = $1")
-        .bind(assignee_id)
-        .fetch_optional(&**pool)
-        .await?;
-
-    if assignee_exists.is_none() {
-        eprintln!(
-            "[assign_task_DEBUG] Assignee user not found: assignee_id={}",
-            assignee_id
-        );
-        return Err(AppError::BadRequest("Assignee user not found".into()));
-    }
+    // 2. Verify assignee_id exists as a user who can be assigned tasks.
+    ensure_assignable(&**pool, &config, assignee_id).await?;
     eprintln!(
         "[assign_task_DEBUG] Assignee user check: assignee_id={} found.",
         assignee_id
     );
 
-    // 3. Update task: SET assigned_to = $assignee_id, updated_at = NOW()
+    // 3. Update task: SET assigned_to = $assignee_id, updated_at = NOW(), and record the handoff
     eprintln!(
         "[assign_task_DEBUG] Preparing to update task: task_uuid={}, assigner_id={}, assignee_id={}",
         task_uuid, assigner_id, assignee_id
     );
-    let updated_task = sqlx::query_as::<_, Task>(
+    let mut tx = pool.begin().await?;
+    let before = lock_task(&mut tx, task_uuid).await?;
+    let updated_task = sqlx::query_as::<_, Task>(&format!(
         "UPDATE tasks SET assigned_to = $1, updated_at = NOW() 
-         WHERE id = $2 AND user_id = $3 
-         RETURNING *",
-    )
+         WHERE id = $2 AND user_id = $3 AND ($4::timestamptz IS NULL OR updated_at = $4)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
     .bind(assignee_id)
     .bind(task_uuid)
     .bind(assigner_id) // Ensures ownership again during the atomic update
-    .fetch_one(&**pool)
+    .bind(expected_version) // Ensures the task is unchanged since the If-Match check
+    .fetch_optional(&mut *tx)
     .await
     .map_err(|e| {
         eprintln!(
@@ -411,18 +1170,101 @@ pub async fn assign_task(
         );
         let app_error: AppError = AppError::from(e);
         app_error
-    })?;
+    })?
+    .ok_or_else(|| write_miss_error(expected_version))?;
+
+    record_assignment(
+        &mut tx,
+        task_uuid,
+        assigner_id,
+        before.assigned_to,
+        Some(assignee_id),
+    )
+    .await?;
+    record_event(
+        &mut tx,
+        Some(assigner_id),
+        TaskEventType::Assigned,
+        Some(&before),
+        Some(&updated_task),
+    )
+    .await?;
+    tx.commit().await?;
 
     eprintln!(
         "[assign_task_DEBUG] Task successfully assigned: task_uuid={}",
         task_uuid
     );
-    Ok(HttpResponse::Ok().json(updated_task))
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&updated_task)))
+        .json(updated_task))
+}
+
+/// Removes the assignee from a task.
+///
+/// The authenticated user must be the owner of the task. The change is recorded
+/// in the task's assignment history. Unassigning a task that has no assignee
+/// succeeds without recording anything.
+///
+/// ## Path Parameters:
+/// - `task_id`: The UUID of the task to unassign.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Task` object with `assigned_to` cleared.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `403 Forbidden`: If the authenticated user is the task's assignee rather than its owner.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `412 Precondition Failed`: If `If-Match` does not match the task's current `ETag`.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{task_id}/assign")]
+pub async fn unassign_task(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    task_id_path: web::Path<Uuid>,
+    authenticated_user: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let task_uuid = task_id_path.into_inner();
+    let owner_id = authenticated_user.0;
+
+    let task = find_owned_task(&**pool, task_uuid, owner_id).await?;
+    let expected_version = check_if_match(&req, &task)?;
+
+    let mut tx = pool.begin().await?;
+    let before = lock_task(&mut tx, task_uuid).await?;
+    let updated_task = sqlx::query_as::<_, Task>(&format!(
+        "UPDATE tasks SET assigned_to = NULL
+         WHERE id = $1 AND user_id = $2 AND ($3::timestamptz IS NULL OR updated_at = $3)
+         RETURNING {}",
+        TASK_COLUMNS
+    ))
+    .bind(task_uuid)
+    .bind(owner_id)
+    .bind(expected_version)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| write_miss_error(expected_version))?;
+
+    record_assignment(&mut tx, task_uuid, owner_id, before.assigned_to, None).await?;
+    record_event(
+        &mut tx,
+        Some(owner_id),
+        TaskEventType::Unassigned,
+        Some(&before),
+        Some(&updated_task),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&updated_task)))
+        .json(updated_task))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::models::{TaskInput, TaskPriority, TaskStatus};
+    use actix_web::test::TestRequest;
     use validator::Validate; // For .validate() method
 
     // No longer async, no actix_rt needed.
@@ -435,8 +1277,9 @@ mod tests {
             title: "".to_string(),
             description: Some("Test Description".to_string()),
             priority: Some(TaskPriority::High),
-            status: TaskStatus::Todo,
+            status: TaskStatus::new("todo"),
             due_date: None,
+            parent_id: None,
         };
         assert!(
             invalid_input_empty_title.validate().is_err(),
@@ -449,8 +1292,9 @@ mod tests {
             title: long_title,
             description: Some("Test Description".to_string()),
             priority: Some(TaskPriority::Medium),
-            status: TaskStatus::InProgress,
+            status: TaskStatus::new("in_progress"),
             due_date: None,
+            parent_id: None,
         };
         assert!(
             invalid_input_long_title.validate().is_err(),
@@ -462,8 +1306,9 @@ mod tests {
             title: "Valid Title".to_string(),
             description: Some("Test Description".to_string()),
             priority: Some(TaskPriority::Low),
-            status: TaskStatus::Done,
+            status: TaskStatus::new("done"),
             due_date: None,
+            parent_id: None,
         };
         assert!(
             valid_input.validate().is_ok(),
@@ -476,8 +1321,9 @@ mod tests {
             title: "Valid title for desc test".to_string(),
             description: Some(long_description),
             priority: Some(TaskPriority::Low),
-            status: TaskStatus::Todo,
+            status: TaskStatus::new("todo"),
             due_date: None,
+            parent_id: None,
         };
         assert!(
             invalid_input_long_desc.validate().is_err(),
@@ -489,4 +1335,86 @@ mod tests {
         // their validation is mostly about type correctness and presence for status, which serde handles at deserialization.
         // The `Validate` derive on TaskInput primarily handles the string length constraints on title and description.
     }
+
+    fn sample_task() -> Task {
+        let now = Utc::now();
+        Task {
+            id: Uuid::new_v4(),
+            title: "ETag task".to_string(),
+            description: None,
+            priority: None,
+            status: TaskStatus::new("todo"),
+            due_date: None,
+            created_at: now,
+            updated_at: now,
+            user_id: 1,
+            assigned_to: None,
+            parent_id: None,
+            deleted_at: None,
+            completed_at: None,
+            archived_at: None,
+            subtasks_total: 0,
+            subtasks_done: 0,
+            comment_count: 0,
+            labels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_task_etag_tracks_updated_at() {
+        let mut task = sample_task();
+        let original = task_etag(&task);
+        assert!(!original.weak);
+        assert!(original.strong_eq(&task_etag(&task)));
+
+        task.updated_at += chrono::Duration::microseconds(1);
+        assert!(!original.strong_eq(&task_etag(&task)));
+    }
+
+    #[test]
+    fn test_check_if_match() {
+        let task = sample_task();
+        let etag = task_etag(&task);
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(check_if_match(&req, &task).unwrap(), None);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_MATCH, "*"))
+            .to_http_request();
+        assert_eq!(check_if_match(&req, &task).unwrap(), None);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_MATCH, format!("\"stale\", {}", etag)))
+            .to_http_request();
+        assert_eq!(check_if_match(&req, &task).unwrap(), Some(task.updated_at));
+
+        // If-Match uses the strong comparison, so a weak tag never matches.
+        let req = TestRequest::default()
+            .insert_header((header::IF_MATCH, format!("W/\"{}\"", etag.tag())))
+            .to_http_request();
+        assert!(matches!(
+            check_if_match(&req, &task),
+            Err(AppError::PreconditionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let task = sample_task();
+        let etag = task_etag(&task);
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!if_none_match(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, format!("W/\"{}\"", etag.tag())))
+            .to_http_request();
+        assert!(if_none_match(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"stale\""))
+            .to_http_request();
+        assert!(!if_none_match(&req, &etag));
+    }
 }
@@ -0,0 +1,129 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{
+        pagination::{clamp_limit, decode_cursor, encode_cursor},
+        task::{TrashCursor, TrashQuery},
+        Page, Task, TaskEventType,
+    },
+    routes::{
+        activity::record_event,
+        tasks::{task_etag, TASK_COLUMNS},
+    },
+};
+use actix_web::{get, http::header::ETag, post, web, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Lists the authenticated user's deleted tasks, most recently deleted first.
+///
+/// Only owners can delete tasks, so the trash only holds tasks the user owns.
+/// Tasks stay here until they are restored or purged after `TRASH_RETENTION_DAYS`.
+///
+/// ## Query Parameters:
+/// - `limit` (optional): Page size, between 1 and 100. Defaults to 50.
+/// - `cursor` (optional): The `next_cursor` value from a previous page.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON object `{ "items": [Task], "next_cursor": string|null, "has_more": bool }`.
+/// - `400 Bad Request`: If the cursor is malformed.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/trash")]
+pub async fn get_trash(
+    pool: web::Data<PgPool>,
+    query_params: web::Query<TrashQuery>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let limit = clamp_limit(query_params.limit);
+    let cursor = query_params
+        .cursor
+        .as_deref()
+        .map(decode_cursor::<TrashCursor>)
+        .transpose()?;
+
+    let mut tasks = sqlx::query_as::<_, Task>(&format!(
+        "SELECT {} FROM tasks
+         WHERE user_id = $1 AND deleted_at IS NOT NULL
+           AND ($2::timestamptz IS NULL OR (deleted_at, id) < ($2, $3))
+         ORDER BY deleted_at DESC, id DESC LIMIT $4",
+        TASK_COLUMNS
+    ))
+    .bind(user_id.0)
+    .bind(cursor.as_ref().map(|c| c.deleted_at))
+    .bind(cursor.as_ref().map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(&**pool)
+    .await?;
+
+    let has_more = tasks.len() as i64 > limit;
+    tasks.truncate(limit as usize);
+    let next_cursor = match tasks.last() {
+        Some(last) if has_more => Some(encode_cursor(&TrashCursor {
+            deleted_at: last.deleted_at.unwrap_or(last.updated_at),
+            id: last.id,
+        })?),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(Page {
+        items: tasks,
+        next_cursor,
+        has_more,
+    }))
+}
+
+/// Restores a task from the authenticated user's trash.
+///
+/// The task returns with its notes, comments, attachments, labels, dependencies
+/// and assignee, and becomes visible to its assignee again. A `restored` event is
+/// recorded in its activity log.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the restored `Task` object as JSON, with its `ETag`.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task is not in the authenticated user's trash.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/restore")]
+pub async fn restore_task(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let mut tx = pool.begin().await?;
+    let trashed: Option<(Uuid,)> = sqlx::query_as(
+        "SELECT id FROM tasks
+         WHERE id = $1 AND user_id = $2 AND deleted_at IS NOT NULL
+         FOR UPDATE",
+    )
+    .bind(task_id.into_inner())
+    .bind(user_id.0)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (task_id,) = trashed.ok_or_else(|| AppError::NotFound("Task not found in trash".into()))?;
+
+    let restored = sqlx::query_as::<_, Task>(&format!(
+        "UPDATE tasks SET deleted_at = NULL WHERE id = $1 RETURNING {}",
+        TASK_COLUMNS
+    ))
+    .bind(task_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    // Recorded like a creation, so the event lists the task's fields as they reappear.
+    record_event(
+        &mut tx,
+        Some(user_id.0),
+        TaskEventType::Restored,
+        None,
+        Some(&restored),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(task_etag(&restored)))
+        .json(restored))
+}
@@ -0,0 +1,324 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    config::Config,
+    error::AppError,
+    models::{
+        pagination::{clamp_limit, decode_cursor, encode_cursor},
+        webhook::{WebhookDeliveryCursor, WebhookDeliveryQuery, WebhookWithSecret},
+        Page, Webhook, WebhookDelivery, WebhookInput,
+    },
+    webhooks::{check_target, generate_secret},
+};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Columns selected for a `Webhook`. The secret is deliberately left out.
+const WEBHOOK_COLUMNS: &str = "id, user_id, url, event_types, active, consecutive_failures, \
+                               disabled_at, created_at, updated_at";
+
+/// Columns selected for a `WebhookDelivery`.
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event_id, event_type, payload, status, attempts, \
+                                next_attempt_at, last_attempt_at, response_status, last_error, \
+                                redelivery_of, created_at, delivered_at";
+
+/// Loads a webhook registered by `user_id`, or returns `AppError::NotFound`.
+async fn find_owned_webhook(
+    pool: &PgPool,
+    webhook_id: Uuid,
+    user_id: i32,
+) -> Result<Webhook, AppError> {
+    sqlx::query_as::<_, Webhook>(&format!(
+        "SELECT {} FROM webhooks WHERE id = $1 AND user_id = $2",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(webhook_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook not found".into()))
+}
+
+/// Lists the authenticated user's webhooks, oldest first.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `Webhook` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("")]
+pub async fn get_webhooks(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let webhooks = sqlx::query_as::<_, Webhook>(&format!(
+        "SELECT {} FROM webhooks WHERE user_id = $1 ORDER BY created_at, id",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(user_id.0)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+/// Registers a webhook for the authenticated user.
+///
+/// From then on, every event of the chosen types on a task the user can see is
+/// POSTed to the URL as JSON, `{ "event": TaskEvent, "task": Task|null }`, with
+/// these headers:
+/// - `X-TaskForge-Event`: The event type.
+/// - `X-TaskForge-Delivery`: The delivery's ID, the same on every retry.
+/// - `X-TaskForge-Signature`: `sha256=` and the hex HMAC-SHA256 of the body, keyed
+///   with the webhook's secret.
+///
+/// Any 2xx response counts as delivered; anything else is retried with exponential
+/// backoff. The webhook is disabled after repeated consecutive failures.
+///
+/// ## Request Body:
+/// A JSON object matching `WebhookInput`:
+///   ```json
+///   { "url": "https://ci.example.com/hooks/taskforge", "event_types": ["created", "updated"] }
+///   ```
+///
+/// ## Responses:
+/// - `201 Created`: Returns the new `Webhook` as JSON, with its `secret`. The secret
+///   is not returned again.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `422 Unprocessable Entity`: If the URL is not an `http` or `https` URL, its host
+///   cannot be resolved or resolves to a private address (unless
+///   `WEBHOOK_ALLOW_PRIVATE_TARGETS` is set), or no event types are given.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("")]
+pub async fn create_webhook(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    webhook_data: web::Json<WebhookInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    webhook_data.validate()?;
+    check_target(&webhook_data.url, config.webhook_allow_private_targets).await?;
+
+    let secret = generate_secret();
+    let webhook = sqlx::query_as::<_, Webhook>(&format!(
+        "INSERT INTO webhooks (user_id, url, secret, event_types, active)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING {}",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(user_id.0)
+    .bind(&webhook_data.url)
+    .bind(&secret)
+    .bind(&webhook_data.event_types)
+    .bind(webhook_data.active)
+    .fetch_one(&**pool)
+    .await?;
+
+    Ok(HttpResponse::Created().json(WebhookWithSecret { webhook, secret }))
+}
+
+/// Retrieves one of the authenticated user's webhooks.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the webhook.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the `Webhook` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the webhook does not exist or belongs to another user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}")]
+pub async fn get_webhook(
+    pool: web::Data<PgPool>,
+    webhook_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let webhook = find_owned_webhook(&pool, webhook_id.into_inner(), user_id.0).await?;
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// Changes the URL, event types or state of one of the authenticated user's webhooks.
+///
+/// Setting `active` to `true` re-enables a webhook that was disabled after failed
+/// deliveries; its pending deliveries are then retried.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the webhook.
+///
+/// ## Request Body:
+/// A JSON object matching `WebhookInput`.
+///
+/// ## Responses:
+/// - `200 OK`: Returns the updated `Webhook` object as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the webhook does not exist or belongs to another user.
+/// - `422 Unprocessable Entity`: If the URL or event types are invalid, as for creation.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("/{id}")]
+pub async fn update_webhook(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    webhook_id: web::Path<Uuid>,
+    webhook_data: web::Json<WebhookInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    webhook_data.validate()?;
+    check_target(&webhook_data.url, config.webhook_allow_private_targets).await?;
+
+    let webhook = sqlx::query_as::<_, Webhook>(&format!(
+        "UPDATE webhooks
+         SET url = $3, event_types = $4, active = $5,
+             consecutive_failures = CASE WHEN $5 THEN 0 ELSE consecutive_failures END,
+             disabled_at = CASE WHEN $5 THEN NULL ELSE disabled_at END,
+             updated_at = now()
+         WHERE id = $1 AND user_id = $2
+         RETURNING {}",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(webhook_id.into_inner())
+    .bind(user_id.0)
+    .bind(&webhook_data.url)
+    .bind(&webhook_data.event_types)
+    .bind(webhook_data.active)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook not found".into()))?;
+
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// Deletes one of the authenticated user's webhooks along with its delivery log.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the webhook.
+///
+/// ## Responses:
+/// - `204 No Content`: On successful deletion.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the webhook does not exist or belongs to another user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("/{id}")]
+pub async fn delete_webhook(
+    pool: web::Data<PgPool>,
+    webhook_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(webhook_id.into_inner())
+        .bind(user_id.0)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Webhook not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lists a webhook's delivery log, newest first.
+///
+/// Each entry shows the payload sent, the number of attempts, the last response
+/// status or error, and when the next retry is due.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the webhook.
+///
+/// ## Query Parameters:
+/// - `status` (optional): Only list `pending`, `succeeded` or `failed` deliveries.
+/// - `limit` (optional): Page size, between 1 and 100. Defaults to 50.
+/// - `cursor` (optional): The `next_cursor` value from a previous page.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON object `{ "items": [WebhookDelivery], "next_cursor": string|null, "has_more": bool }`.
+/// - `400 Bad Request`: If the cursor is malformed.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the webhook does not exist or belongs to another user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/deliveries")]
+pub async fn get_webhook_deliveries(
+    pool: web::Data<PgPool>,
+    webhook_id: web::Path<Uuid>,
+    query_params: web::Query<WebhookDeliveryQuery>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let webhook = find_owned_webhook(&pool, webhook_id.into_inner(), user_id.0).await?;
+
+    let limit = clamp_limit(query_params.limit);
+    let after_id = match &query_params.cursor {
+        Some(cursor) => Some(decode_cursor::<WebhookDeliveryCursor>(cursor)?.id),
+        None => None,
+    };
+
+    let mut deliveries = sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT {} FROM webhook_deliveries
+         WHERE webhook_id = $1
+           AND ($2::bigint IS NULL OR id < $2)
+           AND ($3::webhook_delivery_status IS NULL OR status = $3)
+         ORDER BY id DESC LIMIT $4",
+        DELIVERY_COLUMNS
+    ))
+    .bind(webhook.id)
+    .bind(after_id)
+    .bind(query_params.status)
+    .bind(limit + 1)
+    .fetch_all(&**pool)
+    .await?;
+
+    let has_more = deliveries.len() as i64 > limit;
+    deliveries.truncate(limit as usize);
+    let next_cursor = match deliveries.last() {
+        Some(last) if has_more => Some(encode_cursor(&WebhookDeliveryCursor { id: last.id })?),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(Page {
+        items: deliveries,
+        next_cursor,
+        has_more,
+    }))
+}
+
+/// Queues a delivery to be sent again.
+///
+/// The original entry is left as it is; a new delivery with the same payload is
+/// queued for immediate sending and linked to it through `redelivery_of`.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the webhook.
+/// - `delivery_id`: The ID of the delivery to send again.
+///
+/// ## Responses:
+/// - `202 Accepted`: Returns the new, pending `WebhookDelivery` as JSON.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the webhook or delivery does not exist or belongs to another user.
+/// - `409 Conflict`: If the webhook is disabled; re-enable it first.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[post("/{id}/deliveries/{delivery_id}/redeliver")]
+pub async fn redeliver_webhook_delivery(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, i64)>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let (webhook_id, delivery_id) = path.into_inner();
+    let webhook = find_owned_webhook(&pool, webhook_id, user_id.0).await?;
+    if !webhook.active {
+        return Err(AppError::Conflict(
+            "Webhook is disabled; re-enable it before redelivering".into(),
+        ));
+    }
+
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, payload, redelivery_of)
+         SELECT webhook_id, event_id, event_type, payload, id
+         FROM webhook_deliveries WHERE id = $1 AND webhook_id = $2
+         RETURNING {}",
+        DELIVERY_COLUMNS
+    ))
+    .bind(delivery_id)
+    .bind(webhook.id)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Delivery not found".into()))?;
+
+    Ok(HttpResponse::Accepted().json(delivery))
+}
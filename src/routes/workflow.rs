@@ -0,0 +1,363 @@
+use crate::{
+    auth::extractors::AuthenticatedUserId,
+    error::AppError,
+    models::{AvailableTransition, Task, TaskStatus, Workflow, WorkflowInput, WorkflowTransition},
+    routes::{statuses::load_statuses, tasks::find_visible_task},
+};
+use actix_web::{delete, get, put, web, HttpResponse, Responder};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Loads the workflow that applies to tasks owned by `user_id`, falling back to
+/// the default workflow over the user's statuses if the user has not defined one.
+pub(crate) async fn load_workflow(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> Result<Workflow, AppError> {
+    let transitions = sqlx::query_as::<_, WorkflowTransition>(
+        "SELECT w.from_status, w.to_status, w.requires_assignee, w.requires_reviewer
+         FROM workflow_transitions w
+         JOIN task_statuses f ON f.user_id = w.user_id AND f.name = w.from_status
+         JOIN task_statuses t ON t.user_id = w.user_id AND t.name = w.to_status
+         WHERE w.user_id = $1
+         ORDER BY f.sort_order, f.name, t.sort_order, t.name",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if transitions.is_empty() {
+        let statuses: Vec<TaskStatus> = load_statuses(&mut *conn, user_id)
+            .await?
+            .into_iter()
+            .map(|status| status.name)
+            .collect();
+        return Ok(Workflow::default_workflow(&statuses));
+    }
+    Ok(Workflow {
+        custom: true,
+        transitions,
+    })
+}
+
+/// Checks that `actor_id` may move `task` to `new_status` under `workflow`.
+///
+/// `assignee` is the task's assignee once the change is applied, which differs from
+/// `task.assigned_to` when a patch assigns and moves the task at once. Keeping the
+/// current status is always allowed.
+fn check_transition(
+    workflow: &Workflow,
+    task: &Task,
+    new_status: &TaskStatus,
+    assignee: Option<i32>,
+    actor_id: i32,
+) -> Result<(), AppError> {
+    if *new_status == task.status {
+        return Ok(());
+    }
+    let (from, to) = (task.status.as_str(), new_status.as_str());
+
+    let Some(transition) = workflow.transition(&task.status, new_status) else {
+        let allowed: Vec<&str> = workflow
+            .transitions_from(&task.status)
+            .map(|t| t.to.as_str())
+            .collect();
+        return Err(AppError::InvalidTransition(if allowed.is_empty() {
+            format!("The workflow does not allow tasks to leave {}", from)
+        } else {
+            format!(
+                "The workflow does not allow moving a task from {} to {}; from {} it can move to {}",
+                from,
+                to,
+                from,
+                allowed.join(", ")
+            )
+        }));
+    };
+    if transition.requires_assignee && assignee.is_none() {
+        return Err(AppError::InvalidTransition(format!(
+            "The task must be assigned before it can move from {} to {}",
+            from, to
+        )));
+    }
+    if transition.requires_reviewer && actor_id != task.user_id {
+        return Err(AppError::Forbidden(format!(
+            "Only the task owner can review a task from {} to {}",
+            from, to
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `actor_id` may move `task` to `new_status` under its owner's workflow.
+///
+/// Transitions the workflow does not list, and transitions whose `requires_assignee`
+/// rule is not met, are rejected with `AppError::InvalidTransition`. Transitions
+/// that `requires_reviewer` are rejected with `AppError::Forbidden` for anyone but
+/// the task's owner. Keeping the current status is always allowed.
+pub(crate) async fn ensure_transition_allowed(
+    conn: &mut PgConnection,
+    task: &Task,
+    new_status: &TaskStatus,
+    assignee: Option<i32>,
+    actor_id: i32,
+) -> Result<(), AppError> {
+    if *new_status == task.status {
+        return Ok(());
+    }
+    let workflow = load_workflow(conn, task.user_id).await?;
+    check_transition(&workflow, task, new_status, assignee, actor_id)
+}
+
+/// Returns the authenticated user's workflow, which applies to every task they own.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a `Workflow` object as JSON. `custom` is `false` while the
+///   default workflow, which allows every transition, is in effect.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("")]
+pub async fn get_workflow(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let mut conn = pool.acquire().await?;
+    let workflow = load_workflow(&mut conn, user_id.0).await?;
+    Ok(HttpResponse::Ok().json(workflow))
+}
+
+/// Replaces the authenticated user's workflow.
+///
+/// Only the listed transitions are allowed afterwards, for every task the user
+/// owns. Tasks already in a status the workflow cannot leave stay there until the
+/// workflow changes.
+///
+/// ## Request Body:
+/// A JSON object matching `WorkflowInput`:
+///   ```json
+///   { "transitions": [
+///       { "from": "todo", "to": "in_progress", "requires_assignee": true },
+///       { "from": "in_progress", "to": "review" },
+///       { "from": "review", "to": "done", "requires_reviewer": true },
+///       { "from": "review", "to": "in_progress" }
+///   ] }
+///   ```
+///
+/// ## Responses:
+/// - `200 OK`: Returns the new `Workflow` object as JSON.
+/// - `400 Bad Request`: If a transition names a status the user does not have.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `422 Unprocessable Entity`: If there are no transitions, or a transition is
+///   repeated or keeps the same status.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[put("")]
+pub async fn update_workflow(
+    pool: web::Data<PgPool>,
+    workflow_data: web::Json<WorkflowInput>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    workflow_data.validate()?;
+
+    let mut tx = pool.begin().await?;
+    let statuses = load_statuses(&mut *tx, user_id.0).await?;
+    let unknown = workflow_data
+        .transitions
+        .iter()
+        .flat_map(|transition| [&transition.from, &transition.to])
+        .find(|name| !statuses.iter().any(|status| status.name == **name));
+    if let Some(name) = unknown {
+        return Err(AppError::BadRequest(format!("Unknown status '{}'", name)));
+    }
+
+    sqlx::query("DELETE FROM workflow_transitions WHERE user_id = $1")
+        .bind(user_id.0)
+        .execute(&mut *tx)
+        .await?;
+    for transition in &workflow_data.transitions {
+        sqlx::query(
+            "INSERT INTO workflow_transitions
+                 (user_id, from_status, to_status, requires_assignee, requires_reviewer)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(user_id.0)
+        .bind(&transition.from)
+        .bind(&transition.to)
+        .bind(transition.requires_assignee)
+        .bind(transition.requires_reviewer)
+        .execute(&mut *tx)
+        .await?;
+    }
+    let workflow = load_workflow(&mut tx, user_id.0).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(workflow))
+}
+
+/// Resets the authenticated user's workflow to the default, which allows every transition.
+///
+/// ## Responses:
+/// - `204 No Content`: The default workflow is in effect.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[delete("")]
+pub async fn reset_workflow(
+    pool: web::Data<PgPool>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    sqlx::query("DELETE FROM workflow_transitions WHERE user_id = $1")
+        .bind(user_id.0)
+        .execute(&**pool)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lists the transitions out of a task's current status under its owner's
+/// workflow, and whether the authenticated user can make each one right now.
+///
+/// Meant for rendering status buttons; `reason` explains disabled ones.
+///
+/// ## Path Parameters:
+/// - `id`: The UUID of the task.
+///
+/// ## Responses:
+/// - `200 OK`: Returns a JSON array of `AvailableTransition` objects.
+/// - `401 Unauthorized`: If the request lacks a valid authentication token.
+/// - `404 Not Found`: If the task does not exist or is not visible to the authenticated user.
+/// - `500 Internal Server Error`: For database errors or other unexpected issues.
+#[get("/{id}/transitions")]
+pub async fn get_task_transitions(
+    pool: web::Data<PgPool>,
+    task_id: web::Path<Uuid>,
+    user_id: AuthenticatedUserId,
+) -> Result<impl Responder, AppError> {
+    let mut conn = pool.acquire().await?;
+    let (task, _role) = find_visible_task(&mut *conn, task_id.into_inner(), user_id.0).await?;
+    let workflow = load_workflow(&mut conn, task.user_id).await?;
+
+    let transitions: Vec<AvailableTransition> = workflow
+        .transitions_from(&task.status)
+        .map(|transition| {
+            let reason = match check_transition(
+                &workflow,
+                &task,
+                &transition.to,
+                task.assigned_to,
+                user_id.0,
+            ) {
+                Ok(()) => None,
+                Err(AppError::InvalidTransition(msg)) | Err(AppError::Forbidden(msg)) => Some(msg),
+                Err(other) => Some(other.to_string()),
+            };
+            AvailableTransition {
+                transition: transition.clone(),
+                allowed: reason.is_none(),
+                reason,
+            }
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(transitions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskInput;
+
+    fn task(status: TaskStatus, owner: i32) -> Task {
+        Task::new(
+            TaskInput {
+                title: "Workflow".to_string(),
+                description: None,
+                priority: None,
+                status,
+                due_date: None,
+                parent_id: None,
+            },
+            owner,
+        )
+    }
+
+    fn review_workflow() -> Workflow {
+        let transition = |from, to, requires_assignee, requires_reviewer| WorkflowTransition {
+            from,
+            to,
+            requires_assignee,
+            requires_reviewer,
+        };
+        Workflow {
+            custom: true,
+            transitions: vec![
+                transition(
+                    TaskStatus::new("todo"),
+                    TaskStatus::new("in_progress"),
+                    true,
+                    false,
+                ),
+                transition(
+                    TaskStatus::new("in_progress"),
+                    TaskStatus::new("review"),
+                    false,
+                    false,
+                ),
+                transition(
+                    TaskStatus::new("review"),
+                    TaskStatus::new("done"),
+                    false,
+                    true,
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_check_transition_rejects_unlisted_transitions() {
+        let workflow = review_workflow();
+        let todo = task(TaskStatus::new("todo"), 1);
+
+        assert!(check_transition(&workflow, &todo, &TaskStatus::new("todo"), None, 1).is_ok());
+        match check_transition(&workflow, &todo, &TaskStatus::new("done"), Some(2), 1) {
+            Err(AppError::InvalidTransition(msg)) => {
+                assert!(msg.contains("from todo to done"));
+                assert!(msg.ends_with("can move to in_progress"));
+            }
+            other => panic!("Expected InvalidTransition, got {:?}", other),
+        }
+        match check_transition(
+            &workflow,
+            &task(TaskStatus::new("done"), 1),
+            &TaskStatus::new("todo"),
+            None,
+            1,
+        ) {
+            Err(AppError::InvalidTransition(msg)) => assert!(msg.contains("leave done")),
+            other => panic!("Expected InvalidTransition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_transition_rules() {
+        let workflow = review_workflow();
+
+        let todo = task(TaskStatus::new("todo"), 1);
+        assert!(matches!(
+            check_transition(&workflow, &todo, &TaskStatus::new("in_progress"), None, 1),
+            Err(AppError::InvalidTransition(_))
+        ));
+        assert!(check_transition(
+            &workflow,
+            &todo,
+            &TaskStatus::new("in_progress"),
+            Some(2),
+            2
+        )
+        .is_ok());
+
+        let review = task(TaskStatus::new("review"), 1);
+        assert!(matches!(
+            check_transition(&workflow, &review, &TaskStatus::new("done"), Some(2), 2),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(check_transition(&workflow, &review, &TaskStatus::new("done"), Some(2), 1).is_ok());
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    auth::{
+        token::{verify_token, Claims},
+        TOKEN_REVOCATIONS,
+    },
+    error::AppError,
+    models::realtime::{
+        ClientMessage, ServerMessage, Subscription, MAX_SUBSCRIBED_FILTERS, MAX_SUBSCRIBED_TASKS,
+    },
+    realtime::{TaskBroadcaster, TaskUpdate},
+};
+use actix_web::{dev::ServiceRequest, get, web, HttpRequest, HttpResponse};
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream, Session};
+use futures::StreamExt;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// How often the server pings the client.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long the client may stay silent, pongs included, before it is disconnected.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(75);
+
+/// Query parameters accepted by the WebSocket endpoint.
+#[derive(Debug, Deserialize)]
+pub struct WsAuthQuery {
+    /// The JWT, for clients that cannot set an `Authorization` header on the handshake.
+    pub token: Option<String>,
+}
+
+/// Returns the first line of `req` for the access log, like the `Logger`'s `%r`, but
+/// without the query string on this endpoint, where it carries a bearer token.
+pub fn logged_request_line(req: &ServiceRequest) -> String {
+    let target = match req.query_string() {
+        query if query.is_empty() || req.path() == "/api/ws" => req.path().to_string(),
+        query => format!("{}?{}", req.path(), query),
+    };
+    format!("{} {} {:?}", req.method(), target, req.version())
+}
+
+/// Opens a WebSocket that pushes task events in real time.
+///
+/// The JWT is taken from the `Authorization: Bearer` header or, since browsers
+/// cannot set headers on a WebSocket handshake, from the `token` query parameter.
+/// The server's access log leaves this endpoint's query string out (see
+/// `logged_request_line`), so tokens sent that way are not written to it.
+/// The connection is closed when the token expires. Revoked tokens are rejected
+/// at the handshake, and open connections are closed once a heartbeat finds their
+/// token revoked, for example by a logout or a revoked session.
+///
+/// The server sends a JSON `ServerMessage` for every task the user can see that is
+/// created, updated, assigned, unassigned, deleted, restored, archived or unarchived:
+///   ```json
+///   { "type": "task_event", "event": { "event_type": "updated", "changes": { … }, … }, "task": { … } }
+///   ```
+/// By default every such event is sent. Clients narrow this down with `ClientMessage`s:
+///   ```json
+///   { "type": "subscribe", "task_ids": ["…"] }
+///   { "type": "subscribe", "filter": { "scope": "assigned", "status": "review" } }
+///   { "type": "unsubscribe", "task_ids": ["…"] }
+///   { "type": "unsubscribe" }
+///   ```
+/// Once subscribed, only events on the listed tasks or matching any of the filters
+/// are sent. Each change is acknowledged with a `subscribed` message.
+///
+/// ## Query Parameters:
+/// - `token` (optional): The JWT, if no `Authorization` header is sent.
+///
+/// ## Responses:
+/// - `101 Switching Protocols`: The WebSocket is open.
+/// - `400 Bad Request`: If the request is not a valid WebSocket handshake.
+/// - `401 Unauthorized`: If the token is missing, invalid or revoked.
+#[get("/ws")]
+pub async fn task_updates(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<WsAuthQuery>,
+    pool: web::Data<PgPool>,
+    broadcaster: web::Data<TaskBroadcaster>,
+) -> Result<HttpResponse, AppError> {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(query.token.as_deref())
+        .ok_or_else(|| AppError::Unauthorized("Missing token".into()))?;
+    let claims = verify_token(token)?;
+    TOKEN_REVOCATIONS.check(&pool, &claims).await?;
+
+    let (response, session, messages) = actix_ws::handle(&req, body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid WebSocket handshake: {}", e)))?;
+    actix_web::rt::spawn(run_session(
+        pool.get_ref().clone(),
+        session,
+        messages,
+        broadcaster.subscribe(),
+        claims,
+    ));
+    Ok(response)
+}
+
+/// Serializes a message for the client.
+fn encode(message: &ServerMessage) -> String {
+    serde_json::to_string(message).expect("server messages serialize to JSON")
+}
+
+/// Applies a client message to the subscription and returns the reply.
+fn handle_client_message(subscription: &mut Subscription, text: &str) -> String {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return encode(&ServerMessage::Error {
+                message: format!("Invalid message: {}", e),
+            })
+        }
+    };
+
+    match message {
+        ClientMessage::Subscribe { task_ids, filter } => {
+            let filters = subscription.filters.len() + usize::from(filter.is_some());
+            let mut merged = subscription.task_ids.clone();
+            merged.extend(task_ids);
+            if merged.len() > MAX_SUBSCRIBED_TASKS || filters > MAX_SUBSCRIBED_FILTERS {
+                return encode(&ServerMessage::Error {
+                    message: format!(
+                        "Subscriptions are limited to {} tasks and {} filters",
+                        MAX_SUBSCRIBED_TASKS, MAX_SUBSCRIBED_FILTERS
+                    ),
+                });
+            }
+            subscription.task_ids = merged;
+            subscription.filters.extend(filter);
+        }
+        ClientMessage::Unsubscribe {
+            task_ids: Some(task_ids),
+        } => {
+            for task_id in task_ids {
+                subscription.task_ids.remove(&task_id);
+            }
+        }
+        ClientMessage::Unsubscribe { task_ids: None } => *subscription = Subscription::default(),
+    }
+    encode(&ServerMessage::Subscribed { subscription })
+}
+
+/// Returns the message to send for an update, if the user should receive it.
+fn update_message(
+    user_id: i32,
+    subscription: &Subscription,
+    update: &TaskUpdate,
+) -> Option<String> {
+    if !update.audience.contains(&user_id)
+        || !subscription.matches(user_id, update.event.task_id, &update.state)
+    {
+        return None;
+    }
+    Some(encode(&ServerMessage::TaskEvent {
+        event: &update.event,
+        task: update.task_for(user_id),
+    }))
+}
+
+/// Drives one WebSocket connection until either side closes it.
+async fn run_session(
+    pool: PgPool,
+    mut session: Session,
+    mut messages: MessageStream,
+    mut updates: broadcast::Receiver<Arc<TaskUpdate>>,
+    claims: Claims,
+) {
+    let user_id = claims.sub;
+    let mut subscription = Subscription::default();
+    let mut heartbeat = actix_web::rt::time::interval(HEARTBEAT_INTERVAL);
+    let mut last_seen = Instant::now();
+
+    let close_reason = loop {
+        let sent = tokio::select! {
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > CLIENT_TIMEOUT {
+                    break None;
+                }
+                if chrono::Utc::now().timestamp() >= claims.exp as i64 {
+                    break Some(CloseReason {
+                        code: CloseCode::Policy,
+                        description: Some("Token expired".into()),
+                    });
+                }
+                if TOKEN_REVOCATIONS.check(&pool, &claims).await.is_err() {
+                    break Some(CloseReason {
+                        code: CloseCode::Policy,
+                        description: Some("Session revoked".into()),
+                    });
+                }
+                session.ping(b"").await
+            }
+            message = messages.next() => {
+                last_seen = Instant::now();
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        session.text(handle_client_message(&mut subscription, &text)).await
+                    }
+                    Some(Ok(Message::Ping(bytes))) => session.pong(&bytes).await,
+                    Some(Ok(Message::Close(reason))) => break reason,
+                    Some(Ok(_)) => Ok(()),
+                    Some(Err(_)) | None => break None,
+                }
+            }
+            update = updates.recv() => match update {
+                Ok(update) => match update_message(user_id, &subscription, &update) {
+                    Some(text) => session.text(text).await,
+                    None => Ok(()),
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    session.text(encode(&ServerMessage::Lagged { skipped })).await
+                }
+                Err(RecvError::Closed) => break None,
+            },
+        };
+        if sent.is_err() {
+            // The client is gone; there is nobody left to send a close frame to.
+            return;
+        }
+    };
+
+    let _ = session.close(close_reason).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_logged_request_line_drops_ws_token() {
+        let req = actix_web::test::TestRequest::get()
+            .uri("/api/ws?token=secret.jwt.value")
+            .to_srv_request();
+        assert_eq!(logged_request_line(&req), "GET /api/ws HTTP/1.1");
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/api/tasks?status=todo")
+            .to_srv_request();
+        assert_eq!(
+            logged_request_line(&req),
+            "GET /api/tasks?status=todo HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn test_handle_client_message_updates_subscription() {
+        let mut subscription = Subscription::default();
+        let task_id = Uuid::new_v4();
+
+        let reply: Value = serde_json::from_str(&handle_client_message(
+            &mut subscription,
+            &format!(r#"{{"type":"subscribe","task_ids":["{}"]}}"#, task_id),
+        ))
+        .unwrap();
+        assert_eq!(reply["type"], "subscribed");
+        assert_eq!(reply["subscription"]["task_ids"][0], task_id.to_string());
+
+        handle_client_message(
+            &mut subscription,
+            r#"{"type":"subscribe","filter":{"status":"done"}}"#,
+        );
+        assert_eq!(subscription.filters.len(), 1);
+
+        handle_client_message(
+            &mut subscription,
+            &format!(r#"{{"type":"unsubscribe","task_ids":["{}"]}}"#, task_id),
+        );
+        assert!(subscription.task_ids.is_empty());
+        assert_eq!(subscription.filters.len(), 1);
+
+        handle_client_message(&mut subscription, r#"{"type":"unsubscribe"}"#);
+        assert_eq!(subscription, Subscription::default());
+    }
+
+    #[test]
+    fn test_handle_client_message_rejects_invalid_input() {
+        let mut subscription = Subscription::default();
+
+        let reply: Value =
+            serde_json::from_str(&handle_client_message(&mut subscription, "not json")).unwrap();
+        assert_eq!(reply["type"], "error");
+
+        let too_many: Vec<String> = (0..=MAX_SUBSCRIBED_TASKS)
+            .map(|_| format!("\"{}\"", Uuid::new_v4()))
+            .collect();
+        let reply: Value = serde_json::from_str(&handle_client_message(
+            &mut subscription,
+            &format!(
+                r#"{{"type":"subscribe","task_ids":[{}]}}"#,
+                too_many.join(",")
+            ),
+        ))
+        .unwrap();
+        assert_eq!(reply["type"], "error");
+        assert!(subscription.task_ids.is_empty());
+    }
+}
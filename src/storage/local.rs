@@ -0,0 +1,83 @@
+use super::{ByteStream, StorageBackend};
+use crate::error::AppError;
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::PathBuf,
+};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tokio_util::io::ReaderStream;
+
+/// Stores objects as files under a root directory, one file per key.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// Creates a backend rooted at `root`. The directory is created on first upload.
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::InternalServerError(format!("Storage error: {}", e))
+}
+
+#[async_trait(?Send)]
+impl StorageBackend for LocalStorage {
+    async fn put(&self, key: &str, mut body: ByteStream) -> Result<(), AppError> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+
+        // Write to a temporary file and rename it into place once complete, so
+        // readers never see a partial object.
+        let partial = path.with_extension("part");
+        let result = async {
+            let mut file = fs::File::create(&partial).await.map_err(io_error)?;
+            while let Some(chunk) = body.next().await {
+                file.write_all(&chunk?).await.map_err(io_error)?;
+            }
+            file.flush().await.map_err(io_error)?;
+            fs::rename(&partial, &path).await.map_err(io_error)
+        }
+        .await;
+
+        if result.is_err() {
+            let _ = fs::remove_file(&partial).await;
+        }
+        result
+    }
+
+    async fn get(&self, key: &str, offset: u64, len: u64) -> Result<ByteStream, AppError> {
+        let mut file = fs::File::open(self.path(key)).await.map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                AppError::NotFound("Attachment content not found".into())
+            } else {
+                io_error(e)
+            }
+        })?;
+        file.seek(SeekFrom::Start(offset)).await.map_err(io_error)?;
+
+        Ok(ReaderStream::new(file.take(len))
+            .map_err(io_error)
+            .boxed_local())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
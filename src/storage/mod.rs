@@ -0,0 +1,110 @@
+//! # Attachment Storage
+//!
+//! This module defines the `StorageBackend` trait that task attachment contents are
+//! streamed to and from, along with its implementations:
+//! - `local`: Files in a directory on the local filesystem.
+//! - `s3`: Objects in a bucket of an S3-compatible service, such as AWS S3 or MinIO.
+//!
+//! Attachment metadata lives in Postgres (`task_attachments`); backends only see
+//! opaque keys generated by the application. When an attachment row is deleted,
+//! including through a cascade from its task or uploader, a trigger queues its key
+//! in `storage_orphans`, and `run_orphan_sweeper` removes the object later.
+
+pub mod local;
+pub mod s3;
+
+use crate::{config::StorageConfig, error::AppError};
+use actix_web::web::Bytes;
+use async_trait::async_trait;
+use futures::stream::LocalBoxStream;
+use sqlx::PgPool;
+use std::{sync::Arc, time::Duration};
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// A stream of object contents.
+pub type ByteStream = LocalBoxStream<'static, Result<Bytes, AppError>>;
+
+/// How often `run_orphan_sweeper` looks for objects to remove.
+const ORPHAN_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Maximum number of orphaned objects removed per sweep.
+const ORPHAN_SWEEP_BATCH: i64 = 100;
+
+/// A place to keep attachment contents.
+///
+/// Keys are generated by the application and only contain ASCII letters, digits,
+/// `-` and `/`.
+#[async_trait(?Send)]
+pub trait StorageBackend: Send + Sync {
+    /// Stores `body` under `key`.
+    ///
+    /// An error from `body` (for example a size limit being exceeded) aborts the
+    /// upload and is returned unchanged; no partial object is left behind.
+    async fn put(&self, key: &str, body: ByteStream) -> Result<(), AppError>;
+
+    /// Streams `len` bytes of the object stored under `key`, starting at `offset`.
+    async fn get(&self, key: &str, offset: u64, len: u64) -> Result<ByteStream, AppError>;
+
+    /// Removes the object stored under `key`. Removing a missing object succeeds.
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Builds the backend selected in the configuration.
+pub fn from_config(config: &StorageConfig) -> Arc<dyn StorageBackend> {
+    match config {
+        StorageConfig::Local { root } => Arc::new(LocalStorage::new(root.clone())),
+        StorageConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+        } => Arc::new(S3Storage::new(
+            endpoint.clone(),
+            bucket.clone(),
+            region.clone(),
+            access_key.clone(),
+            secret_key.clone(),
+        )),
+    }
+}
+
+/// Removes up to `ORPHAN_SWEEP_BATCH` queued orphaned objects and returns how many
+/// were removed. Objects whose removal fails stay queued for the next sweep.
+pub async fn sweep_orphans(pool: &PgPool, storage: &dyn StorageBackend) -> Result<usize, AppError> {
+    let keys: Vec<(String,)> =
+        sqlx::query_as("SELECT storage_key FROM storage_orphans ORDER BY queued_at LIMIT $1")
+            .bind(ORPHAN_SWEEP_BATCH)
+            .fetch_all(pool)
+            .await?;
+
+    let mut removed = 0;
+    for (key,) in keys {
+        match storage.delete(&key).await {
+            Ok(()) => {
+                sqlx::query("DELETE FROM storage_orphans WHERE storage_key = $1")
+                    .bind(&key)
+                    .execute(pool)
+                    .await?;
+                removed += 1;
+            }
+            Err(e) => log::warn!("Failed to remove orphaned object {}: {}", key, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Runs `sweep_orphans` every `ORPHAN_SWEEP_INTERVAL` until the server stops.
+pub async fn run_orphan_sweeper(pool: PgPool, storage: Arc<dyn StorageBackend>) {
+    let mut interval = actix_web::rt::time::interval(ORPHAN_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match sweep_orphans(&pool, storage.as_ref()).await {
+            Ok(0) => {}
+            Ok(removed) => log::info!("Removed {} orphaned attachment objects", removed),
+            Err(e) => log::error!("Orphaned attachment sweep failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! # Task Trash
+//!
+//! Deleting a task only sets its `deleted_at`, moving it to the owner's trash
+//! (`routes::trash`), from which it can be restored. `run_trash_purger` deletes
+//! trashed tasks for good once they have been there for `Config::trash_retention_days`,
+//! along with their notes, comments, attachments and other dependent rows.
+
+use crate::error::AppError;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// How often `run_trash_purger` removes expired tasks.
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Permanently deletes tasks that have been in the trash for longer than
+/// `retention_days` and returns how many were deleted.
+///
+/// Subtasks of a purged task are kept and become top-level tasks.
+pub async fn purge_trash(pool: &PgPool, retention_days: u32) -> Result<u64, AppError> {
+    let result =
+        sqlx::query("DELETE FROM tasks WHERE deleted_at < now() - make_interval(days => $1)")
+            .bind(retention_days as i32)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+/// Runs `purge_trash` every `TRASH_PURGE_INTERVAL` until the server stops.
+pub async fn run_trash_purger(pool: PgPool, retention_days: u32) {
+    let mut interval = actix_web::rt::time::interval(TRASH_PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match purge_trash(&pool, retention_days).await {
+            Ok(0) => {}
+            Ok(purged) => log::info!("Purged {} tasks from the trash", purged),
+            Err(e) => log::error!("Trash purge failed: {}", e),
+        }
+    }
+}
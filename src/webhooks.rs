@@ -0,0 +1,523 @@
+//! # Outgoing Webhooks
+//!
+//! Users register webhooks (`routes::webhooks`) to have task events POSTed to a URL.
+//! Deliveries go through the `webhook_deliveries` table, which doubles as the
+//! delivery log:
+//! 1. `enqueue_deliveries` is called by `routes::activity::record_event` in the
+//!    transaction that changes a task. It queues a delivery for every active webhook
+//!    whose owner can see the task and which subscribes to the event type, so a
+//!    delivery exists exactly when the change commits.
+//! 2. Every server instance runs `run_delivery_worker`, which claims due deliveries
+//!    (`deliver_due`) and POSTs them, signed with the webhook's secret. Claims use
+//!    `FOR UPDATE SKIP LOCKED` and a lease, so instances never send the same attempt.
+//!
+//! Failed attempts are retried with exponential backoff up to `MAX_DELIVERY_ATTEMPTS`
+//! times. A webhook is disabled after `DISABLE_AFTER_FAILURES` consecutive failed
+//! attempts; its pending deliveries resume once it is re-enabled.
+//!
+//! Unless `WEBHOOK_ALLOW_PRIVATE_TARGETS` is set, webhooks may not target loopback,
+//! link-local, private or unspecified addresses, so that users cannot make the server
+//! send requests inside its own network. URLs are checked when a webhook is saved
+//! (`check_target`) and again on every attempt, when the host is resolved, so a name
+//! cannot be rebound to an internal address after it was checked.
+
+use crate::{
+    error::AppError,
+    models::{Task, TaskEvent, TaskEventType},
+};
+use hmac::{Hmac, Mac};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{header::CONTENT_TYPE, Url};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`, keyed with the webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-TaskForge-Signature";
+/// Header carrying the delivery's ID, the same on every attempt.
+pub const DELIVERY_HEADER: &str = "X-TaskForge-Delivery";
+/// Header carrying the event type, e.g. `updated`.
+pub const EVENT_HEADER: &str = "X-TaskForge-Event";
+
+/// Attempts made before a delivery is marked `failed`.
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+
+/// Consecutive failed attempts after which a webhook is disabled.
+pub const DISABLE_AFTER_FAILURES: i32 = 20;
+
+/// Delay before the first retry; each further retry waits twice as long.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest delay between two attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+/// How long a receiver has to respond.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a claimed delivery is reserved for the instance sending it. Must exceed
+/// `DELIVERY_TIMEOUT`, or a slow attempt could be repeated by another instance.
+const CLAIM_LEASE_SECS: f64 = 60.0;
+
+/// Most deliveries claimed and sent at once.
+const DELIVERY_BATCH_SIZE: i64 = 20;
+
+/// How often `run_delivery_worker` looks for due deliveries.
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Returns the `X-TaskForge-Signature` value for `body`: `sha256=` followed by the
+/// hex-encoded HMAC-SHA256 of the body, keyed with `secret`.
+///
+/// Receivers should compute the same value over the raw request body and compare
+/// the two in constant time.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Returns how long to wait after the `attempts`-th failed attempt.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.pow(exponent))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Generates a new webhook secret: 64 hex characters from two random UUIDs.
+pub fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Returns whether webhooks may not target `ip` unless `WEBHOOK_ALLOW_PRIVATE_TARGETS`
+/// is set: loopback, link-local, private, shared (RFC 6598) and unspecified addresses,
+/// including IPv4 addresses mapped into IPv6.
+pub fn is_private_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || first == 0
+                || (first == 100 && second & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private_address(IpAddr::V4(ip)),
+            None => {
+                let first_segment = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local (fc00::/7) and link-local (fe80::/10) addresses.
+                    || first_segment & 0xfe00 == 0xfc00
+                    || first_segment & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Returns the host of `url` if it is an IP address rather than a name.
+fn literal_address(url: &Url) -> Option<IpAddr> {
+    url.host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// Checks that `url` may be saved as a webhook's URL: unless `allow_private_targets`
+/// is set, its host must resolve, and only to public addresses.
+pub async fn check_target(url: &str, allow_private_targets: bool) -> Result<(), AppError> {
+    if allow_private_targets {
+        return Ok(());
+    }
+    let url =
+        Url::parse(url).map_err(|_| AppError::ValidationError("Invalid webhook URL".into()))?;
+    let addresses: Vec<IpAddr> = match (literal_address(&url), url.host_str()) {
+        (Some(ip), _) => vec![ip],
+        (None, Some(host)) => {
+            let port = url.port_or_known_default().unwrap_or(80);
+            match tokio::net::lookup_host((host, port)).await {
+                Ok(addrs) => addrs.map(|addr| addr.ip()).collect(),
+                Err(_) => Vec::new(),
+            }
+        }
+        (None, None) => Vec::new(),
+    };
+    if addresses.is_empty() {
+        return Err(AppError::ValidationError(
+            "Webhook URL host could not be resolved".into(),
+        ));
+    }
+    if addresses.into_iter().any(is_private_address) {
+        return Err(AppError::ValidationError(
+            "Webhook URL must not point to a loopback, link-local or private address".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Resolves the hosts deliveries are sent to, failing for names that resolve to any
+/// private address.
+struct PublicAddressResolver;
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addrs.iter().any(|addr| is_private_address(addr.ip())) {
+                return Err("host resolves to a private address".into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The HTTP client deliveries are sent with.
+#[derive(Clone)]
+pub struct DeliveryClient {
+    http: reqwest::Client,
+    allow_private_targets: bool,
+}
+
+/// Builds the HTTP client deliveries are sent with. Unless `allow_private_targets`
+/// is set, it refuses to connect to private addresses.
+///
+/// Redirects are not followed, so a receiver cannot bounce deliveries elsewhere.
+pub fn http_client(allow_private_targets: bool) -> DeliveryClient {
+    let mut builder = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("TaskForge-Webhooks/", env!("CARGO_PKG_VERSION")));
+    if !allow_private_targets {
+        builder = builder.dns_resolver(Arc::new(PublicAddressResolver));
+    }
+    DeliveryClient {
+        http: builder
+            .build()
+            .expect("webhook HTTP client configuration is valid"),
+        allow_private_targets,
+    }
+}
+
+/// Queues deliveries of `event` to the webhooks of `audience` that subscribe to its type.
+///
+/// `task` is the task after the change, or `None` for deletions. It is included in
+/// the payload only for webhooks whose owner can still see it, like on the event stream.
+pub(crate) async fn enqueue_deliveries(
+    conn: &mut PgConnection,
+    event: &TaskEvent,
+    task: Option<&Task>,
+    audience: &[i32],
+) -> Result<(), AppError> {
+    let with_task = json!({ "event": event, "task": task });
+    let without_task = json!({ "event": event, "task": Value::Null });
+    let can_see_task: Vec<i32> = task
+        .map(|t| std::iter::once(t.user_id).chain(t.assigned_to).collect())
+        .unwrap_or_default();
+
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, payload)
+         SELECT id, $1, $2, CASE WHEN user_id = ANY($3) THEN $4 ELSE $5 END
+         FROM webhooks
+         WHERE active AND user_id = ANY($6) AND $2 = ANY(event_types)",
+    )
+    .bind(event.id)
+    .bind(event.event_type)
+    .bind(&can_see_task)
+    .bind(with_task)
+    .bind(without_task)
+    .bind(audience)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// A delivery claimed for an attempt, with what is needed to send it.
+#[derive(Debug, FromRow)]
+struct ClaimedDelivery {
+    id: i64,
+    webhook_id: Uuid,
+    event_type: TaskEventType,
+    payload: Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Result of one delivery attempt.
+#[derive(Debug)]
+enum AttemptOutcome {
+    /// The receiver answered with this 2xx status.
+    Delivered(u16),
+    /// The receiver answered with a non-2xx status, or could not be reached.
+    Failed { status: Option<u16>, error: String },
+}
+
+/// Describes a request that got no response. The underlying error is only logged:
+/// its details would tell the webhook's owner how the server's network responds.
+fn transport_error(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "Request timed out"
+    } else if error.is_connect() {
+        "Could not connect to the receiver"
+    } else {
+        "Request failed"
+    }
+}
+
+/// Sends one attempt of a delivery.
+async fn attempt(client: &DeliveryClient, delivery: &ClaimedDelivery) -> AttemptOutcome {
+    // Addresses in the URL itself are not resolved, so the resolver cannot refuse them.
+    let literal_target = Url::parse(&delivery.url)
+        .ok()
+        .as_ref()
+        .and_then(literal_address);
+    if !client.allow_private_targets && literal_target.is_some_and(is_private_address) {
+        return AttemptOutcome::Failed {
+            status: None,
+            error: "Could not connect to the receiver".to_string(),
+        };
+    }
+
+    let body = delivery.payload.to_string();
+    let event_type = serde_json::to_value(delivery.event_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let response = client
+        .http
+        .post(&delivery.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event_type)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(SIGNATURE_HEADER, sign(&delivery.secret, body.as_bytes()))
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => {
+            AttemptOutcome::Delivered(response.status().as_u16())
+        }
+        Ok(response) => AttemptOutcome::Failed {
+            status: Some(response.status().as_u16()),
+            error: format!("Receiver responded with {}", response.status()),
+        },
+        Err(e) => {
+            log::debug!("Webhook delivery {} failed: {}", delivery.id, e);
+            AttemptOutcome::Failed {
+                status: None,
+                error: transport_error(&e).to_string(),
+            }
+        }
+    }
+}
+
+/// Records the outcome of an attempt on the delivery and its webhook.
+async fn record_attempt(
+    pool: &PgPool,
+    delivery: &ClaimedDelivery,
+    outcome: AttemptOutcome,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    match outcome {
+        AttemptOutcome::Delivered(status) => {
+            sqlx::query(
+                "UPDATE webhook_deliveries
+                 SET status = 'succeeded', attempts = attempts + 1, last_attempt_at = now(),
+                     delivered_at = now(), response_status = $2, last_error = NULL
+                 WHERE id = $1",
+            )
+            .bind(delivery.id)
+            .bind(status as i32)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE webhooks SET consecutive_failures = 0 WHERE id = $1")
+                .bind(delivery.webhook_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        AttemptOutcome::Failed { status, error } => {
+            let delay = retry_delay(delivery.attempts + 1);
+            sqlx::query(
+                "UPDATE webhook_deliveries
+                 SET attempts = attempts + 1, last_attempt_at = now(),
+                     response_status = $2, last_error = $3,
+                     status = CASE WHEN attempts + 1 >= $4
+                                   THEN 'failed'::webhook_delivery_status ELSE status END,
+                     next_attempt_at = now() + make_interval(secs => $5)
+                 WHERE id = $1",
+            )
+            .bind(delivery.id)
+            .bind(status.map(i32::from))
+            .bind(&error)
+            .bind(MAX_DELIVERY_ATTEMPTS)
+            .bind(delay.as_secs_f64())
+            .execute(&mut *tx)
+            .await?;
+            // Only the attempt that crosses the threshold disables the webhook.
+            let disabled_now: Option<bool> = sqlx::query_scalar(
+                "UPDATE webhooks
+                 SET consecutive_failures = consecutive_failures + 1,
+                     active = active AND consecutive_failures + 1 < $2,
+                     disabled_at = CASE WHEN active AND consecutive_failures + 1 >= $2
+                                        THEN now() ELSE disabled_at END
+                 WHERE id = $1
+                 RETURNING consecutive_failures = $2",
+            )
+            .bind(delivery.webhook_id)
+            .bind(DISABLE_AFTER_FAILURES)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if disabled_now == Some(true) {
+                log::warn!(
+                    "Disabled webhook {} after {} consecutive failed deliveries; last error: {}",
+                    delivery.webhook_id,
+                    DISABLE_AFTER_FAILURES,
+                    error
+                );
+            }
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Claims the deliveries that are due and sends them, returning how many were attempted.
+///
+/// The background worker calls this every few seconds; it is public so that a
+/// single pass can be driven directly.
+pub async fn deliver_due(pool: &PgPool, client: &DeliveryClient) -> Result<usize, AppError> {
+    let claimed = sqlx::query_as::<_, ClaimedDelivery>(
+        "UPDATE webhook_deliveries d
+         SET next_attempt_at = now() + make_interval(secs => $2)
+         FROM webhooks w
+         WHERE w.id = d.webhook_id
+           AND d.id IN (SELECT d2.id FROM webhook_deliveries d2
+                        JOIN webhooks w2 ON w2.id = d2.webhook_id
+                        WHERE d2.status = 'pending' AND d2.next_attempt_at <= now() AND w2.active
+                        ORDER BY d2.next_attempt_at, d2.id
+                        LIMIT $1
+                        FOR UPDATE OF d2 SKIP LOCKED)
+         RETURNING d.id, d.webhook_id, d.event_type, d.payload, d.attempts, w.url, w.secret",
+    )
+    .bind(DELIVERY_BATCH_SIZE)
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_all(pool)
+    .await?;
+
+    let outcomes =
+        futures::future::join_all(claimed.iter().map(|delivery| attempt(client, delivery))).await;
+    for (delivery, outcome) in claimed.iter().zip(outcomes) {
+        record_attempt(pool, delivery, outcome).await?;
+    }
+    Ok(claimed.len())
+}
+
+/// Runs `deliver_due` every `DELIVERY_POLL_INTERVAL` until the server stops.
+pub async fn run_delivery_worker(pool: PgPool, client: DeliveryClient) {
+    let mut interval = actix_web::rt::time::interval(DELIVERY_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        // Keep going while there is a backlog.
+        loop {
+            match deliver_due(&pool, &client).await {
+                Ok(sent) if sent as i64 == DELIVERY_BATCH_SIZE => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    log::error!("Webhook delivery failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+        assert_eq!(retry_delay(10), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(i32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_is_private_address() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(is_private_address(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_private_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_check_target() {
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "http://localhost/hook",
+        ] {
+            assert!(
+                matches!(
+                    check_target(url, false).await,
+                    Err(AppError::ValidationError(_))
+                ),
+                "{}",
+                url
+            );
+            assert!(check_target(url, true).await.is_ok());
+        }
+        assert!(check_target("https://93.184.216.34/hook", false)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 64);
+        assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(secret, generate_secret());
+    }
+}
@@ -4,6 +4,7 @@ use actix_web::{test, web, App};
 use dotenv::dotenv;
 use serde_json::json;
 use sqlx::PgPool;
+use taskforge::config::Config;
 use taskforge::models::{TaskPriority, TaskStatus};
 use taskforge::routes; // For routes::config
 use taskforge::routes::health; // For the health service // Added dotenv // Added imports for enums
@@ -40,6 +41,7 @@ async fn test_register_and_login_flow() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -160,7 +162,7 @@ async fn test_register_and_login_flow() {
     // 3. Use the token to access a protected route (e.g., create a task)
     let create_task_payload = json!({
         "title": "Task created by token test",
-        "status": TaskStatus::Todo, // Using the enum variant
+        "status": TaskStatus::new("todo"), // Using the enum variant
         "priority": TaskPriority::Medium // Adding optional priority for thoroughness
     });
 
@@ -191,7 +193,7 @@ async fn test_register_and_login_flow() {
     );
     assert_eq!(
         created_task_response.get("status").and_then(|s| s.as_str()),
-        Some("todo") // Assuming TaskStatus::Todo serializes to "todo"
+        Some("todo") // Assuming TaskStatus::new("todo") serializes to "todo"
     );
     assert_eq!(
         created_task_response
@@ -226,6 +228,7 @@ async fn test_invalid_registration_inputs() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -329,6 +332,7 @@ async fn test_invalid_login_inputs() {
         // Temporary app instance for setup
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default()) // Minimal middleware for setup
             .service(web::scope("/api").configure(routes::config)),
     )
@@ -354,6 +358,7 @@ async fn test_invalid_login_inputs() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -442,6 +447,7 @@ async fn test_protected_route_with_invalid_tokens() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -456,6 +462,9 @@ async fn test_protected_route_with_invalid_tokens() {
     let expired_claims = taskforge::auth::Claims {
         sub: 999, // Arbitrary user ID
         exp: (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp() as usize,
+        jti: uuid::Uuid::new_v4(),
+        sid: uuid::Uuid::new_v4(),
+        generation: 0,
     };
     let jwt_secret_for_test =
         std::env::var("JWT_SECRET").unwrap_or_else(|_| "test_secret".to_string());
@@ -513,6 +522,9 @@ async fn test_protected_route_with_invalid_tokens() {
     let claims_for_wrong_secret = taskforge::auth::Claims {
         sub: 998,
         exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        jti: uuid::Uuid::new_v4(),
+        sid: uuid::Uuid::new_v4(),
+        generation: 0,
     };
     let token_wrong_secret = jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
@@ -541,3 +553,720 @@ async fn test_protected_route_with_invalid_tokens() {
         panic!("Expected error for token with wrong secret, but got Ok");
     }
 }
+
+#[actix_rt::test]
+async fn test_refresh_token_rotation_and_reuse_detection() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let email = "refresh_user@example.com";
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    // 1. Registration returns a short-lived access token and a refresh token
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(json!({
+            "username": "refresh_user",
+            "email": email,
+            "password": "Password123!"
+        }))
+        .to_request();
+    let registered: taskforge::auth::AuthResponse = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(registered.expires_in, 15 * 60);
+    assert!(!registered.refresh_token.is_empty());
+
+    // Only the hash of the refresh token is stored
+    let (stored,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM refresh_tokens WHERE user_id = $1 AND token_hash = $2",
+    )
+    .bind(registered.user_id)
+    .bind(&registered.refresh_token)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(stored, 0);
+
+    let refresh = |refresh_token: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/refresh")
+            .set_json(json!({ "refresh_token": refresh_token }))
+            .to_request()
+    };
+
+    // 2. Refreshing rotates the refresh token and issues a working access token
+    let resp = test::call_service(&app, refresh(&registered.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let refreshed: taskforge::auth::AuthResponse = test::read_body_json(resp).await;
+    assert_eq!(refreshed.user_id, registered.user_id);
+    assert_ne!(refreshed.refresh_token, registered.refresh_token);
+
+    let req = test::TestRequest::get()
+        .uri("/api/tasks")
+        .append_header(("Authorization", format!("Bearer {}", refreshed.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 3. Reusing the old refresh token is rejected and revokes the whole family
+    let resp = test::call_service(&app, refresh(&registered.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, refresh(&refreshed.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    // 4. Logging in again starts a new family, unaffected by the revoked one
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "email": email, "password": "Password123!" }))
+        .to_request();
+    let logged_in: taskforge::auth::AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let resp = test::call_service(&app, refresh(&logged_in.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 5. Unknown refresh tokens are rejected
+    let resp = test::call_service(&app, refresh("not-a-refresh-token")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await;
+}
+
+#[actix_rt::test]
+async fn test_logout_and_token_revocation() {
+    use taskforge::auth::{verify_token, AuthResponse, TokenRevocations};
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let email = "logout_user@example.com";
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    // Builds a request to `uri` made with `token`
+    let authed = |method: &str, uri: &str, token: &str| {
+        let req = match method {
+            "POST" => test::TestRequest::post(),
+            _ => test::TestRequest::get(),
+        };
+        req.uri(uri)
+            .append_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+    // Returns the status of a response, including rejections by the middleware
+    fn status_of<B>(
+        result: Result<actix_web::dev::ServiceResponse<B>, actix_web::Error>,
+    ) -> actix_web::http::StatusCode {
+        match result {
+            Ok(resp) => resp.status(),
+            Err(e) => e.error_response().status(),
+        }
+    }
+    let refresh = |refresh_token: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/refresh")
+            .set_json(json!({ "refresh_token": refresh_token }))
+            .to_request()
+    };
+
+    // 1. Two sessions: one from registering, one from logging in
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(json!({
+            "username": "logout_user",
+            "email": email,
+            "password": "Password123!"
+        }))
+        .to_request();
+    let first: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let login = || {
+        test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(json!({ "email": email, "password": "Password123!" }))
+            .to_request()
+    };
+    let second: AuthResponse = test::call_and_read_body_json(&app, login()).await;
+
+    let resp = test::try_call_service(&app, authed("GET", "/api/tasks", &first.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::OK);
+
+    // 2. Logging out revokes the session's access and refresh tokens at once
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .append_header(("Authorization", format!("Bearer {}", first.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    let resp = test::try_call_service(&app, authed("GET", "/api/tasks", &first.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, refresh(&first.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    // The revocation is stored in Postgres, not only in this process's cache
+    let first_claims = verify_token(&first.token).unwrap();
+    let fresh_store = TokenRevocations::new(std::time::Duration::from_secs(30));
+    assert!(fresh_store.check(&pool, &first_claims).await.is_err());
+
+    // 3. The other session is unaffected
+    let resp = test::try_call_service(&app, authed("GET", "/api/tasks", &second.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::OK);
+
+    // 4. Logging out of all sessions revokes every token issued so far
+    let resp =
+        test::try_call_service(&app, authed("POST", "/api/auth/logout-all", &second.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::NO_CONTENT);
+
+    let resp = test::try_call_service(&app, authed("GET", "/api/tasks", &second.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, refresh(&second.refresh_token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let second_claims = verify_token(&second.token).unwrap();
+    assert!(fresh_store.check(&pool, &second_claims).await.is_err());
+
+    // 5. Tokens issued afterwards carry the new generation and work
+    let third: AuthResponse = test::call_and_read_body_json(&app, login()).await;
+    let resp = test::try_call_service(&app, authed("GET", "/api/tasks", &third.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::OK);
+
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await;
+}
+
+#[actix_rt::test]
+async fn test_session_management() {
+    use taskforge::auth::AuthResponse;
+    use taskforge::models::Session;
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let (email, other_email) = ("sessions_user@example.com", "sessions_other@example.com");
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1 OR email = $2")
+        .bind(email)
+        .bind(other_email)
+        .execute(&pool)
+        .await;
+
+    // Requests from 127.0.0.1 come through a trusted reverse proxy.
+    let mut config = Config::from_env();
+    config.trusted_proxies = vec!["127.0.0.1".parse().unwrap()];
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    fn status_of<B>(
+        result: Result<actix_web::dev::ServiceResponse<B>, actix_web::Error>,
+    ) -> actix_web::http::StatusCode {
+        match result {
+            Ok(resp) => resp.status(),
+            Err(e) => e.error_response().status(),
+        }
+    }
+    let list_sessions = |token: &str| {
+        test::TestRequest::get()
+            .uri("/api/auth/sessions")
+            .append_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+    let delete_session = |token: &str, id: uuid::Uuid| {
+        test::TestRequest::delete()
+            .uri(&format!("/api/auth/sessions/{}", id))
+            .append_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    // 1. Registering and logging in each record a session with the client's details
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .peer_addr("127.0.0.1:40000".parse().unwrap())
+        .insert_header(("User-Agent", "TaskForge-Test/1.0 (laptop)"))
+        .insert_header(("X-Forwarded-For", "203.0.113.7"))
+        .set_json(json!({
+            "username": "sessions_user",
+            "email": email,
+            "password": "Password123!"
+        }))
+        .to_request();
+    let laptop: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .peer_addr("198.51.100.9:40000".parse().unwrap())
+        .insert_header(("User-Agent", "TaskForge-Test/1.0 (phone)"))
+        // Not from the proxy, so the header is ignored.
+        .insert_header(("X-Forwarded-For", "192.0.2.1"))
+        .set_json(json!({ "email": email, "password": "Password123!" }))
+        .to_request();
+    let phone: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let sessions: Vec<Session> =
+        test::call_and_read_body_json(&app, list_sessions(&phone.token)).await;
+    assert_eq!(sessions.len(), 2);
+    let current = sessions
+        .iter()
+        .find(|s| s.current)
+        .expect("current session");
+    assert_eq!(
+        current.user_agent.as_deref(),
+        Some("TaskForge-Test/1.0 (phone)")
+    );
+    assert_eq!(current.ip_address.as_deref(), Some("198.51.100.9"));
+    let laptop_session = sessions.iter().find(|s| !s.current).expect("other session");
+    assert_eq!(
+        laptop_session.user_agent.as_deref(),
+        Some("TaskForge-Test/1.0 (laptop)")
+    );
+    assert_eq!(laptop_session.ip_address.as_deref(), Some("203.0.113.7"));
+    let (laptop_id, phone_id) = (laptop_session.id, current.id);
+
+    // 2. Refreshing keeps the session and updates when it was last seen
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(json!({ "refresh_token": laptop.refresh_token }))
+        .to_request();
+    let laptop: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let sessions: Vec<Session> =
+        test::call_and_read_body_json(&app, list_sessions(&laptop.token)).await;
+    assert_eq!(sessions.len(), 2);
+    let refreshed = sessions.iter().find(|s| s.current).unwrap();
+    assert_eq!(refreshed.id, laptop_id);
+    assert!(refreshed.last_seen_at > refreshed.created_at);
+
+    // 3. Other users cannot revoke the session
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(json!({
+            "username": "sessions_other",
+            "email": other_email,
+            "password": "Password123!"
+        }))
+        .to_request();
+    let other: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let resp = test::try_call_service(&app, delete_session(&other.token, laptop_id)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::NOT_FOUND);
+
+    // 4. Revoking the session invalidates its tokens immediately
+    let resp = test::try_call_service(&app, list_sessions(&laptop.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::OK);
+    let resp = test::try_call_service(&app, delete_session(&phone.token, laptop_id)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::NO_CONTENT);
+
+    let resp = test::try_call_service(&app, list_sessions(&laptop.token)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::UNAUTHORIZED);
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(json!({ "refresh_token": laptop.refresh_token }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    let sessions: Vec<Session> =
+        test::call_and_read_body_json(&app, list_sessions(&phone.token)).await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, phone_id);
+
+    // 5. Revoked and unknown sessions are not found
+    let resp = test::try_call_service(&app, delete_session(&phone.token, laptop_id)).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::NOT_FOUND);
+    let resp =
+        test::try_call_service(&app, delete_session(&phone.token, uuid::Uuid::new_v4())).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::NOT_FOUND);
+
+    let _ = sqlx::query("DELETE FROM users WHERE email = $1 OR email = $2")
+        .bind(email)
+        .bind(other_email)
+        .execute(&pool)
+        .await;
+}
+
+#[actix_rt::test]
+async fn test_password_reset() {
+    use taskforge::auth::AuthResponse;
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let email = "reset_user@example.com";
+    let unknown_email = "reset_nobody@example.com";
+    let cleanup = || async {
+        let _ = sqlx::query("DELETE FROM users WHERE email = $1")
+            .bind(email)
+            .execute(&pool)
+            .await;
+        let _ = sqlx::query("DELETE FROM email_outbox WHERE recipient = $1 OR recipient = $2")
+            .bind(email)
+            .bind(unknown_email)
+            .execute(&pool)
+            .await;
+    };
+    cleanup().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    // Returns the status of a response, including rejections by the middleware
+    fn status_of<B>(
+        result: Result<actix_web::dev::ServiceResponse<B>, actix_web::Error>,
+    ) -> actix_web::http::StatusCode {
+        match result {
+            Ok(resp) => resp.status(),
+            Err(e) => e.error_response().status(),
+        }
+    }
+    let forgot = |email: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/password/forgot")
+            .set_json(json!({ "email": email }))
+            .to_request()
+    };
+    let reset = |token: &str, password: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/password/reset")
+            .set_json(json!({ "token": token, "password": password }))
+            .to_request()
+    };
+    let login = |password: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(json!({ "email": email, "password": password }))
+            .to_request()
+    };
+    // Takes the reset tokens out of the reset emails queued for `email`, oldest first
+    let queued_tokens = || async {
+        let bodies: Vec<(String,)> = sqlx::query_as(
+            "SELECT body FROM email_outbox WHERE recipient = $1 AND subject = $2 ORDER BY id",
+        )
+        .bind(email)
+        .bind("Reset your TaskForge password")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        bodies
+            .into_iter()
+            .map(|(body,)| {
+                let start = body.find("?token=").expect("No reset link in the email") + 7;
+                body[start..start + 64].to_string()
+            })
+            .collect::<Vec<String>>()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(json!({
+            "username": "reset_user",
+            "email": email,
+            "password": "Password123!"
+        }))
+        .to_request();
+    let registered: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    // 1. Registered and unknown emails get the same response; only the former get an email
+    let resp = test::call_service(&app, forgot(email)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::ACCEPTED);
+    assert!(test::read_body(resp).await.is_empty());
+    let resp = test::call_service(&app, forgot(unknown_email)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::ACCEPTED);
+    assert!(test::read_body(resp).await.is_empty());
+    let resp = test::call_service(&app, forgot("not-an-email")).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let (unknown_emails,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM email_outbox WHERE recipient = $1")
+            .bind(unknown_email)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(unknown_emails, 0);
+    let tokens = queued_tokens().await;
+    assert_eq!(tokens.len(), 1);
+
+    // Only the hash of the reset token is stored
+    let (stored,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM password_reset_tokens WHERE token_hash = $1")
+            .bind(&tokens[0])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored, 0);
+
+    // 2. Invalid new passwords and unknown tokens are rejected
+    let resp = test::call_service(&app, reset(&tokens[0], "123")).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let resp = test::call_service(&app, reset("not-a-reset-token", "NewPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 3. Resetting changes the password and logs out every session
+    test::call_service(&app, forgot(email)).await;
+    let tokens = queued_tokens().await;
+    assert_eq!(tokens.len(), 2);
+
+    let resp = test::call_service(&app, reset(&tokens[0], "NewPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/tasks")
+        .append_header(("Authorization", format!("Bearer {}", registered.token)))
+        .to_request();
+    let resp = test::try_call_service(&app, req).await;
+    assert_eq!(status_of(resp), actix_web::http::StatusCode::UNAUTHORIZED);
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(json!({ "refresh_token": registered.refresh_token }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    let resp = test::call_service(&app, login("Password123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, login("NewPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 4. The used token, and the user's other outstanding tokens, no longer work
+    let resp = test::call_service(&app, reset(&tokens[0], "OtherPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, reset(&tokens[1], "OtherPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 5. Expired tokens are rejected
+    let expired = taskforge::auth::issue_password_reset_token(
+        &pool,
+        registered.user_id,
+        chrono::Utc::now() - chrono::Duration::minutes(1),
+    )
+    .await
+    .unwrap();
+    let resp = test::call_service(&app, reset(&expired, "OtherPassword123!")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    cleanup().await;
+}
+
+#[actix_rt::test]
+async fn test_email_verification() {
+    use taskforge::auth::AuthResponse;
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let email = "verify_user@example.com";
+    let owner_email = "verify_owner@example.com";
+    let cleanup = || async {
+        let _ = sqlx::query("DELETE FROM users WHERE email = $1 OR email = $2")
+            .bind(email)
+            .bind(owner_email)
+            .execute(&pool)
+            .await;
+        let _ = sqlx::query("DELETE FROM email_outbox WHERE recipient = $1 OR recipient = $2")
+            .bind(email)
+            .bind(owner_email)
+            .execute(&pool)
+            .await;
+    };
+    cleanup().await;
+
+    let mut restricted_config = Config::from_env();
+    restricted_config.restrict_unverified_accounts = true;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(restricted_config))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let register = |username: &str, email: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(json!({
+                "username": username,
+                "email": email,
+                "password": "Password123!"
+            }))
+            .to_request()
+    };
+    let verify = |token: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/email/verify")
+            .set_json(json!({ "token": token }))
+            .to_request()
+    };
+    let resend = |access_token: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/email/resend")
+            .append_header(("Authorization", format!("Bearer {}", access_token)))
+            .to_request()
+    };
+    // Takes the verification tokens out of the emails queued for `email`, oldest first
+    let queued_tokens = || async {
+        let bodies: Vec<(String,)> =
+            sqlx::query_as("SELECT body FROM email_outbox WHERE recipient = $1 ORDER BY id")
+                .bind(email)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        bodies
+            .into_iter()
+            .map(|(body,)| {
+                let start = body
+                    .find("?token=")
+                    .expect("No verification link in the email")
+                    + 7;
+                body[start..start + 64].to_string()
+            })
+            .collect::<Vec<String>>()
+    };
+
+    // 1. Registering sends a verification link, and the account starts out unverified
+    let registered: AuthResponse =
+        test::call_and_read_body_json(&app, register("verify_user", email)).await;
+    assert!(!registered.email_verified);
+    let tokens = queued_tokens().await;
+    assert_eq!(tokens.len(), 1);
+
+    let owner: AuthResponse =
+        test::call_and_read_body_json(&app, register("verify_owner", owner_email)).await;
+    sqlx::query("UPDATE users SET email_verified_at = now() WHERE id = $1")
+        .bind(owner.user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // 2. Unverified accounts cannot be assigned tasks while the restriction is on
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(("Authorization", format!("Bearer {}", owner.token)))
+        .set_json(json!({
+            "title": "Verified assignees only",
+            "status": TaskStatus::new("todo")
+        }))
+        .to_request();
+    let task: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let assign = || {
+        test::TestRequest::post()
+            .uri(&format!(
+                "/api/tasks/{}/assign",
+                task["id"].as_str().unwrap()
+            ))
+            .append_header(("Authorization", format!("Bearer {}", owner.token)))
+            .set_json(json!({ "assignee_id": registered.user_id }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, assign()).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    // 3. Resending queues another link
+    let resp = test::call_service(&app, resend(&registered.token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::ACCEPTED);
+    let tokens = queued_tokens().await;
+    assert_eq!(tokens.len(), 2);
+
+    // 4. Unknown tokens are rejected; a valid one verifies the address
+    let resp = test::call_service(&app, verify("not-a-verification-token")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, verify(&tokens[0])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    // The used token, and the user's other outstanding tokens, no longer work
+    let resp = test::call_service(&app, verify(&tokens[0])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, verify(&tokens[1])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 5. Verified accounts can be assigned tasks, and have nothing left to resend
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "email": email, "password": "Password123!" }))
+        .to_request();
+    let logged_in: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    assert!(logged_in.email_verified);
+
+    let resp = test::call_service(&app, assign()).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let resp = test::call_service(&app, resend(&logged_in.token)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    cleanup().await;
+}
@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use dotenv::dotenv;
+use sqlx::PgPool;
+use std::sync::Mutex;
+use taskforge::config::SmtpTls;
+use taskforge::error::AppError;
+use taskforge::mail::{self, Email, LogMailer, Mailer, SmtpMailer};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+fn email(to: &str) -> Email {
+    Email {
+        to: to.to_string(),
+        subject: "Reset your TaskForge password".to_string(),
+        body: "Hi,\n\nFollow this link:\n.\nhttp://localhost:3000/reset-password?token=abc\n"
+            .to_string(),
+    }
+}
+
+/// Accepts one SMTP connection on `listener`, like a local SMTP sink, and returns
+/// the commands it received and the message data.
+async fn smtp_sink(listener: TcpListener) -> (Vec<String>, String) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let (mut commands, mut data) = (Vec::new(), String::new());
+
+    writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        commands.push(line.clone());
+        let reply: &[u8] = match line.split_whitespace().next().unwrap_or("") {
+            "EHLO" => b"250-sink greets you\r\n250-8BITMIME\r\n250 AUTH PLAIN\r\n",
+            "AUTH" => b"235 2.7.0 Authentication successful\r\n",
+            "DATA" => {
+                writer.write_all(b"354 End data with .\r\n").await.unwrap();
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if line == "." {
+                        break;
+                    }
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                b"250 2.0.0 Queued\r\n"
+            }
+            "QUIT" => {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            }
+            _ => b"250 OK\r\n",
+        };
+        writer.write_all(reply).await.unwrap();
+    }
+    (commands, data)
+}
+
+#[actix_rt::test]
+async fn test_smtp_mailer_against_local_sink() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let sink = actix_web::rt::spawn(smtp_sink(listener));
+
+    let mailer = SmtpMailer::new(
+        "127.0.0.1".to_string(),
+        port,
+        SmtpTls::None,
+        Some(("taskforge".to_string(), "secret".to_string())),
+        "TaskForge <noreply@example.com>".to_string(),
+    );
+    mailer
+        .send(&email("user@example.com"))
+        .await
+        .expect("Sending to the sink failed");
+
+    let (commands, data) = sink.await.unwrap();
+    assert_eq!(
+        commands,
+        vec![
+            "EHLO localhost".to_string(),
+            "AUTH PLAIN AHRhc2tmb3JnZQBzZWNyZXQ=".to_string(),
+            "MAIL FROM:<noreply@example.com>".to_string(),
+            "RCPT TO:<user@example.com>".to_string(),
+            "DATA".to_string(),
+            "QUIT".to_string(),
+        ]
+    );
+    let (headers, body) = data.split_once("\n\n").unwrap();
+    assert!(headers.contains("From: TaskForge <noreply@example.com>\n"));
+    assert!(headers.contains("To: user@example.com\n"));
+    assert!(headers.contains("Subject: Reset your TaskForge password\n"));
+    // Lines starting with a dot are dot-stuffed, so the sink sees them doubled
+    assert_eq!(
+        body,
+        "Hi,\n\nFollow this link:\n..\nhttp://localhost:3000/reset-password?token=abc\n"
+    );
+}
+
+#[actix_rt::test]
+async fn test_smtp_mailer_reports_rejections() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    actix_web::rt::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream
+            .write_all(b"554 No SMTP service here\r\n")
+            .await
+            .unwrap();
+    });
+
+    let mailer = SmtpMailer::new(
+        "127.0.0.1".to_string(),
+        port,
+        SmtpTls::None,
+        None,
+        "noreply@example.com".to_string(),
+    );
+    match mailer.send(&email("user@example.com")).await {
+        Err(AppError::InternalServerError(msg)) => assert!(msg.contains("554")),
+        other => panic!("Expected an SMTP error, got {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn test_log_mailer_appends_to_file() {
+    let path = std::env::temp_dir().join(format!("taskforge-mail-{}.log", uuid::Uuid::new_v4()));
+    let mailer = LogMailer::new("noreply@example.com".to_string(), Some(path.clone()));
+
+    mailer.send(&email("first@example.com")).await.unwrap();
+    mailer.send(&email("second@example.com")).await.unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(contents.starts_with("From: noreply@example.com\nTo: first@example.com\n"));
+    assert!(contents.contains("To: second@example.com\nSubject: Reset your TaskForge password\n"));
+    assert_eq!(contents.matches("reset-password?token=abc").count(), 2);
+}
+
+/// Records the emails it is asked to send, or fails them all.
+struct RecordingMailer {
+    sent: Mutex<Vec<Email>>,
+    fail: bool,
+}
+
+#[async_trait(?Send)]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: &Email) -> Result<(), AppError> {
+        if self.fail {
+            return Err(AppError::InternalServerError("SMTP error: refused".into()));
+        }
+        self.sent.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+/// Returns the attempts made on the email queued for `to`, and whether its next
+/// attempt is in the future, or `None` if nothing is queued for `to`.
+async fn queued(pool: &PgPool, to: &str) -> Option<(i32, bool)> {
+    sqlx::query_as(
+        "SELECT attempts, next_attempt_at > now() FROM email_outbox WHERE recipient = $1",
+    )
+    .bind(to)
+    .fetch_optional(pool)
+    .await
+    .unwrap()
+}
+
+/// Postpones every queued email not addressed to `to`, so that `send_due` only
+/// claims this test's email whatever other tests left in the outbox.
+async fn postpone_others(pool: &PgPool, to: &str) {
+    sqlx::query(
+        "UPDATE email_outbox SET next_attempt_at = now() + interval '1 day' WHERE recipient <> $1",
+    )
+    .bind(to)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Makes the email queued for `to` due now.
+async fn make_due(pool: &PgPool, to: &str) {
+    sqlx::query("UPDATE email_outbox SET next_attempt_at = now() WHERE recipient = $1")
+        .bind(to)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn test_outbox_retries_and_deletes_emails() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let to = format!("outbox-{}@example.com", uuid::Uuid::new_v4());
+    let failing = RecordingMailer {
+        sent: Mutex::new(Vec::new()),
+        fail: true,
+    };
+    let working = RecordingMailer {
+        sent: Mutex::new(Vec::new()),
+        fail: false,
+    };
+
+    // 1. A failed attempt keeps the email queued and schedules a retry
+    mail::queue_email(&pool, &email(&to)).await.unwrap();
+    postpone_others(&pool, &to).await;
+    mail::send_due(&pool, &failing).await.unwrap();
+    assert_eq!(queued(&pool, &to).await, Some((1, true)));
+
+    // Emails that are not due yet are left alone
+    mail::send_due(&pool, &working).await.unwrap();
+    assert!(!working
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|sent| sent.to == to));
+
+    // 2. Once sent, the email is removed from the outbox
+    make_due(&pool, &to).await;
+    mail::send_due(&pool, &working).await.unwrap();
+    let sent: Vec<Email> = working
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|sent| sent.to == to)
+        .cloned()
+        .collect();
+    assert_eq!(sent, vec![email(&to)]);
+    assert_eq!(queued(&pool, &to).await, None);
+
+    // 3. Emails are given up on after the last attempt fails
+    mail::queue_email(&pool, &email(&to)).await.unwrap();
+    for attempt in 1..=mail::MAX_SEND_ATTEMPTS {
+        mail::send_due(&pool, &failing).await.unwrap();
+        if attempt < mail::MAX_SEND_ATTEMPTS {
+            assert_eq!(queued(&pool, &to).await, Some((attempt, true)));
+            make_due(&pool, &to).await;
+        }
+    }
+    assert_eq!(queued(&pool, &to).await, None);
+}
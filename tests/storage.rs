@@ -0,0 +1,115 @@
+use actix_web::web::Bytes;
+use futures::{stream, StreamExt, TryStreamExt};
+use taskforge::error::AppError;
+use taskforge::storage::{ByteStream, LocalStorage, S3Storage, StorageBackend};
+
+fn body(chunks: &[&'static [u8]]) -> ByteStream {
+    stream::iter(
+        chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from_static(chunk)))
+            .collect::<Vec<_>>(),
+    )
+    .boxed_local()
+}
+
+async fn read(storage: &dyn StorageBackend, key: &str, offset: u64, len: u64) -> Vec<u8> {
+    let chunks: Vec<Bytes> = storage
+        .get(key, offset, len)
+        .await
+        .expect("get failed")
+        .try_collect()
+        .await
+        .expect("reading the object failed");
+    chunks.concat()
+}
+
+/// Exercises the `StorageBackend` contract against one backend.
+async fn check_backend(storage: &dyn StorageBackend) {
+    let key = format!("attachments/{}", uuid::Uuid::new_v4());
+
+    storage
+        .put(&key, body(&[b"hello ", b"storage ", b"world"]))
+        .await
+        .expect("put failed");
+    assert_eq!(read(storage, &key, 0, 19).await, b"hello storage world");
+    assert_eq!(read(storage, &key, 6, 7).await, b"storage");
+
+    // Uploads larger than a part of an S3 multipart upload arrive whole.
+    let large_key = format!("attachments/{}", uuid::Uuid::new_v4());
+    let large: Vec<u8> = (0..9 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let chunks: ByteStream = stream::iter(
+        large
+            .chunks(64 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>(),
+    )
+    .boxed_local();
+    storage
+        .put(&large_key, chunks)
+        .await
+        .expect("large put failed");
+    let len = large.len() as u64;
+    assert!(read(storage, &large_key, 0, len).await == large);
+    assert_eq!(
+        read(storage, &large_key, len - 10, 10).await,
+        &large[large.len() - 10..]
+    );
+    storage
+        .delete(&large_key)
+        .await
+        .expect("large delete failed");
+
+    // A failing body aborts the upload without leaving an object behind.
+    let failed_key = format!("attachments/{}", uuid::Uuid::new_v4());
+    let failing: ByteStream = stream::iter(vec![
+        Ok(Bytes::from_static(b"partial")),
+        Err(AppError::PayloadTooLarge("too big".into())),
+    ])
+    .boxed_local();
+    assert!(matches!(
+        storage.put(&failed_key, failing).await,
+        Err(AppError::PayloadTooLarge(_))
+    ));
+    assert!(matches!(
+        storage.get(&failed_key, 0, 7).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    storage.delete(&key).await.expect("delete failed");
+    assert!(matches!(
+        storage.get(&key, 0, 19).await,
+        Err(AppError::NotFound(_))
+    ));
+    // Deleting a missing object is not an error.
+    storage.delete(&key).await.expect("second delete failed");
+}
+
+#[actix_rt::test]
+async fn test_local_storage_backend() {
+    let root = std::env::temp_dir().join(format!("taskforge-storage-{}", uuid::Uuid::new_v4()));
+    check_backend(&LocalStorage::new(root.clone())).await;
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Runs against an S3-compatible service when `S3_TEST_ENDPOINT` is set, e.g. a
+/// local MinIO started with
+/// `docker run -p 9000:9000 minio/minio server /data` and a bucket created in it.
+/// `S3_TEST_BUCKET`, `S3_TEST_ACCESS_KEY_ID` and `S3_TEST_SECRET_ACCESS_KEY`
+/// default to `taskforge` and MinIO's `minioadmin` credentials.
+#[actix_rt::test]
+async fn test_s3_storage_backend() {
+    let Ok(endpoint) = std::env::var("S3_TEST_ENDPOINT") else {
+        eprintln!("S3_TEST_ENDPOINT not set; skipping S3 storage test");
+        return;
+    };
+    let var = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.into());
+    let storage = S3Storage::new(
+        endpoint,
+        var("S3_TEST_BUCKET", "taskforge"),
+        var("S3_TEST_REGION", "us-east-1"),
+        var("S3_TEST_ACCESS_KEY_ID", "minioadmin"),
+        var("S3_TEST_SECRET_ACCESS_KEY", "minioadmin"),
+    );
+    check_backend(&storage).await;
+}
@@ -5,7 +5,8 @@ use dotenv::dotenv;
 use serde_json::json;
 use sqlx::PgPool;
 use std::net::TcpListener;
-use taskforge::models::{Task, TaskPriority, TaskStatus};
+use taskforge::config::Config;
+use taskforge::models::{Page, Task, TaskPriority, TaskStatus};
 use taskforge::routes;
 use taskforge::routes::health;
 // reqwest client will be used in the test_create_task_unauthorized
@@ -108,7 +109,7 @@ async fn test_create_task_unauthorized() {
     let client = reqwest::Client::new();
     let task_payload = json!({
         "title": "Unauthorized Task",
-        "status": TaskStatus::Todo
+        "status": TaskStatus::new("todo")
     });
 
     let request_url = format!("http://127.0.0.1:{}/api/tasks", port);
@@ -151,6 +152,7 @@ async fn test_task_crud_flow() {
     let app_for_crud = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -182,7 +184,7 @@ async fn test_task_crud_flow() {
     // 1. Create Task
     let task_payload_create = json!({
         "title": "CRUD Task 1 Original",
-        "status": TaskStatus::Todo,
+        "status": TaskStatus::new("todo"),
         "description": "Initial description",
         "priority": TaskPriority::Medium
     });
@@ -195,7 +197,7 @@ async fn test_task_crud_flow() {
     assert_eq!(resp_create.status(), actix_web::http::StatusCode::CREATED);
     let created_task: Task = test::read_body_json(resp_create).await;
     assert_eq!(created_task.title, "CRUD Task 1 Original");
-    assert_eq!(created_task.status, TaskStatus::Todo);
+    assert_eq!(created_task.status, TaskStatus::new("todo"));
     assert_eq!(
         created_task.description.as_deref(),
         Some("Initial description")
@@ -218,7 +220,7 @@ async fn test_task_crud_flow() {
     // 3. Update Task
     let task_payload_update = json!({
         "title": "CRUD Task 1 Updated",
-        "status": TaskStatus::InProgress,
+        "status": TaskStatus::new("in_progress"),
         "description": "Updated description",
         "priority": TaskPriority::High
     });
@@ -232,7 +234,7 @@ async fn test_task_crud_flow() {
     let updated_task: Task = test::read_body_json(resp_update).await;
     assert_eq!(updated_task.id, task_id_1);
     assert_eq!(updated_task.title, "CRUD Task 1 Updated");
-    assert_eq!(updated_task.status, TaskStatus::InProgress);
+    assert_eq!(updated_task.status, TaskStatus::new("in_progress"));
     assert_eq!(
         updated_task.description.as_deref(),
         Some("Updated description")
@@ -242,7 +244,7 @@ async fn test_task_crud_flow() {
     // 4. Create a second task for Get All check
     let task_payload_create2 = json!({
         "title": "CRUD Task 2",
-        "status": TaskStatus::Done,
+        "status": TaskStatus::new("done"),
         "priority": TaskPriority::Low
     });
     let req_create2 = test::TestRequest::post()
@@ -262,7 +264,9 @@ async fn test_task_crud_flow() {
         .to_request();
     let resp_get_all = test::call_service(&app_for_crud, req_get_all).await;
     assert_eq!(resp_get_all.status(), actix_web::http::StatusCode::OK);
-    let tasks: Vec<Task> = test::read_body_json(resp_get_all).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_get_all)
+        .await
+        .items;
     assert!(
         tasks.len() >= 2,
         "Expected at least 2 tasks for the user, found {}",
@@ -322,6 +326,7 @@ async fn test_task_ownership_and_authorization() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -364,7 +369,7 @@ async fn test_task_ownership_and_authorization() {
     // User A creates a task
     let task_payload_user_a = json!({
         "title": "User A\'s Task",
-        "status": TaskStatus::Todo,
+        "status": TaskStatus::new("todo"),
         "priority": TaskPriority::High
     });
     let req_create_task_a = test::TestRequest::post()
@@ -388,7 +393,9 @@ async fn test_task_ownership_and_authorization() {
         .to_request();
     let resp_list_tasks_b = test::call_service(&app, req_list_tasks_b).await;
     assert_eq!(resp_list_tasks_b.status(), actix_web::http::StatusCode::OK);
-    let tasks_for_b: Vec<Task> = test::read_body_json(resp_list_tasks_b).await;
+    let tasks_for_b: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_list_tasks_b)
+        .await
+        .items;
     assert!(
         !tasks_for_b.iter().any(|t| t.id == task_a_id),
         "User B should not see User A\'s task in their list"
@@ -409,7 +416,7 @@ async fn test_task_ownership_and_authorization() {
     // 3. User B tries to update User A's task: should get 404
     let update_payload_by_b = json!({
         "title": "Attempted Update by B",
-        "status": TaskStatus::InProgress
+        "status": TaskStatus::new("in_progress")
     });
     let req_update_task_a_by_b = test::TestRequest::put()
         .uri(&format!("/api/tasks/{}", task_a_id))
@@ -463,6 +470,7 @@ async fn test_get_tasks_with_filtering() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -484,11 +492,11 @@ async fn test_get_tasks_with_filtering() {
 
     // --- Create a diverse set of tasks ---
     let tasks_to_create = vec![
-        json!({ "title": "Alpha Todo Low", "status": TaskStatus::Todo, "priority": TaskPriority::Low, "description": "Searchable one" }),
-        json!({ "title": "Bravo InProgress Medium", "status": TaskStatus::InProgress, "priority": TaskPriority::Medium, "description": "Another task" }),
-        json!({ "title": "Charlie Done High", "status": TaskStatus::Done, "priority": TaskPriority::High, "description": "High importance" }),
-        json!({ "title": "Delta Todo Medium", "status": TaskStatus::Todo, "priority": TaskPriority::Medium, "description": "Searchable two" }),
-        json!({ "title": "Echo Review Urgent", "status": TaskStatus::Review, "priority": TaskPriority::Urgent, "description": "Urgent review needed" }),
+        json!({ "title": "Alpha Todo Low", "status": TaskStatus::new("todo"), "priority": TaskPriority::Low, "description": "Searchable one" }),
+        json!({ "title": "Bravo InProgress Medium", "status": TaskStatus::new("in_progress"), "priority": TaskPriority::Medium, "description": "Another task" }),
+        json!({ "title": "Charlie Done High", "status": TaskStatus::new("done"), "priority": TaskPriority::High, "description": "High importance" }),
+        json!({ "title": "Delta Todo Medium", "status": TaskStatus::new("todo"), "priority": TaskPriority::Medium, "description": "Searchable two" }),
+        json!({ "title": "Echo Review Urgent", "status": TaskStatus::new("review"), "priority": TaskPriority::Urgent, "description": "Urgent review needed" }),
     ];
 
     let mut created_task_ids: Vec<uuid::Uuid> = Vec::new();
@@ -522,11 +530,13 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_status_todo = test::call_service(&app, req_status_todo).await;
     assert_eq!(resp_status_todo.status(), actix_web::http::StatusCode::OK);
-    let tasks_status_todo: Vec<Task> = test::read_body_json(resp_status_todo).await;
+    let tasks_status_todo: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_status_todo)
+        .await
+        .items;
     assert_eq!(tasks_status_todo.len(), 2);
     assert!(tasks_status_todo
         .iter()
-        .all(|t| t.status == TaskStatus::Todo));
+        .all(|t| t.status == TaskStatus::new("todo")));
 
     // Filter by priority: Medium (should be 2 tasks: Bravo, Delta)
     let req_prio_medium = test::TestRequest::get()
@@ -535,7 +545,9 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_prio_medium = test::call_service(&app, req_prio_medium).await;
     assert_eq!(resp_prio_medium.status(), actix_web::http::StatusCode::OK);
-    let tasks_prio_medium: Vec<Task> = test::read_body_json(resp_prio_medium).await;
+    let tasks_prio_medium: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_prio_medium)
+        .await
+        .items;
     assert_eq!(tasks_prio_medium.len(), 2);
     assert!(tasks_prio_medium
         .iter()
@@ -548,7 +560,9 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_search = test::call_service(&app, req_search).await;
     assert_eq!(resp_search.status(), actix_web::http::StatusCode::OK);
-    let tasks_search: Vec<Task> = test::read_body_json(resp_search).await;
+    let tasks_search: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_search)
+        .await
+        .items;
     assert_eq!(tasks_search.len(), 2);
     assert!(tasks_search.iter().any(|t| t.title.contains("Alpha")));
     assert!(tasks_search.iter().any(|t| t.title.contains("Delta")));
@@ -560,7 +574,9 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_search_title = test::call_service(&app, req_search_title).await;
     assert_eq!(resp_search_title.status(), actix_web::http::StatusCode::OK);
-    let tasks_search_title: Vec<Task> = test::read_body_json(resp_search_title).await;
+    let tasks_search_title: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_search_title)
+        .await
+        .items;
     assert_eq!(tasks_search_title.len(), 1);
     assert_eq!(tasks_search_title[0].title, "Alpha Todo Low");
 
@@ -571,7 +587,9 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_status_prio = test::call_service(&app, req_status_prio).await;
     assert_eq!(resp_status_prio.status(), actix_web::http::StatusCode::OK);
-    let tasks_status_prio: Vec<Task> = test::read_body_json(resp_status_prio).await;
+    let tasks_status_prio: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_status_prio)
+        .await
+        .items;
     assert_eq!(tasks_status_prio.len(), 1);
     assert_eq!(tasks_status_prio[0].title, "Delta Todo Medium");
 
@@ -582,7 +600,9 @@ async fn test_get_tasks_with_filtering() {
         .to_request();
     let resp_no_results = test::call_service(&app, req_no_results).await;
     assert_eq!(resp_no_results.status(), actix_web::http::StatusCode::OK);
-    let tasks_no_results: Vec<Task> = test::read_body_json(resp_no_results).await;
+    let tasks_no_results: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp_no_results)
+        .await
+        .items;
     assert!(tasks_no_results.is_empty());
 
     // --- Cleanup ---
@@ -607,6 +627,7 @@ async fn test_create_task_minimal_fields() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -625,7 +646,7 @@ async fn test_create_task_minimal_fields() {
 
     let minimal_payload = json!({
         "title": "Minimal Task Title",
-        "status": TaskStatus::Todo // Status is mandatory in TaskInput
+        "status": TaskStatus::new("todo") // Status is mandatory in TaskInput
         // description, priority, due_date are omitted
     });
 
@@ -643,7 +664,7 @@ async fn test_create_task_minimal_fields() {
 
     let created_task: Task = test::read_body_json(resp).await;
     assert_eq!(created_task.title, "Minimal Task Title");
-    assert_eq!(created_task.status, TaskStatus::Todo);
+    assert_eq!(created_task.status, TaskStatus::new("todo"));
     assert!(created_task.description.is_none());
     assert!(created_task.priority.is_none()); // Default priority is None if not provided
     assert!(created_task.due_date.is_none());
@@ -663,6 +684,7 @@ async fn test_update_non_existent_task() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -687,7 +709,7 @@ async fn test_update_non_existent_task() {
     let non_existent_task_id = uuid::Uuid::new_v4(); // Random, non-existent UUID
     let update_payload = json!({
         "title": "Update for Non-Existent Task",
-        "status": TaskStatus::Done
+        "status": TaskStatus::new("done")
     });
 
     let req = test::TestRequest::put()
@@ -716,6 +738,7 @@ async fn test_delete_non_existent_task() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -764,6 +787,7 @@ async fn test_task_invalid_uuid_format() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(Logger::default())
             .service(health::health)
             .service(
@@ -799,7 +823,7 @@ async fn test_task_invalid_uuid_format() {
     // Test PUT with invalid UUID
     let update_payload = json!({
         "title": "Attempted Update",
-        "status": TaskStatus::Todo
+        "status": TaskStatus::new("todo")
     });
     let req_put = test::TestRequest::put()
         .uri(&format!("/api/tasks/{}", invalid_uuid))
@@ -839,6 +863,7 @@ async fn test_assign_task_flow() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -882,7 +907,7 @@ async fn test_assign_task_flow() {
     // 1. Task Owner creates a task
     let task_payload_create = json!({
         "title": "Task for Assignment",
-        "status": TaskStatus::Todo,
+        "status": TaskStatus::new("todo"),
         "priority": TaskPriority::Medium
     });
     let req_create_task = test::TestRequest::post()
@@ -950,7 +975,7 @@ async fn test_assign_task_flow() {
     let resp_assign_not_owner = test::call_service(&app, req_assign_not_owner).await;
     assert_eq!(
         resp_assign_not_owner.status(),
-        actix_web::http::StatusCode::NOT_FOUND, // Or FORBIDDEN, but NOT_FOUND is current pattern for ownership issues
+        actix_web::http::StatusCode::FORBIDDEN, // The assignee can see the task, but only the owner may reassign it
         "Assigning non-owned task did not fail as expected."
     );
 
@@ -1027,3 +1052,3782 @@ async fn test_assign_task_flow() {
     cleanup_user(&pool, owner_email).await;
     cleanup_user(&pool, assignee_email).await;
 }
+
+async fn fetch_all_task_pages(
+    app: &impl actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+        Error = actix_web::Error,
+    >,
+    token: &str,
+    query: &str,
+) -> Vec<Page<Task>> {
+    let mut pages = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let uri = match &cursor {
+            Some(c) => format!("/api/tasks?{}&cursor={}", query, c),
+            None => format!("/api/tasks?{}", query),
+        };
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let page: Page<Task> = test::read_body_json(resp).await;
+        cursor = page.next_cursor.clone();
+        let has_more = page.has_more;
+        pages.push(page);
+        if !has_more {
+            break;
+        }
+        assert!(pages.len() < 20, "Pagination did not terminate");
+    }
+    pages
+}
+
+#[actix_rt::test]
+async fn test_get_tasks_pagination_with_tied_sort_keys() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let user_email = "pagination_user@example.com";
+    cleanup_user(&pool, user_email).await;
+    let test_user = register_and_login_user(&app, user_email, "pagination_user", "PassPaging123!")
+        .await
+        .expect("Failed to register/login user for pagination test");
+
+    // Seven tasks sharing one created_at timestamp; priorities tie in groups and two are NULL.
+    let priorities = [
+        Some("high"),
+        Some("high"),
+        Some("high"),
+        Some("low"),
+        Some("low"),
+        None,
+        None,
+    ];
+    let mut created_ids: Vec<uuid::Uuid> = Vec::new();
+    for (i, priority) in priorities.iter().enumerate() {
+        let (id,): (uuid::Uuid,) = sqlx::query_as(
+            "INSERT INTO tasks (title, priority, status, user_id, created_at)
+             VALUES ($1, CAST($2 AS task_priority), 'todo', $3, '2025-01-01T00:00:00Z')
+             RETURNING id",
+        )
+        .bind(format!("Paged Task {}", i))
+        .bind(*priority)
+        .bind(test_user.id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to insert task for pagination test");
+        created_ids.push(id);
+    }
+    let mut expected_ids = created_ids.clone();
+    expected_ids.sort();
+
+    // 1. Default sort (created_at desc): every created_at ties, so the id tie-breaker drives paging.
+    let pages = fetch_all_task_pages(&app, &test_user.token, "limit=2").await;
+    assert_eq!(pages.len(), 4);
+    assert!(pages[..3].iter().all(|p| p.items.len() == 2 && p.has_more));
+    assert_eq!(pages[3].items.len(), 1);
+    assert!(pages[3].next_cursor.is_none());
+    let mut seen: Vec<uuid::Uuid> = pages
+        .iter()
+        .flat_map(|p| p.items.iter().map(|t| t.id))
+        .collect();
+    let in_page_order = seen.clone();
+    seen.sort();
+    assert_eq!(
+        seen, expected_ids,
+        "Pages must cover every task exactly once"
+    );
+    let mut expected_desc = expected_ids.clone();
+    expected_desc.reverse();
+    assert_eq!(in_page_order, expected_desc);
+
+    // 2. Sort by priority ascending with ties and NULLs spanning page boundaries.
+    let pages =
+        fetch_all_task_pages(&app, &test_user.token, "limit=2&sort=priority&order=asc").await;
+    let items: Vec<&Task> = pages.iter().flat_map(|p| p.items.iter()).collect();
+    let mut seen: Vec<uuid::Uuid> = items.iter().map(|t| t.id).collect();
+    seen.sort();
+    assert_eq!(
+        seen, expected_ids,
+        "Pages must cover every task exactly once"
+    );
+    let priority_order: Vec<Option<TaskPriority>> =
+        items.iter().map(|t| t.priority.clone()).collect();
+    assert_eq!(
+        priority_order,
+        vec![
+            Some(TaskPriority::Low),
+            Some(TaskPriority::Low),
+            Some(TaskPriority::High),
+            Some(TaskPriority::High),
+            Some(TaskPriority::High),
+            None,
+            None,
+        ]
+    );
+
+    // 3. Sort by priority descending still places NULL priorities last.
+    let pages =
+        fetch_all_task_pages(&app, &test_user.token, "limit=3&sort=priority&order=desc").await;
+    let items: Vec<&Task> = pages.iter().flat_map(|p| p.items.iter()).collect();
+    assert_eq!(items.len(), 7);
+    assert_eq!(items[0].priority, Some(TaskPriority::High));
+    assert!(items[5].priority.is_none() && items[6].priority.is_none());
+
+    // 4. A cursor cannot be reused with a different sort.
+    let first_page = &fetch_all_task_pages(&app, &test_user.token, "limit=6").await[0];
+    let cursor = first_page
+        .next_cursor
+        .clone()
+        .expect("Expected a next cursor");
+    let req_mismatch = test::TestRequest::get()
+        .uri(&format!(
+            "/api/tasks?limit=2&sort=priority&cursor={}",
+            cursor
+        ))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", test_user.token)))
+        .to_request();
+    let resp_mismatch = test::call_service(&app, req_mismatch).await;
+    assert_eq!(
+        resp_mismatch.status(),
+        actix_web::http::StatusCode::BAD_REQUEST
+    );
+
+    // 5. A malformed cursor is rejected.
+    let req_bad_cursor = test::TestRequest::get()
+        .uri("/api/tasks?cursor=not-a-cursor")
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", test_user.token)))
+        .to_request();
+    let resp_bad_cursor = test::call_service(&app, req_bad_cursor).await;
+    assert_eq!(
+        resp_bad_cursor.status(),
+        actix_web::http::StatusCode::BAD_REQUEST
+    );
+
+    cleanup_user(&pool, user_email).await;
+}
+
+#[actix_rt::test]
+async fn test_get_tasks_fulltext_search() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let user_email = "fts_user@example.com";
+    cleanup_user(&pool, user_email).await;
+    let test_user = register_and_login_user(&app, user_email, "fts_user", "PassFts123!")
+        .await
+        .expect("Failed to register/login user for full-text search test");
+
+    let tasks_to_create = vec![
+        json!({ "title": "Database backup", "status": TaskStatus::new("todo"), "description": "Nightly database dumps for the database cluster" }),
+        json!({ "title": "Write release notes", "status": TaskStatus::new("todo"), "description": "Mention the database upgrade" }),
+        json!({ "title": "Deploying the API", "status": TaskStatus::new("todo"), "description": "Roll out to staging" }),
+        json!({ "title": "Database migration", "status": TaskStatus::new("todo"), "description": "Schema changes" }),
+    ];
+    for task_payload in tasks_to_create {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", test_user.token)))
+            .set_json(&task_payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    }
+
+    let search = |query: &'static str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/tasks?{}", query))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", test_user.token)))
+            .to_request()
+    };
+
+    // 1. Full-text search is ranked: the task mentioning "database" most often comes first,
+    //    and every hit carries a rank and a highlighted headline.
+    let resp = test::call_service(&app, search("search=database")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let page: serde_json::Value = test::read_body_json(resp).await;
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0]["title"], "Database backup");
+    let ranks: Vec<f64> = items.iter().map(|t| t["rank"].as_f64().unwrap()).collect();
+    assert!(
+        ranks.windows(2).all(|w| w[0] >= w[1]),
+        "Results must be ordered by rank"
+    );
+    assert!(items
+        .iter()
+        .all(|t| t["headline"].as_str().unwrap().contains("<mark>")));
+
+    // 2. Stemming: "deploy" matches "Deploying".
+    let resp = test::call_service(&app, search("search=deploy")).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].title, "Deploying the API");
+
+    // 3. Web search syntax: exclusion with "-".
+    let resp = test::call_service(&app, search("search=database%20-migration%20-upgrade")).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].title, "Database backup");
+
+    // 4. Partial words only match in substring mode, which has no rank or headline.
+    let resp = test::call_service(&app, search("search=atab")).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert!(tasks.is_empty());
+    let resp = test::call_service(&app, search("search=atab&match=substring")).await;
+    let page: serde_json::Value = test::read_body_json(resp).await;
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert!(items
+        .iter()
+        .all(|t| t.get("rank").is_none() && t.get("headline").is_none()));
+
+    // 5. Relevance-ordered results paginate without gaps or duplicates.
+    let pages = fetch_all_task_pages(&app, &test_user.token, "search=database&limit=1").await;
+    let titles: Vec<String> = pages
+        .iter()
+        .flat_map(|p| p.items.iter().map(|t| t.title.clone()))
+        .collect();
+    assert_eq!(titles.len(), 3);
+    assert_eq!(titles[0], "Database backup");
+
+    // 6. Sorting by relevance without a full-text search is rejected.
+    let resp = test::call_service(&app, search("sort=relevance")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(
+        &app,
+        search("search=database&match=substring&sort=relevance"),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    cleanup_user(&pool, user_email).await;
+}
+
+#[actix_rt::test]
+async fn test_assignee_visibility_and_permissions() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "visibility_owner@example.com";
+    let assignee_email = "visibility_assignee@example.com";
+    let outsider_email = "visibility_outsider@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+    cleanup_user(&pool, outsider_email).await;
+
+    let owner = register_and_login_user(&app, owner_email, "visibility_owner", "PassOwner123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee = register_and_login_user(
+        &app,
+        assignee_email,
+        "visibility_assignee",
+        "PassAssignee1!",
+    )
+    .await
+    .expect("Failed to register assignee");
+    let outsider = register_and_login_user(
+        &app,
+        outsider_email,
+        "visibility_outsider",
+        "PassOutsider1!",
+    )
+    .await
+    .expect("Failed to register outsider");
+
+    let auth = |user: &TestUser| (header::AUTHORIZATION, format!("Bearer {}", user.token));
+
+    // Owner creates a task and assigns it; the assignee creates a task of their own.
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth(&owner))
+        .set_json(json!({ "title": "Shared Task", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let shared_task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/assign", shared_task.id))
+        .append_header(auth(&owner))
+        .set_json(json!({ "assignee_id": assignee.id }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth(&assignee))
+        .set_json(json!({ "title": "Assignee Own Task", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let own_task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+
+    let list = |user: &TestUser, query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/tasks?{}", query))
+            .append_header(auth(user))
+            .to_request()
+    };
+
+    // 1. Scopes: all (default) includes both, owned and assigned split them.
+    let resp = test::call_service(&app, list(&assignee, "")).await;
+    let ids: Vec<uuid::Uuid> = test::read_body_json::<Page<Task>, _>(resp)
+        .await
+        .items
+        .iter()
+        .map(|t| t.id)
+        .collect();
+    assert!(ids.contains(&shared_task.id) && ids.contains(&own_task.id));
+
+    let resp = test::call_service(&app, list(&assignee, "scope=assigned")).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, shared_task.id);
+
+    let resp = test::call_service(&app, list(&assignee, "scope=owned")).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, own_task.id);
+
+    // The owner filter narrows the assignee's view to one owner's tasks.
+    let resp = test::call_service(&app, list(&assignee, &format!("user_id={}", owner.id))).await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, shared_task.id);
+
+    // The owner can filter their own tasks by assignee.
+    let resp = test::call_service(
+        &app,
+        list(&owner, &format!("scope=owned&assigned_to={}", assignee.id)),
+    )
+    .await;
+    let tasks: Vec<Task> = test::read_body_json::<Page<Task>, _>(resp).await.items;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, shared_task.id);
+
+    // 2. The assignee can read the task; an outsider cannot.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", shared_task.id))
+        .append_header(auth(&assignee))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", shared_task.id))
+        .append_header(auth(&outsider))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    // 3. The assignee can change the status, but not edit or delete the task.
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}/status", shared_task.id))
+        .append_header(auth(&assignee))
+        .set_json(json!({ "status": TaskStatus::new("in_progress") }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let updated: Task = test::read_body_json(resp).await;
+    assert_eq!(updated.status, TaskStatus::new("in_progress"));
+    assert_eq!(updated.title, "Shared Task");
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}", shared_task.id))
+        .append_header(auth(&assignee))
+        .set_json(json!({ "title": "Hijacked", "status": TaskStatus::new("done") }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::FORBIDDEN
+    );
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/tasks/{}", shared_task.id))
+        .append_header(auth(&assignee))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::FORBIDDEN
+    );
+
+    // An outsider can't change the status either.
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}/status", shared_task.id))
+        .append_header(auth(&outsider))
+        .set_json(json!({ "status": TaskStatus::new("done") }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    // 4. Notes: both the assignee and the owner can add and read notes; outsiders can't.
+    for (user, body) in [(&assignee, "Started on this"), (&owner, "Thanks!")] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/tasks/{}/notes", shared_task.id))
+            .append_header(auth(user))
+            .set_json(json!({ "body": body }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::CREATED
+        );
+    }
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/notes", shared_task.id))
+        .append_header(auth(&outsider))
+        .set_json(json!({ "body": "Sneaky" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/notes", shared_task.id))
+        .append_header(auth(&owner))
+        .to_request();
+    let notes: Vec<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0]["body"], "Started on this");
+    assert_eq!(notes[0]["user_id"], assignee.id);
+    assert_eq!(notes[1]["body"], "Thanks!");
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+    cleanup_user(&pool, outsider_email).await;
+}
+
+#[actix_rt::test]
+async fn test_patch_task_merge_patch() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "patch_owner@example.com";
+    let assignee_email = "patch_assignee@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+    let owner = register_and_login_user(&app, owner_email, "patch_owner", "PassPatch123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee = register_and_login_user(&app, assignee_email, "patch_assignee", "PassPatch456!")
+        .await
+        .expect("Failed to register assignee");
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .set_json(json!({
+            "title": "Patch Me",
+            "description": "Original description",
+            "priority": TaskPriority::High,
+            "status": TaskStatus::new("todo"),
+            "due_date": "2030-01-01T00:00:00Z"
+        }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+
+    let patch = |user: &TestUser, body: serde_json::Value| {
+        test::TestRequest::patch()
+            .uri(&format!("/api/tasks/{}", task.id))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", user.token)))
+            .insert_header((header::CONTENT_TYPE, "application/merge-patch+json"))
+            .set_payload(body.to_string())
+            .to_request()
+    };
+
+    // 1. Changing only the status leaves every other column untouched.
+    let resp = test::call_service(&app, patch(&owner, json!({ "status": "in_progress" }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let patched: Task = test::read_body_json(resp).await;
+    assert_eq!(patched.status, TaskStatus::new("in_progress"));
+    assert_eq!(patched.title, "Patch Me");
+    assert_eq!(patched.description.as_deref(), Some("Original description"));
+    assert_eq!(patched.priority, Some(TaskPriority::High));
+    assert!(patched.due_date.is_some());
+
+    // 2. null clears nullable columns; absent fields are kept.
+    let resp = test::call_service(
+        &app,
+        patch(
+            &owner,
+            json!({ "description": null, "due_date": null, "title": "Patched" }),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let patched: Task = test::read_body_json(resp).await;
+    assert_eq!(patched.title, "Patched");
+    assert!(patched.description.is_none());
+    assert!(patched.due_date.is_none());
+    assert_eq!(patched.priority, Some(TaskPriority::High));
+    assert_eq!(patched.status, TaskStatus::new("in_progress"));
+
+    // 3. Assigning and unassigning through the patch.
+    let resp = test::call_service(&app, patch(&owner, json!({ "assigned_to": assignee.id }))).await;
+    let patched: Task = test::read_body_json(resp).await;
+    assert_eq!(patched.assigned_to, Some(assignee.id));
+
+    // 4. The assignee may patch the status, but nothing else.
+    let resp = test::call_service(&app, patch(&assignee, json!({ "status": "review" }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let resp = test::call_service(&app, patch(&assignee, json!({ "priority": null }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(&app, patch(&owner, json!({ "assigned_to": null }))).await;
+    let patched: Task = test::read_body_json(resp).await;
+    assert!(patched.assigned_to.is_none());
+    assert_eq!(patched.status, TaskStatus::new("review"));
+
+    // 5. Invalid patches.
+    let resp = test::call_service(&app, patch(&owner, json!({ "title": null }))).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let resp = test::call_service(&app, patch(&owner, json!({ "title": "" }))).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let resp = test::call_service(&app, patch(&owner, json!({ "user_id": assignee.id }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, patch(&owner, json!({ "assigned_to": 999999 }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 6. An empty patch is a no-op.
+    let resp = test::call_service(&app, patch(&owner, json!({}))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let unchanged: Task = test::read_body_json(resp).await;
+    assert_eq!(unchanged.title, "Patched");
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+}
+
+/// Revalidates a task with a stale `ETag`, asserts the server sends the task again
+/// instead of `304 Not Modified`, and returns the fresh `ETag`.
+async fn assert_task_modified(
+    app: &impl actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+        Error = actix_web::Error,
+    >,
+    token: &str,
+    task_id: uuid::Uuid,
+    etag: &str,
+    change: &str,
+) -> String {
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", task_id))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+        .insert_header((header::IF_NONE_MATCH, etag))
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::OK,
+        "ETag unchanged after {}",
+        change
+    );
+    resp.headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[actix_rt::test]
+async fn test_task_etag_conditional_requests() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "etag_owner@example.com";
+    let assignee_email = "etag_assignee@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+    let owner = register_and_login_user(&app, owner_email, "etag_owner", "PassETag123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee = register_and_login_user(&app, assignee_email, "etag_assignee", "PassETag456!")
+        .await
+        .expect("Failed to register assignee");
+    let auth = (header::AUTHORIZATION, format!("Bearer {}", owner.token));
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth.clone())
+        .set_json(json!({ "title": "Versioned", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let task_uri = format!("/api/tasks/{}", task.id);
+
+    // 1. GET returns a strong ETag and honours If-None-Match.
+    let req = test::TestRequest::get()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let etag = resp
+        .headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(
+        etag.starts_with('"'),
+        "expected a strong ETag, got {}",
+        etag
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .insert_header((header::IF_NONE_MATCH, etag.clone()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers().get(header::ETAG).unwrap(), etag.as_str());
+
+    // 2. A write with the current ETag succeeds and returns the new one.
+    let req = test::TestRequest::put()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .insert_header((header::IF_MATCH, etag.clone()))
+        .set_json(json!({ "title": "Versioned v2", "status": TaskStatus::new("in_progress") }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let new_etag = resp
+        .headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(new_etag, etag);
+
+    let req = test::TestRequest::get()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .insert_header((header::IF_NONE_MATCH, etag.clone()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    assert_eq!(resp.headers().get(header::ETAG).unwrap(), new_etag.as_str());
+
+    // 3. The stale ETag is rejected by every write endpoint.
+    let stale_requests = vec![
+        test::TestRequest::put()
+            .uri(&task_uri)
+            .set_json(json!({ "title": "Lost update", "status": TaskStatus::new("done") })),
+        test::TestRequest::patch()
+            .uri(&task_uri)
+            .set_json(json!({ "title": "Lost update" })),
+        test::TestRequest::put()
+            .uri(&format!("{}/status", task_uri))
+            .set_json(json!({ "status": "done" })),
+        test::TestRequest::post()
+            .uri(&format!("{}/assign", task_uri))
+            .set_json(json!({ "assignee_id": assignee.id })),
+        test::TestRequest::delete().uri(&task_uri),
+    ];
+    for req in stale_requests {
+        let req = req
+            .append_header(auth.clone())
+            .insert_header((header::IF_MATCH, etag.clone()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.status(),
+            actix_web::http::StatusCode::PRECONDITION_FAILED
+        );
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .to_request();
+    let unchanged: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(unchanged.title, "Versioned v2");
+    assert!(unchanged.assigned_to.is_none());
+
+    // 4. Changes to data the task embeds from other rows change its ETag as well.
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/comments", task_uri))
+        .append_header(auth.clone())
+        .set_json(json!({ "body": "First" }))
+        .to_request();
+    let comment: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    let etag = assert_task_modified(&app, &owner.token, task.id, &new_etag, "a comment").await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!(
+            "{}/comments/{}",
+            task_uri,
+            comment["id"].as_str().unwrap()
+        ))
+        .append_header(auth.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NO_CONTENT
+    );
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a comment deletion").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth.clone())
+        .set_json(
+            json!({ "title": "Child", "status": TaskStatus::new("todo"), "parent_id": task.id }),
+        )
+        .to_request();
+    let child: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a new subtask").await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}/status", child.id))
+        .append_header(auth.clone())
+        .set_json(json!({ "status": "done" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let etag = assert_task_modified(
+        &app,
+        &owner.token,
+        task.id,
+        &etag,
+        "a subtask status change",
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth.clone())
+        .set_json(json!({ "title": "Blocker", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let blocker: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/dependencies", task_uri))
+        .append_header(auth.clone())
+        .set_json(json!({ "blocker_id": blocker.id }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::CREATED
+    );
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a new dependency").await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", blocker.id))
+        .append_header(auth.clone())
+        .set_json(json!({ "title": "Renamed blocker" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a blocker rename").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/labels")
+        .append_header(auth.clone())
+        .set_json(json!({ "name": "etag", "color": "#112233" }))
+        .to_request();
+    let label: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let label_uri = format!("/api/labels/{}", label["id"].as_str().unwrap());
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "{}/labels/{}",
+            task_uri,
+            label["id"].as_str().unwrap()
+        ))
+        .append_header(auth.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a label").await;
+
+    let req = test::TestRequest::put()
+        .uri(&label_uri)
+        .append_header(auth.clone())
+        .set_json(json!({ "name": "etag-renamed", "color": "#445566" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let etag = assert_task_modified(&app, &owner.token, task.id, &etag, "a label rename").await;
+
+    let req = test::TestRequest::delete()
+        .uri(&label_uri)
+        .append_header(auth.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NO_CONTENT
+    );
+    assert_task_modified(&app, &owner.token, task.id, &etag, "a label deletion").await;
+
+    // 5. If-Match: * only requires the task to exist.
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/assign", task_uri))
+        .append_header(auth.clone())
+        .insert_header((header::IF_MATCH, "*"))
+        .set_json(json!({ "assignee_id": assignee.id }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let latest_etag = resp
+        .headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let req = test::TestRequest::delete()
+        .uri(&task_uri)
+        .append_header(auth.clone())
+        .insert_header((header::IF_MATCH, latest_etag))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+}
+
+#[actix_rt::test]
+async fn test_unassign_and_assignment_history() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "history_owner@example.com";
+    let first_email = "history_first@example.com";
+    let second_email = "history_second@example.com";
+    for email in [owner_email, first_email, second_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "history_owner", "PassHist123!")
+        .await
+        .expect("Failed to register owner");
+    let first = register_and_login_user(&app, first_email, "history_first", "PassHist456!")
+        .await
+        .expect("Failed to register first assignee");
+    let second = register_and_login_user(&app, second_email, "history_second", "PassHist789!")
+        .await
+        .expect("Failed to register second assignee");
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .set_json(json!({ "title": "Handoff", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let assign_uri = format!("/api/tasks/{}/assign", task.id);
+    let history_uri = format!("/api/tasks/{}/assignments", task.id);
+
+    // 1. Assign, reassign through PATCH, and assign the same user again (a no-op).
+    let assign = |assignee_id: i32| {
+        test::TestRequest::post()
+            .uri(&assign_uri)
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+            .set_json(json!({ "assignee_id": assignee_id }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, assign(first.id)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .set_json(json!({ "assigned_to": second.id }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    let resp = test::call_service(&app, assign(second.id)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 2. Only the owner may unassign.
+    let req = test::TestRequest::delete()
+        .uri(&assign_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", second.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::delete()
+        .uri(&assign_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let unassigned: Task = test::read_body_json(resp).await;
+    assert!(unassigned.assigned_to.is_none());
+
+    // Unassigning again succeeds without a new history entry.
+    let req = test::TestRequest::delete()
+        .uri(&assign_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 3. The history records each handoff, oldest first.
+    let req = test::TestRequest::get()
+        .uri(&history_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let history: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    let handoffs: Vec<(serde_json::Value, serde_json::Value)> = history
+        .iter()
+        .map(|entry| {
+            assert_eq!(entry["assigned_by"], json!(owner.id));
+            (
+                entry["previous_assignee_id"].clone(),
+                entry["assignee_id"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        handoffs,
+        vec![
+            (json!(null), json!(first.id)),
+            (json!(first.id), json!(second.id)),
+            (json!(second.id), json!(null)),
+        ]
+    );
+
+    // 4. Once unassigned, the former assignee can no longer see the history.
+    let req = test::TestRequest::get()
+        .uri(&history_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", second.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    for email in [owner_email, first_email, second_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+#[actix_rt::test]
+async fn test_bulk_task_operations() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "bulk_owner@example.com";
+    let other_email = "bulk_other@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+    let owner = register_and_login_user(&app, owner_email, "bulk_owner", "PassBulk123!")
+        .await
+        .expect("Failed to register owner");
+    let other = register_and_login_user(&app, other_email, "bulk_other", "PassBulk456!")
+        .await
+        .expect("Failed to register other user");
+
+    let bulk = |user: &TestUser, body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/tasks/bulk")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", user.token)))
+            .set_json(body)
+            .to_request()
+    };
+
+    let mut task_ids = Vec::new();
+    for title in ["Bulk A", "Bulk B", "Bulk C"] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+            .set_json(json!({ "title": title, "status": TaskStatus::new("todo") }))
+            .to_request();
+        let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+        task_ids.push(task.id);
+    }
+    let foreign_req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", other.token)))
+        .set_json(json!({ "title": "Not yours", "status": TaskStatus::new("todo") }))
+        .to_request();
+    let foreign: Task = test::read_body_json(test::call_service(&app, foreign_req).await).await;
+
+    let count_owned = || async {
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM tasks WHERE user_id = $1 AND deleted_at IS NULL")
+                .bind(owner.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        count
+    };
+
+    // 1. Atomic mode rolls everything back if one operation fails.
+    let resp = test::call_service(
+        &app,
+        bulk(
+            &owner,
+            json!({
+                "operations": [
+                    { "op": "create", "title": "Rolled back", "status": "todo" },
+                    { "op": "delete", "id": task_ids[0] },
+                    { "op": "delete", "id": foreign.id }
+                ]
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["committed"], json!(false));
+    assert_eq!(body["results"][0]["status"], json!(201));
+    assert_eq!(body["results"][1]["status"], json!(204));
+    assert_eq!(body["results"][2]["status"], json!(404));
+    assert_eq!(count_owned().await, 3);
+
+    // 2. Partial mode keeps successful operations and reports per-item failures.
+    let resp = test::call_service(
+        &app,
+        bulk(
+            &owner,
+            json!({
+                "mode": "partial",
+                "operations": [
+                    { "op": "create", "title": "Bulk D", "status": "todo", "priority": "high" },
+                    { "op": "create", "title": "", "status": "todo" },
+                    { "op": "update", "id": task_ids[0], "status": "done", "priority": "low" },
+                    { "op": "assign", "id": task_ids[1], "assignee_id": other.id },
+                    { "op": "assign", "id": task_ids[2], "assignee_id": 999999 },
+                    { "op": "delete", "id": task_ids[2] },
+                    { "op": "update", "id": foreign.id, "status": "done" }
+                ]
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["committed"], json!(true));
+    let statuses: Vec<u64> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["status"].as_u64().unwrap())
+        .collect();
+    assert_eq!(statuses, vec![201, 422, 200, 200, 400, 204, 404]);
+    assert_eq!(body["results"][2]["task"]["status"], json!("done"));
+    assert_eq!(body["results"][2]["task"]["priority"], json!("low"));
+    assert_eq!(body["results"][3]["task"]["assigned_to"], json!(other.id));
+    assert!(body["results"][1]["error"].is_string());
+    assert_eq!(count_owned().await, 3);
+
+    // 3. The assignee may bulk-update the status of an assigned task, but not its priority.
+    let resp = test::call_service(
+        &app,
+        bulk(
+            &other,
+            json!({
+                "mode": "partial",
+                "operations": [
+                    { "op": "update", "id": task_ids[1], "status": "in_progress" },
+                    { "op": "update", "id": task_ids[1], "priority": "high" },
+                    { "op": "delete", "id": task_ids[1] }
+                ]
+            }),
+        ),
+    )
+    .await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let statuses: Vec<u64> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["status"].as_u64().unwrap())
+        .collect();
+    assert_eq!(statuses, vec![200, 403, 403]);
+
+    // 4. Empty batches are rejected.
+    let resp = test::call_service(&app, bulk(&owner, json!({ "operations": [] }))).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+}
+
+#[actix_rt::test]
+async fn test_subtasks_hierarchy_and_progress() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut config = Config::from_env();
+    config.max_task_depth = 3;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "subtask_owner@example.com";
+    let other_email = "subtask_other@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+    let owner = register_and_login_user(&app, owner_email, "subtask_owner", "PassSub123!")
+        .await
+        .expect("Failed to register owner");
+    let other = register_and_login_user(&app, other_email, "subtask_other", "PassSub456!")
+        .await
+        .expect("Failed to register other user");
+
+    let create = |user: &TestUser, body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", user.token)))
+            .set_json(body)
+            .to_request()
+    };
+    let patch = |task_id: uuid::Uuid, body: serde_json::Value| {
+        test::TestRequest::patch()
+            .uri(&format!("/api/tasks/{}", task_id))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+            .set_json(body)
+            .to_request()
+    };
+
+    // Epic -> Story -> Step is three levels, the configured maximum.
+    let epic: Task = test::read_body_json(
+        test::call_service(
+            &app,
+            create(&owner, json!({ "title": "Epic", "status": "todo" })),
+        )
+        .await,
+    )
+    .await;
+    let story: Task = test::read_body_json(
+        test::call_service(
+            &app,
+            create(
+                &owner,
+                json!({ "title": "Story", "status": "todo", "parent_id": epic.id }),
+            ),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(story.parent_id, Some(epic.id));
+    let resp = test::call_service(
+        &app,
+        create(
+            &owner,
+            json!({ "title": "Step", "status": "done", "parent_id": story.id }),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let step: Task = test::read_body_json(resp).await;
+    let sibling: Task = test::read_body_json(
+        test::call_service(
+            &app,
+            create(
+                &owner,
+                json!({ "title": "Sibling", "status": "todo", "parent_id": epic.id }),
+            ),
+        )
+        .await,
+    )
+    .await;
+
+    // 1. A fourth level exceeds the maximum depth.
+    let resp = test::call_service(
+        &app,
+        create(
+            &owner,
+            json!({ "title": "Too deep", "status": "todo", "parent_id": step.id }),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    // Moving Story (a two-level subtree) under Sibling would also make it four levels deep.
+    let resp = test::call_service(&app, patch(story.id, json!({ "parent_id": sibling.id }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 2. Cycles are rejected.
+    let resp = test::call_service(&app, patch(epic.id, json!({ "parent_id": step.id }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, patch(epic.id, json!({ "parent_id": epic.id }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 3. Parents must belong to the same user.
+    let resp = test::call_service(
+        &app,
+        create(
+            &other,
+            json!({ "title": "Intruder", "status": "todo", "parent_id": epic.id }),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 4. Progress counts cover direct children.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", story.id))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let fetched: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!((fetched.subtasks_done, fetched.subtasks_total), (1, 1));
+
+    let resp = test::call_service(&app, patch(story.id, json!({ "status": "done" }))).await;
+    let patched: Task = test::read_body_json(resp).await;
+    assert_eq!((patched.subtasks_done, patched.subtasks_total), (1, 1));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/subtasks", epic.id))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let subtasks: Vec<Task> = test::read_body_json(resp).await;
+    let titles: Vec<&str> = subtasks.iter().map(|task| task.title.as_str()).collect();
+    assert_eq!(titles, vec!["Story", "Sibling"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks?parent_id={}", epic.id))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+        .to_request();
+    let page: Page<Task> = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(page.items.len(), 2);
+    let epic_item = {
+        let req = test::TestRequest::get()
+            .uri("/api/tasks?search=Epic")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", owner.token)))
+            .to_request();
+        let page: Page<Task> = test::read_body_json(test::call_service(&app, req).await).await;
+        page.items
+            .into_iter()
+            .find(|task| task.id == epic.id)
+            .unwrap()
+    };
+    assert_eq!((epic_item.subtasks_done, epic_item.subtasks_total), (1, 2));
+
+    // 5. Moving a subtask back to the top level is allowed, and so is the move
+    //    that was too deep before.
+    let resp = test::call_service(&app, patch(sibling.id, json!({ "parent_id": null }))).await;
+    let detached: Task = test::read_body_json(resp).await;
+    assert!(detached.parent_id.is_none());
+    let resp = test::call_service(&app, patch(step.id, json!({ "parent_id": sibling.id }))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 6. A move is validated against moves committing while it waits: with X being
+    //    moved under Y, moving Y under X would close a cycle.
+    let mut tops = Vec::new();
+    for title in ["X", "Y"] {
+        let task: Task = test::read_body_json(
+            test::call_service(
+                &app,
+                create(&owner, json!({ "title": title, "status": "todo" })),
+            )
+            .await,
+        )
+        .await;
+        tops.push(task);
+    }
+    let (x, y) = (&tops[0], &tops[1]);
+    let mut other_move = pool.begin().await.unwrap();
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('task_hierarchy'), $1)")
+        .bind(owner.id)
+        .execute(&mut *other_move)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tasks SET parent_id = $1 WHERE id = $2")
+        .bind(y.id)
+        .bind(x.id)
+        .execute(&mut *other_move)
+        .await
+        .unwrap();
+    let (resp, _) = futures::join!(
+        test::call_service(&app, patch(y.id, json!({ "parent_id": x.id }))),
+        async {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            other_move.commit().await.unwrap();
+        }
+    );
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let (y_parent,): (Option<uuid::Uuid>,) =
+        sqlx::query_as("SELECT parent_id FROM tasks WHERE id = $1")
+            .bind(y.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(y_parent.is_none());
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+}
+
+#[actix_rt::test]
+async fn test_task_dependencies_blocking_and_cycles() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "deps_owner@example.com";
+    cleanup_user(&pool, owner_email).await;
+    let owner = register_and_login_user(&app, owner_email, "deps_owner", "PassDeps123!")
+        .await
+        .expect("Failed to register owner");
+    let auth = (header::AUTHORIZATION, format!("Bearer {}", owner.token));
+
+    let mut ids = Vec::new();
+    for title in ["Design", "Build", "Ship"] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(auth.clone())
+            .set_json(json!({ "title": title, "status": "todo" }))
+            .to_request();
+        let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+        ids.push(task.id);
+    }
+    let (design, build, ship) = (ids[0], ids[1], ids[2]);
+
+    let add = |blocked: uuid::Uuid, blocker: uuid::Uuid| {
+        test::TestRequest::post()
+            .uri(&format!("/api/tasks/{}/dependencies", blocked))
+            .append_header(auth.clone())
+            .set_json(json!({ "blocker_id": blocker }))
+            .to_request()
+    };
+    let set_status = |task_id: uuid::Uuid, status: &str| {
+        test::TestRequest::put()
+            .uri(&format!("/api/tasks/{}/status", task_id))
+            .append_header(auth.clone())
+            .set_json(json!({ "status": status }))
+            .to_request()
+    };
+
+    // Design blocks Build, Build blocks Ship.
+    let resp = test::call_service(&app, add(build, design)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let resp = test::call_service(&app, add(ship, build)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+
+    // 1. Duplicates, self-edges and cycles are rejected.
+    let resp = test::call_service(&app, add(build, design)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let resp = test::call_service(&app, add(build, build)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, add(design, ship)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 2. get_task shows both directions.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", build))
+        .append_header(auth.clone())
+        .to_request();
+    let detail: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(detail["title"], json!("Build"));
+    assert_eq!(detail["blocked_by"][0]["id"], json!(design));
+    assert_eq!(detail["blocked_by"][0]["status"], json!("todo"));
+    assert_eq!(detail["blocks"][0]["id"], json!(ship));
+
+    // 3. A blocked task cannot start or finish, through any endpoint.
+    let resp = test::call_service(&app, set_status(build, "in_progress")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", build))
+        .append_header(auth.clone())
+        .set_json(json!({ "status": "done" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}", build))
+        .append_header(auth.clone())
+        .set_json(json!({ "title": "Build", "status": "in_progress" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    // `review` is an active status too, so it also waits for the blocker.
+    let resp = test::call_service(&app, set_status(build, "review")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    // 4. Once the blocker is done, the task can move on.
+    let resp = test::call_service(&app, set_status(design, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let resp = test::call_service(&app, set_status(build, "in_progress")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 5. Removing an edge unblocks the downstream task.
+    let resp = test::call_service(&app, set_status(ship, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let remove = || {
+        test::TestRequest::delete()
+            .uri(&format!("/api/tasks/{}/dependencies/{}", ship, build))
+            .append_header(auth.clone())
+            .to_request()
+    };
+    let resp = test::call_service(&app, remove()).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+    let resp = test::call_service(&app, remove()).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+    let resp = test::call_service(&app, set_status(ship, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    cleanup_user(&pool, owner_email).await;
+}
+
+#[actix_rt::test]
+async fn test_labels_crud_and_filtering() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "labels_owner@example.com";
+    let other_email = "labels_other@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+    let owner = register_and_login_user(&app, owner_email, "labels_owner", "PassLabel123!")
+        .await
+        .expect("Failed to register owner");
+    let other = register_and_login_user(&app, other_email, "labels_other", "PassLabel456!")
+        .await
+        .expect("Failed to register other user");
+    let auth = (header::AUTHORIZATION, format!("Bearer {}", owner.token));
+
+    // 1. Label CRUD.
+    let mut label_ids = Vec::new();
+    for (name, color) in [
+        ("bug", "#d73a4a"),
+        ("frontend", "#1f6feb"),
+        ("urgent", "#ff0000"),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/labels")
+            .append_header(auth.clone())
+            .set_json(json!({ "name": name, "color": color }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+        let label: serde_json::Value = test::read_body_json(resp).await;
+        label_ids.push(label["id"].as_str().unwrap().to_string());
+    }
+    let req = test::TestRequest::post()
+        .uri("/api/labels")
+        .append_header(auth.clone())
+        .set_json(json!({ "name": "bug", "color": "#000000" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let req = test::TestRequest::post()
+        .uri("/api/labels")
+        .append_header(auth.clone())
+        .set_json(json!({ "name": "bad colour", "color": "red" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/labels/{}", label_ids[2]))
+        .append_header(auth.clone())
+        .set_json(json!({ "name": "p0", "color": "#b60205" }))
+        .to_request();
+    let renamed: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(renamed["name"], json!("p0"));
+
+    // Other users can neither see nor change the labels.
+    let req = test::TestRequest::get()
+        .uri("/api/labels")
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", other.token)))
+        .to_request();
+    let labels: Vec<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(labels.is_empty());
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/labels/{}", label_ids[0]))
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", other.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    // 2. Attach labels: A has bug+frontend, B has bug, C has none.
+    let mut task_ids = Vec::new();
+    for title in ["Label A", "Label B", "Label C"] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(auth.clone())
+            .set_json(json!({ "title": title, "status": "todo" }))
+            .to_request();
+        let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+        assert!(task.labels.is_empty());
+        task_ids.push(task.id);
+    }
+    let attach = |task_id: uuid::Uuid, label_id: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/tasks/{}/labels/{}", task_id, label_id))
+            .append_header(auth.clone())
+            .to_request()
+    };
+    test::call_service(&app, attach(task_ids[0], &label_ids[0])).await;
+    let resp = test::call_service(&app, attach(task_ids[0], &label_ids[1])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let labelled: Task = test::read_body_json(resp).await;
+    let names: Vec<&str> = labelled.labels.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, vec!["bug", "frontend"]);
+    test::call_service(&app, attach(task_ids[1], &label_ids[0])).await;
+
+    // 3. Filter in any-of and all-of modes.
+    let titles_for = |query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/tasks?sort=created_at&order=asc&{}", query))
+            .append_header(auth.clone())
+            .to_request()
+    };
+    let page: Page<Task> =
+        test::read_body_json(test::call_service(&app, titles_for("labels=bug,frontend")).await)
+            .await;
+    let titles: Vec<&str> = page.items.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Label A", "Label B"]);
+    let page: Page<Task> = test::read_body_json(
+        test::call_service(&app, titles_for("labels=bug,frontend&labels_match=all")).await,
+    )
+    .await;
+    let titles: Vec<&str> = page.items.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Label A"]);
+    assert_eq!(page.items[0].labels.len(), 2);
+
+    // 4. Detach, and deleting a label removes it from tasks.
+    let req = test::TestRequest::delete()
+        .uri(&format!(
+            "/api/tasks/{}/labels/{}",
+            task_ids[0], label_ids[1]
+        ))
+        .append_header(auth.clone())
+        .to_request();
+    let detached: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(detached.labels.len(), 1);
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/labels/{}", label_ids[0]))
+        .append_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", task_ids[0]))
+        .append_header(auth.clone())
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(task.labels.is_empty());
+
+    // 5. Labels cannot be attached to other users' tasks or with other users' labels.
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&format!(
+                "/api/tasks/{}/labels/{}",
+                task_ids[2], label_ids[1]
+            ))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", other.token)))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, other_email).await;
+}
+
+#[actix_rt::test]
+async fn test_threaded_task_comments() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "comments_owner@example.com";
+    let assignee_email = "comments_assignee@example.com";
+    let outsider_email = "comments_outsider@example.com";
+    for email in [owner_email, assignee_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "comments_owner", "PassComm123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee =
+        register_and_login_user(&app, assignee_email, "comments_assignee", "PassComm456!")
+            .await
+            .expect("Failed to register assignee");
+    let outsider =
+        register_and_login_user(&app, outsider_email, "comments_outsider", "PassComm789!")
+            .await
+            .expect("Failed to register outsider");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "title": "Discuss me", "status": "todo" }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(task.comment_count, 0);
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/assign", task.id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "assignee_id": assignee.id }))
+        .to_request();
+    test::call_service(&app, req).await;
+    let comments_uri = format!("/api/tasks/{}/comments", task.id);
+
+    // 1. Owner comments, assignee replies; outsiders cannot see the thread.
+    let req = test::TestRequest::post()
+        .uri(&comments_uri)
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "body": "Should this be split up?" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let root: serde_json::Value = test::read_body_json(resp).await;
+    assert!(root["parent_id"].is_null());
+    assert!(root["edited_at"].is_null());
+
+    let req = test::TestRequest::post()
+        .uri(&comments_uri)
+        .append_header(bearer(&assignee.token))
+        .set_json(json!({ "body": "Yes, into two tasks", "parent_id": root["id"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let reply: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(reply["parent_id"], root["id"]);
+
+    let req = test::TestRequest::post()
+        .uri(&comments_uri)
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "body": "Orphan", "parent_id": uuid::Uuid::new_v4() }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&comments_uri)
+        .append_header(bearer(&outsider.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let fetched: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(fetched.comment_count, 2);
+
+    // 2. Only the author can edit, and edits are stamped.
+    let reply_uri = format!("{}/{}", comments_uri, reply["id"].as_str().unwrap());
+    let req = test::TestRequest::put()
+        .uri(&reply_uri)
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "body": "Hijacked" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::put()
+        .uri(&reply_uri)
+        .append_header(bearer(&assignee.token))
+        .set_json(json!({ "body": "Yes, into three tasks" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let edited: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(edited["body"], json!("Yes, into three tasks"));
+    assert!(!edited["edited_at"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri(&comments_uri)
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let thread: Vec<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(thread.len(), 2);
+    assert_eq!(thread[0]["id"], root["id"]);
+    assert_eq!(thread[1]["body"], json!("Yes, into three tasks"));
+
+    // 3. The assignee cannot delete the owner's comment; the owner can, and replies go with it.
+    let root_uri = format!("{}/{}", comments_uri, root["id"].as_str().unwrap());
+    let req = test::TestRequest::delete()
+        .uri(&root_uri)
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::delete()
+        .uri(&root_uri)
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let fetched: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(fetched.comment_count, 0);
+
+    for email in [owner_email, assignee_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+/// Builds a `multipart/form-data` body with a single `file` field.
+fn multipart_file(filename: &str, content_type: &str, contents: &[u8]) -> (String, Vec<u8>) {
+    let boundary = "taskforge-test-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\nContent-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(contents);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+#[actix_rt::test]
+async fn test_task_attachments_quotas_and_ranges() {
+    use sha2::{Digest, Sha256};
+    use taskforge::{config::StorageConfig, storage};
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let root = std::env::temp_dir().join(format!("taskforge-attachments-{}", uuid::Uuid::new_v4()));
+    let mut config = Config::from_env();
+    config.max_attachment_size = 64;
+    config.attachment_quota_per_user = 100;
+    config.storage = StorageConfig::Local { root: root.clone() };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage::from_config(&config.storage)))
+            .app_data(web::Data::new(config))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "attach_owner@example.com";
+    let outsider_email = "attach_outsider@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, outsider_email).await;
+    let owner = register_and_login_user(&app, owner_email, "attach_owner", "PassAttach123!")
+        .await
+        .expect("Failed to register owner");
+    let outsider =
+        register_and_login_user(&app, outsider_email, "attach_outsider", "PassAttach456!")
+            .await
+            .expect("Failed to register outsider");
+    let auth = (header::AUTHORIZATION, format!("Bearer {}", owner.token));
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth.clone())
+        .set_json(json!({ "title": "With files", "status": "todo" }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    let attachments_uri = format!("/api/tasks/{}/attachments", task.id);
+    let upload = |filename: &str, contents: &[u8]| {
+        let (content_type, body) = multipart_file(filename, "text/plain", contents);
+        test::TestRequest::post()
+            .uri(&attachments_uri)
+            .append_header(auth.clone())
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(body)
+            .to_request()
+    };
+
+    // 1. Upload: metadata is recorded and the path is stripped from the name.
+    let contents = b"0123456789abcdefghijklmnopqrstuvwxyzABCD";
+    let resp = test::call_service(&app, upload("../notes/todo.txt", contents)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let attachment: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(attachment["filename"], json!("todo.txt"));
+    assert_eq!(attachment["content_type"], json!("text/plain"));
+    assert_eq!(attachment["size_bytes"], json!(40));
+    assert_eq!(
+        attachment["sha256"],
+        json!(hex::encode(Sha256::digest(contents)))
+    );
+    assert!(attachment.get("storage_key").is_none());
+    let attachment_id = attachment["id"].as_str().unwrap().to_string();
+    let object_path = root.join("attachments").join(&attachment_id);
+    assert!(object_path.exists());
+
+    // 2. Per-file limit (64 bytes) and per-user quota (100 bytes).
+    let resp = test::call_service(&app, upload("big.txt", &[b'x'; 65])).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::PAYLOAD_TOO_LARGE
+    );
+    let resp = test::call_service(&app, upload("second.txt", &[b'y'; 40])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let resp = test::call_service(&app, upload("third.txt", &[b'z'; 40])).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::PAYLOAD_TOO_LARGE
+    );
+    let req = test::TestRequest::get()
+        .uri(&attachments_uri)
+        .append_header(auth.clone())
+        .to_request();
+    let listed: Vec<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(listed.len(), 2);
+
+    // 3. Downloads, whole and ranged.
+    let download_uri = format!("{}/{}", attachments_uri, attachment_id);
+    let download = |range: Option<&str>| {
+        let mut req = test::TestRequest::get()
+            .uri(&download_uri)
+            .append_header(auth.clone());
+        if let Some(range) = range {
+            req = req.insert_header((header::RANGE, range.to_string()));
+        }
+        req.to_request()
+    };
+    let resp = test::call_service(&app, download(None)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    assert_eq!(resp.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+    assert!(resp
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .contains("todo.txt"));
+    assert_eq!(test::read_body(resp).await.as_ref(), contents);
+
+    let resp = test::call_service(&app, download(Some("bytes=10-19"))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_RANGE).unwrap(),
+        "bytes 10-19/40"
+    );
+    assert_eq!(test::read_body(resp).await.as_ref(), b"abcdefghij");
+
+    let resp = test::call_service(&app, download(Some("bytes=-4"))).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(test::read_body(resp).await.as_ref(), b"ABCD");
+
+    let resp = test::call_service(&app, download(Some("bytes=40-"))).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::RANGE_NOT_SATISFIABLE
+    );
+    assert_eq!(
+        resp.headers().get(header::CONTENT_RANGE).unwrap(),
+        "bytes */40"
+    );
+
+    // 4. Outsiders cannot see attachments; deleting removes the stored object.
+    let req = test::TestRequest::get()
+        .uri(&download_uri)
+        .append_header((header::AUTHORIZATION, format!("Bearer {}", outsider.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::delete()
+        .uri(&download_uri)
+        .append_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+    assert!(!object_path.exists());
+    let resp = test::call_service(&app, download(None)).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    // The freed space is available again.
+    let resp = test::call_service(&app, upload("third.txt", &[b'z'; 40])).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, outsider_email).await;
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[actix_rt::test]
+async fn test_task_activity_log() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "activity_owner@example.com";
+    let assignee_email = "activity_assignee@example.com";
+    let outsider_email = "activity_outsider@example.com";
+    for email in [owner_email, assignee_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "activity_owner", "PassAct123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee =
+        register_and_login_user(&app, assignee_email, "activity_assignee", "PassAct456!")
+            .await
+            .expect("Failed to register assignee");
+    let outsider =
+        register_and_login_user(&app, outsider_email, "activity_outsider", "PassAct789!")
+            .await
+            .expect("Failed to register outsider");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+
+    // 1. Create, edit and assign a task; a no-op edit is not recorded.
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "title": "Audit me", "status": "todo" }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    for patch in [json!({ "status": "review" }), json!({ "status": "review" })] {
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/tasks/{}", task.id))
+            .append_header(bearer(&owner.token))
+            .set_json(patch)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::OK
+        );
+    }
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/assign", task.id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "assignee_id": assignee.id }))
+        .to_request();
+    test::call_service(&app, req).await;
+    let activity_uri = format!("/api/tasks/{}/activity", task.id);
+
+    // 2. The assignee pages through the log, newest first.
+    let req = test::TestRequest::get()
+        .uri(&format!("{}?limit=2", activity_uri))
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let page: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(page["has_more"], true);
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["event_type"], "assigned");
+    assert_eq!(items[0]["actor_id"], owner.id);
+    assert_eq!(
+        items[0]["changes"],
+        json!({ "assigned_to": { "old": null, "new": assignee.id } })
+    );
+    assert_eq!(items[1]["event_type"], "updated");
+    assert_eq!(
+        items[1]["changes"],
+        json!({ "status": { "old": "todo", "new": "review" } })
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "{}?limit=2&cursor={}",
+            activity_uri,
+            page["next_cursor"].as_str().unwrap()
+        ))
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let page: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(page["has_more"], false);
+    assert!(page["next_cursor"].is_null());
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["event_type"], "created");
+    assert_eq!(items[0]["changes"]["title"]["new"], "Audit me");
+
+    // 3. Outsiders cannot read the log.
+    let req = test::TestRequest::get()
+        .uri(&activity_uri)
+        .append_header(bearer(&outsider.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    // 4. The log survives deletion and stays readable by the former owner only.
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NO_CONTENT
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&activity_uri)
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let page: serde_json::Value = test::read_body_json(resp).await;
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0]["event_type"], "deleted");
+    assert_eq!(items[0]["changes"]["title"]["old"], "Audit me");
+    assert!(items[0]["changes"]["title"]["new"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri(&activity_uri)
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    for email in [owner_email, assignee_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+/// Reads the next JSON text message from a WebSocket, or `None` after `wait`.
+async fn next_ws_json<S>(ws: &mut S, wait: std::time::Duration) -> Option<serde_json::Value>
+where
+    S: futures::Stream<
+            Item = Result<
+                tokio_tungstenite::tungstenite::Message,
+                tokio_tungstenite::tungstenite::Error,
+            >,
+        > + Unpin,
+{
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    loop {
+        match tokio::time::timeout(wait, ws.next()).await.ok()? {
+            Some(Ok(Message::Text(text))) => return serde_json::from_str(&text).ok(),
+            Some(Ok(_)) => continue,
+            _ => return None,
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_websocket_task_updates() {
+    use futures::{SinkExt, StreamExt};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use taskforge::realtime::{run_listener, TaskBroadcaster};
+    use tokio_tungstenite::tungstenite::Message;
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    // A real server for the WebSocket connections, fed by the LISTEN/NOTIFY listener.
+    let broadcaster = Arc::new(TaskBroadcaster::new());
+    let listener_handle = rt::spawn(run_listener(pool.clone(), broadcaster.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    let port = listener.local_addr().unwrap().port();
+    // The server gets its own pool: connections opened on its worker threads stop
+    // working once the workers shut down, and must not be reused by the test.
+    let server_pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+    let server_broadcaster = broadcaster.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(server_pool.clone()))
+            .app_data(web::Data::from(server_broadcaster.clone()))
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            )
+    })
+    .listen(listener)
+    .expect("Failed to listen")
+    .run();
+    let server_handle = server.handle();
+    rt::spawn(server);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let owner_email = "ws_owner@example.com";
+    let outsider_email = "ws_outsider@example.com";
+    for email in [owner_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "ws_owner", "PassWs123!")
+        .await
+        .expect("Failed to register owner");
+    let outsider = register_and_login_user(&app, outsider_email, "ws_outsider", "PassWs456!")
+        .await
+        .expect("Failed to register outsider");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+    let ws_url = |token: &str| format!("ws://127.0.0.1:{}/api/ws?token={}", port, token);
+
+    // 1. The handshake requires a valid token.
+    match tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/api/ws", port)).await {
+        Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), 401)
+        }
+        other => panic!(
+            "Expected 401 handshake failure, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+    let (mut owner_ws, _) = tokio_tungstenite::connect_async(ws_url(&owner.token))
+        .await
+        .expect("Owner failed to connect");
+    let (mut outsider_ws, _) = tokio_tungstenite::connect_async(ws_url(&outsider.token))
+        .await
+        .expect("Outsider failed to connect");
+
+    // 2. The owner subscribes to tasks that are done.
+    owner_ws
+        .send(Message::Text(
+            json!({ "type": "subscribe", "filter": { "status": "done" } }).to_string(),
+        ))
+        .await
+        .unwrap();
+    let reply = next_ws_json(&mut owner_ws, Duration::from_secs(5))
+        .await
+        .expect("No subscription reply");
+    assert_eq!(reply["type"], "subscribed");
+    assert_eq!(reply["subscription"]["filters"][0]["status"], "done");
+
+    // 3. Only the matching task is pushed, and only to its owner.
+    for (title, status) in [("Still todo", "todo"), ("Already done", "done")] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "title": title, "status": status }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::CREATED
+        );
+    }
+    let pushed = next_ws_json(&mut owner_ws, Duration::from_secs(5))
+        .await
+        .expect("No task event pushed");
+    assert_eq!(pushed["type"], "task_event");
+    assert_eq!(pushed["event"]["event_type"], "created");
+    assert_eq!(pushed["task"]["title"], "Already done");
+    assert_eq!(pushed["event"]["changes"]["status"]["new"], "done");
+
+    assert!(
+        next_ws_json(&mut outsider_ws, Duration::from_millis(500))
+            .await
+            .is_none(),
+        "Outsider must not receive events for tasks they cannot see"
+    );
+
+    // 4. Invalid client messages are answered with an error.
+    owner_ws
+        .send(Message::Text(json!({ "type": "shout" }).to_string()))
+        .await
+        .unwrap();
+    let reply = next_ws_json(&mut owner_ws, Duration::from_secs(5))
+        .await
+        .expect("No error reply");
+    assert_eq!(reply["type"], "error");
+
+    // 5. Logging out closes the connections opened with the token at the next heartbeat.
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert!(test::call_service(&app, req).await.status().is_success());
+    let close = loop {
+        match tokio::time::timeout(Duration::from_secs(40), owner_ws.next()).await {
+            Ok(Some(Ok(Message::Close(frame)))) => break frame,
+            Ok(Some(Ok(_))) => continue,
+            other => panic!("Expected a close frame, got {:?}", other),
+        }
+    };
+    let close = close.expect("Close frame without a reason");
+    assert_eq!(
+        close.code,
+        tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Policy
+    );
+    assert_eq!(close.reason, "Session revoked");
+
+    let _ = outsider_ws.close(None).await;
+    server_handle.stop(false).await;
+    listener_handle.abort();
+    for email in [owner_email, outsider_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+/// Reads Server-Sent Events frames until one carrying `data` arrives, or `None` after `wait`.
+///
+/// Returns the frame's `id`, `event` and `data` fields.
+async fn next_sse_frame<S>(
+    body: &mut S,
+    buffer: &mut String,
+    wait: std::time::Duration,
+) -> Option<(Option<String>, Option<String>, String)>
+where
+    S: futures::Stream<Item = reqwest::Result<actix_web::web::Bytes>> + Unpin,
+{
+    use futures::StreamExt;
+
+    loop {
+        while let Some(end) = buffer.find("\n\n") {
+            let frame: String = buffer.drain(..end + 2).collect();
+            let (mut id, mut event, mut data) = (None, None, Vec::new());
+            for line in frame.lines() {
+                if let Some(value) = line.strip_prefix("id: ") {
+                    id = Some(value.to_string());
+                } else if let Some(value) = line.strip_prefix("event: ") {
+                    event = Some(value.to_string());
+                } else if let Some(value) = line.strip_prefix("data: ") {
+                    data.push(value);
+                }
+            }
+            if !data.is_empty() {
+                return Some((id, event, data.join("\n")));
+            }
+        }
+        let chunk = tokio::time::timeout(wait, body.next()).await.ok()??.ok()?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+    }
+}
+
+#[actix_rt::test]
+async fn test_task_event_stream_with_resume() {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use taskforge::realtime::{prune_event_log, run_listener, TaskBroadcaster};
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let broadcaster = Arc::new(TaskBroadcaster::new());
+    let listener_handle = rt::spawn(run_listener(pool.clone(), broadcaster.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    let port = listener.local_addr().unwrap().port();
+    // The server gets its own pool: connections opened on its worker threads stop
+    // working once the workers shut down, and must not be reused by the test.
+    let server_pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+    let server_broadcaster = broadcaster.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(server_pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .app_data(web::Data::from(server_broadcaster.clone()))
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            )
+    })
+    .listen(listener)
+    .expect("Failed to listen")
+    .run();
+    let server_handle = server.handle();
+    rt::spawn(server);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+    let owner_email = "sse_owner@example.com";
+    cleanup_user(&pool, owner_email).await;
+    let owner = register_and_login_user(&app, owner_email, "sse_owner", "PassSse123!")
+        .await
+        .expect("Failed to register owner");
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}/api", port);
+    let create_task = |title: &'static str| {
+        let request = client
+            .post(format!("{}/tasks", base))
+            .bearer_auth(&owner.token)
+            .json(&json!({ "title": title, "status": "todo" }));
+        async move {
+            let task: Task = request.send().await.unwrap().json().await.unwrap();
+            task
+        }
+    };
+
+    // 1. Events that happen while disconnected are replayed after Last-Event-ID.
+    let first = create_task("Before the stream").await;
+    let activity: serde_json::Value = client
+        .get(format!("{}/tasks/{}/activity", base, first.id))
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let first_event_id = activity["items"][0]["id"].as_i64().unwrap();
+    let missed = create_task("While disconnected").await;
+
+    let resp = client
+        .get(format!("{}/tasks/events", base))
+        .bearer_auth(&owner.token)
+        .header("Last-Event-ID", first_event_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    assert_eq!(
+        resp.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let mut body = resp.bytes_stream();
+    let mut buffer = String::new();
+
+    let (id, event, data) = next_sse_frame(&mut body, &mut buffer, Duration::from_secs(5))
+        .await
+        .expect("No replayed event");
+    assert!(event.is_none());
+    let message: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(message["type"], "task_event");
+    assert_eq!(message["event"]["event_type"], "created");
+    assert_eq!(message["task"]["id"], missed.id.to_string());
+    let replayed_id: i64 = id.unwrap().parse().unwrap();
+    assert!(replayed_id > first_event_id);
+
+    // 2. Live events follow on the same stream.
+    let live = create_task("While connected").await;
+    let (id, _, data) = next_sse_frame(&mut body, &mut buffer, Duration::from_secs(5))
+        .await
+        .expect("No live event");
+    let message: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(message["task"]["id"], live.id.to_string());
+    assert!(id.unwrap().parse::<i64>().unwrap() > replayed_id);
+    drop(body);
+
+    // 3. Malformed Last-Event-ID values are rejected.
+    let resp = client
+        .get(format!("{}/tasks/events", base))
+        .bearer_auth(&owner.token)
+        .header("Last-Event-ID", "yesterday")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // 4. Once the missed events have been pruned, the client is told to reset.
+    prune_event_log(&pool, 0).await.unwrap();
+    let resp = client
+        .get(format!("{}/tasks/events", base))
+        .bearer_auth(&owner.token)
+        .header("Last-Event-ID", first_event_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    let mut body = resp.bytes_stream();
+    let (id, event, _) = next_sse_frame(&mut body, &mut buffer, Duration::from_secs(5))
+        .await
+        .expect("No reset event");
+    assert_eq!(event.as_deref(), Some("reset"));
+    assert!(id.unwrap().parse::<i64>().unwrap() >= replayed_id);
+    drop(body);
+
+    server_handle.stop(false).await;
+    listener_handle.abort();
+    cleanup_user(&pool, owner_email).await;
+}
+
+/// A request received by the local webhook receiver: its headers and raw body.
+type ReceivedHook = (actix_web::http::header::HeaderMap, web::Bytes);
+
+#[actix_rt::test]
+async fn test_webhooks_signed_deliveries_retries_and_disabling() {
+    use std::sync::atomic::{AtomicU16, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use taskforge::webhooks::{
+        deliver_due, http_client, sign, DISABLE_AFTER_FAILURES, MAX_DELIVERY_ATTEMPTS,
+    };
+
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    // The receiver runs on this machine, so the app has to allow private targets.
+    let app_with = |allow_private_targets: bool| {
+        let mut config = Config::from_env();
+        config.webhook_allow_private_targets = allow_private_targets;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(config))
+                .wrap(Logger::default())
+                .service(health::health)
+                .service(
+                    web::scope("/api")
+                        .wrap(taskforge::auth::AuthMiddleware)
+                        .configure(routes::config),
+                ),
+        )
+    };
+    let app = app_with(true).await;
+    let restricted_app = app_with(false).await;
+
+    // A local receiver that records every request and answers with `reply_status`.
+    let received: Arc<Mutex<Vec<ReceivedHook>>> = Arc::new(Mutex::new(Vec::new()));
+    let reply_status = Arc::new(AtomicU16::new(200));
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    let port = listener.local_addr().unwrap().port();
+    let (server_received, server_status) = (received.clone(), reply_status.clone());
+    let server = HttpServer::new(move || {
+        let received = server_received.clone();
+        let status = server_status.clone();
+        App::new().default_service(web::to(
+            move |req: actix_web::HttpRequest, body: web::Bytes| {
+                let (received, status) = (received.clone(), status.clone());
+                async move {
+                    received.lock().unwrap().push((req.headers().clone(), body));
+                    let status =
+                        actix_web::http::StatusCode::from_u16(status.load(Ordering::SeqCst))
+                            .unwrap();
+                    actix_web::HttpResponse::build(status).finish()
+                }
+            },
+        ))
+    })
+    .workers(1)
+    .listen(listener)
+    .expect("Failed to listen")
+    .run();
+    let server_handle = server.handle();
+    rt::spawn(server);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let receiver_url = format!("http://127.0.0.1:{}/hooks/taskforge", port);
+    let client = http_client(true);
+
+    let owner_email = "hook_owner@example.com";
+    let assignee_email = "hook_assignee@example.com";
+    for email in [owner_email, assignee_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "hook_owner", "PassHook123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee = register_and_login_user(&app, assignee_email, "hook_assignee", "PassHook456!")
+        .await
+        .expect("Failed to register assignee");
+    let auth = |user: &TestUser| (header::AUTHORIZATION, format!("Bearer {}", user.token));
+    let force_due = |webhook_id: String| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query(
+                "UPDATE webhook_deliveries SET next_attempt_at = now() WHERE webhook_id = $1::uuid",
+            )
+            .bind(webhook_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    };
+
+    // 1. Registration validates the URL and returns the secret only once.
+    let req = test::TestRequest::post()
+        .uri("/api/webhooks")
+        .append_header(auth(&owner))
+        .set_json(json!({ "url": "ftp://example.com", "event_types": ["created"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 422);
+
+    // Private addresses are refused unless the configuration allows them.
+    let register = || {
+        test::TestRequest::post()
+            .uri("/api/webhooks")
+            .append_header(auth(&owner))
+            .set_json(json!({ "url": receiver_url, "event_types": ["created", "assigned"] }))
+            .to_request()
+    };
+    assert_eq!(
+        test::call_service(&restricted_app, register())
+            .await
+            .status(),
+        422
+    );
+
+    let resp = test::call_service(&app, register()).await;
+    assert_eq!(resp.status(), 201);
+    let webhook: serde_json::Value = test::read_body_json(resp).await;
+    let webhook_id = webhook["id"].as_str().unwrap().to_string();
+    let secret = webhook["secret"].as_str().unwrap().to_string();
+    assert_eq!(secret.len(), 64);
+    assert_eq!(webhook["active"], true);
+
+    let req = test::TestRequest::get()
+        .uri("/api/webhooks")
+        .append_header(auth(&owner))
+        .to_request();
+    let webhooks: Vec<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(webhooks.len(), 1);
+    assert!(webhooks[0].get("secret").is_none());
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/webhooks/{}", webhook_id))
+        .append_header(auth(&assignee))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    // 2. A subscribed event is delivered with a verifiable signature.
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(auth(&owner))
+        .set_json(json!({ "title": "Run the pipeline", "status": "todo" }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+    {
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (headers, body) = &received[0];
+        assert_eq!(
+            headers
+                .get("X-TaskForge-Signature")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            sign(&secret, body)
+        );
+        assert_eq!(headers.get("X-TaskForge-Event").unwrap(), "created");
+        assert!(headers.get("X-TaskForge-Delivery").is_some());
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["event"]["task_id"], task.id.to_string());
+        assert_eq!(payload["task"]["title"], "Run the pipeline");
+    }
+
+    // Event types the webhook does not subscribe to are not delivered.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header(auth(&owner))
+        .set_json(json!({ "priority": "high" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+
+    // 3. A failed attempt is logged and retried after a backoff.
+    reply_status.store(500, Ordering::SeqCst);
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/assign", task.id))
+        .append_header(auth(&owner))
+        .set_json(json!({ "assignee_id": assignee.id }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/webhooks/{}/deliveries", webhook_id))
+        .append_header(auth(&owner))
+        .to_request();
+    let log: Page<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(log.items.len(), 2);
+    let retried = &log.items[0];
+    assert_eq!(retried["event_type"], "assigned");
+    assert_eq!(retried["status"], "pending");
+    assert_eq!(retried["attempts"], 1);
+    assert_eq!(retried["response_status"], 500);
+    assert!(retried["last_error"].as_str().unwrap().contains("500"));
+    assert_eq!(log.items[1]["status"], "succeeded");
+
+    // A client that refuses private targets does not connect to the receiver, and
+    // does not log why.
+    reply_status.store(200, Ordering::SeqCst);
+    force_due(webhook_id.clone()).await;
+    assert_eq!(deliver_due(&pool, &http_client(false)).await.unwrap(), 1);
+    assert_eq!(received.lock().unwrap().len(), 2);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/webhooks/{}/deliveries", webhook_id))
+        .append_header(auth(&owner))
+        .to_request();
+    let log: Page<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(log.items[0]["attempts"], 2);
+    assert!(log.items[0]["response_status"].is_null());
+    assert_eq!(
+        log.items[0]["last_error"],
+        "Could not connect to the receiver"
+    );
+
+    force_due(webhook_id.clone()).await;
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/webhooks/{}/deliveries?status=succeeded",
+            webhook_id
+        ))
+        .append_header(auth(&owner))
+        .to_request();
+    let log: Page<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(log.items.len(), 2);
+    assert_eq!(log.items[0]["attempts"], 3);
+    let first_delivery_id = log.items[1]["id"].as_i64().unwrap();
+
+    // 4. Manual redelivery sends the same payload as a new delivery.
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/webhooks/{}/deliveries/{}/redeliver",
+            webhook_id, first_delivery_id
+        ))
+        .append_header(auth(&owner))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+    let redelivery: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(redelivery["redelivery_of"], first_delivery_id);
+    assert_eq!(redelivery["status"], "pending");
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+    {
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 4);
+        assert_eq!(received[3].1, received[0].1);
+        assert_ne!(
+            received[3].0.get("X-TaskForge-Delivery"),
+            received[0].0.get("X-TaskForge-Delivery")
+        );
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/webhooks/{}/deliveries/{}/redeliver",
+            webhook_id,
+            i64::MAX
+        ))
+        .append_header(auth(&owner))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    // 5. Deliveries that fail every attempt are marked failed, and repeated failures
+    // disable the webhook.
+    let redeliver = || {
+        test::TestRequest::post()
+            .uri(&format!(
+                "/api/webhooks/{}/deliveries/{}/redeliver",
+                webhook_id, first_delivery_id
+            ))
+            .append_header(auth(&owner))
+            .to_request()
+    };
+    reply_status.store(503, Ordering::SeqCst);
+    for _ in 0..2 {
+        assert_eq!(test::call_service(&app, redeliver()).await.status(), 202);
+    }
+    for _ in 0..MAX_DELIVERY_ATTEMPTS {
+        force_due(webhook_id.clone()).await;
+        assert_eq!(deliver_due(&pool, &client).await.unwrap(), 2);
+    }
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/webhooks/{}/deliveries?status=failed",
+            webhook_id
+        ))
+        .append_header(auth(&owner))
+        .to_request();
+    let failed: Page<serde_json::Value> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(failed.items.len(), 2);
+    assert_eq!(failed.items[0]["attempts"], MAX_DELIVERY_ATTEMPTS);
+    force_due(webhook_id.clone()).await;
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+
+    for _ in 0..2 {
+        assert_eq!(test::call_service(&app, redeliver()).await.status(), 202);
+    }
+    while deliver_due(&pool, &client).await.unwrap() > 0 {
+        force_due(webhook_id.clone()).await;
+    }
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/webhooks/{}", webhook_id))
+        .append_header(auth(&owner))
+        .to_request();
+    let disabled: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(disabled["active"], false);
+    assert!(!disabled["disabled_at"].is_null());
+    assert_eq!(disabled["consecutive_failures"], DISABLE_AFTER_FAILURES);
+
+    // Pending deliveries wait while the webhook is disabled.
+    force_due(webhook_id.clone()).await;
+    assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+    assert_eq!(test::call_service(&app, redeliver()).await.status(), 409);
+
+    // Re-enabling resumes the pending deliveries.
+    reply_status.store(200, Ordering::SeqCst);
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/webhooks/{}", webhook_id))
+        .append_header(auth(&owner))
+        .set_json(
+            json!({ "url": receiver_url, "event_types": ["created", "assigned"], "active": true }),
+        )
+        .to_request();
+    let enabled: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(enabled["active"], true);
+    assert_eq!(enabled["consecutive_failures"], 0);
+    assert!(enabled["disabled_at"].is_null());
+    force_due(webhook_id.clone()).await;
+    assert!(deliver_due(&pool, &client).await.unwrap() > 0);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/webhooks/{}", webhook_id))
+        .append_header(auth(&owner))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    server_handle.stop(false).await;
+    for email in [owner_email, assignee_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+#[actix_rt::test]
+async fn test_soft_delete_trash_restore_and_purge() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "trash_owner@example.com";
+    let assignee_email = "trash_assignee@example.com";
+    for email in [owner_email, assignee_email] {
+        cleanup_user(&pool, email).await;
+    }
+    let owner = register_and_login_user(&app, owner_email, "trash_owner", "PassTrash123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee = register_and_login_user(&app, assignee_email, "trash_assignee", "PassTrash456!")
+        .await
+        .expect("Failed to register assignee");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+
+    // 1. Create and assign two tasks, then delete both.
+    let mut tasks = Vec::new();
+    for title in ["Trash me first", "Trash me second"] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "title": title, "status": "todo" }))
+            .to_request();
+        let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/tasks/{}/assign", task.id))
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "assignee_id": assignee.id }))
+            .to_request();
+        test::call_service(&app, req).await;
+        tasks.push(task);
+    }
+    for task in &tasks {
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/tasks/{}", task.id))
+            .append_header(bearer(&owner.token))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::NO_CONTENT
+        );
+    }
+
+    // 2. Trashed tasks are hidden from lookups, listings and updates.
+    for token in [&owner.token, &assignee.token] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/tasks/{}", tasks[0].id))
+            .append_header(bearer(token))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
+        let req = test::TestRequest::get()
+            .uri("/api/tasks")
+            .append_header(bearer(token))
+            .to_request();
+        let page: serde_json::Value =
+            test::read_body_json(test::call_service(&app, req).await).await;
+        assert!(page["items"].as_array().unwrap().is_empty());
+    }
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", tasks[0].id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "status": "done" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    // 3. The owner's trash lists them, most recently deleted first; the assignee's is empty.
+    let req = test::TestRequest::get()
+        .uri("/api/tasks/trash?limit=1")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let page: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(page["has_more"], true);
+    assert_eq!(page["items"][0]["id"], tasks[1].id.to_string());
+    assert!(page["items"][0]["deleted_at"].is_string());
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/tasks/trash?limit=1&cursor={}",
+            page["next_cursor"].as_str().unwrap()
+        ))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let page: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(page["has_more"], false);
+    assert_eq!(page["items"][0]["id"], tasks[0].id.to_string());
+
+    let req = test::TestRequest::get()
+        .uri("/api/tasks/trash")
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let page: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    // 4. Only the owner can restore, and the task comes back to its assignee too.
+    let restore_uri = format!("/api/tasks/{}/restore", tasks[0].id);
+    let req = test::TestRequest::post()
+        .uri(&restore_uri)
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+    let req = test::TestRequest::post()
+        .uri(&restore_uri)
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    assert!(resp.headers().contains_key(header::ETAG));
+    let restored: Task = test::read_body_json(resp).await;
+    assert!(restored.deleted_at.is_none());
+    assert_eq!(restored.assigned_to, Some(assignee.id));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", tasks[0].id))
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+
+    // Restoring a task that is not in the trash is a 404.
+    let req = test::TestRequest::post()
+        .uri(&restore_uri)
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+
+    // 5. The activity log records the deletion and the restore.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/activity", tasks[0].id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let page: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items[0]["event_type"], "restored");
+    assert_eq!(items[0]["changes"]["title"]["new"], "Trash me first");
+    assert_eq!(items[1]["event_type"], "deleted");
+
+    // 6. The purge only removes tasks trashed longer than the retention period.
+    sqlx::query("UPDATE tasks SET deleted_at = now() - interval '31 days' WHERE id = $1")
+        .bind(tasks[1].id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    taskforge::trash::purge_trash(&pool, 30)
+        .await
+        .expect("Failed to purge trash");
+    let (remaining,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tasks WHERE id = ANY($1)")
+        .bind(vec![tasks[0].id, tasks[1].id])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 1);
+
+    for email in [owner_email, assignee_email] {
+        cleanup_user(&pool, email).await;
+    }
+}
+
+#[actix_rt::test]
+async fn test_archiving_and_auto_archive() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "archive_owner@example.com";
+    cleanup_user(&pool, owner_email).await;
+    let owner = register_and_login_user(&app, owner_email, "archive_owner", "PassArch123!")
+        .await
+        .expect("Failed to register owner");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+    let list_titles = |include_archived: bool| {
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/tasks?sort=created_at&order=asc&include_archived={}",
+                include_archived
+            ))
+            .append_header(bearer(&owner.token))
+            .to_request();
+        let app = &app;
+        async move {
+            let page: serde_json::Value =
+                test::read_body_json(test::call_service(app, req).await).await;
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["title"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // 1. Completed tasks record when they were completed.
+    let mut tasks = Vec::new();
+    for (title, status) in [("Shipped", "done"), ("Someday", "todo")] {
+        let req = test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "title": title, "status": status }))
+            .to_request();
+        let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+        tasks.push(task);
+    }
+    let (done, todo) = (&tasks[0], &tasks[1]);
+    assert!(done.completed_at.is_some());
+    assert!(todo.completed_at.is_none());
+    assert!(done.archived_at.is_none());
+
+    // 2. Archiving hides a task from listings unless `include_archived=true`.
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/archive", todo.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let archived: Task = test::read_body_json(resp).await;
+    assert!(archived.archived_at.is_some());
+    assert_eq!(archived.status, TaskStatus::new("todo"));
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/archive", todo.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::CONFLICT
+    );
+    assert_eq!(list_titles(false).await, vec!["Shipped"]);
+    assert_eq!(list_titles(true).await, vec!["Shipped", "Someday"]);
+
+    // Archived tasks can still be fetched directly.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", todo.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+
+    // 3. Unarchiving lists it again.
+    for expected in [
+        actix_web::http::StatusCode::OK,
+        actix_web::http::StatusCode::CONFLICT,
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/tasks/{}/unarchive", todo.id))
+            .append_header(bearer(&owner.token))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), expected);
+    }
+    assert_eq!(list_titles(false).await, vec!["Shipped", "Someday"]);
+
+    // 4. Tasks done for longer than the setting are archived automatically, with no actor.
+    sqlx::query("UPDATE tasks SET completed_at = now() - interval '15 days' WHERE id = $1")
+        .bind(done.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    taskforge::archive::archive_completed_tasks(&pool, 14)
+        .await
+        .expect("Failed to auto-archive");
+    assert_eq!(list_titles(false).await, vec!["Someday"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/activity", done.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let page: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(page["items"][0]["event_type"], "archived");
+    assert!(page["items"][0]["actor_id"].is_null());
+    assert!(page["items"][0]["changes"]["archived_at"]["old"].is_null());
+
+    // 5. A task unarchived by its owner stays unarchived until it is completed again.
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/unarchive", done.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    test::call_service(&app, req).await;
+    taskforge::archive::archive_completed_tasks(&pool, 14)
+        .await
+        .expect("Failed to auto-archive");
+    assert_eq!(list_titles(false).await, vec!["Shipped", "Someday"]);
+
+    // Reopening a task clears `completed_at`.
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/tasks/{}/status", done.id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "status": "in_progress" }))
+        .to_request();
+    let reopened: Task = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(reopened.completed_at.is_none());
+
+    cleanup_user(&pool, owner_email).await;
+}
+
+#[actix_rt::test]
+async fn test_status_workflow_transitions() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "workflow_owner@example.com";
+    let assignee_email = "workflow_assignee@example.com";
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+    let owner = register_and_login_user(&app, owner_email, "workflow_owner", "PassFlow123!")
+        .await
+        .expect("Failed to register owner");
+    let assignee =
+        register_and_login_user(&app, assignee_email, "workflow_assignee", "PassFlow123!")
+            .await
+            .expect("Failed to register assignee");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+    let set_status = |token: &str, id: uuid::Uuid, status: &str| {
+        test::TestRequest::put()
+            .uri(&format!("/api/tasks/{}/status", id))
+            .append_header(bearer(token))
+            .set_json(json!({ "status": status }))
+            .to_request()
+    };
+
+    // 1. Without a workflow, every status change is allowed.
+    let req = test::TestRequest::get()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let workflow: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(workflow["custom"], false);
+    assert_eq!(workflow["transitions"].as_array().unwrap().len(), 12);
+
+    // 2. Invalid workflows are rejected.
+    let req = test::TestRequest::put()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "transitions": [
+            { "from": "todo", "to": "done" },
+            { "from": "todo", "to": "done" }
+        ] }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let req = test::TestRequest::put()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "transitions": [
+            { "from": "todo", "to": "in_progress", "requires_assignee": true },
+            { "from": "in_progress", "to": "review" },
+            { "from": "review", "to": "done", "requires_reviewer": true },
+            { "from": "review", "to": "in_progress" }
+        ] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let workflow: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(workflow["custom"], true);
+    assert_eq!(workflow["transitions"].as_array().unwrap().len(), 4);
+
+    let req = test::TestRequest::post()
+        .uri("/api/tasks")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "title": "Reviewed work", "status": "todo" }))
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&app, req).await).await;
+
+    // 3. Transitions the workflow does not list are rejected with a descriptive error.
+    let resp = test::call_service(&app, set_status(&owner.token, task.id, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("from todo to done"), "{}", error);
+    assert!(error.ends_with("can move to in_progress"), "{}", error);
+
+    // 4. `requires_assignee` blocks the change until the task is assigned.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/transitions", task.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let transitions: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(transitions.as_array().unwrap().len(), 1);
+    assert_eq!(transitions[0]["to"], "in_progress");
+    assert_eq!(transitions[0]["allowed"], false);
+    assert!(transitions[0]["reason"]
+        .as_str()
+        .unwrap()
+        .contains("must be assigned"));
+
+    let resp = test::call_service(&app, set_status(&owner.token, task.id, "in_progress")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    // A patch that assigns the task and moves it at once is allowed.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/tasks/{}", task.id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "assigned_to": assignee.id, "status": "in_progress" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    let resp = test::call_service(&app, set_status(&assignee.token, task.id, "review")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // 5. `requires_reviewer` lets only the owner make the change.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}/transitions", task.id))
+        .append_header(bearer(&assignee.token))
+        .to_request();
+    let transitions: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    let to_done = transitions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["to"] == "done")
+        .unwrap();
+    assert_eq!(to_done["requires_reviewer"], true);
+    assert_eq!(to_done["allowed"], false);
+
+    let resp = test::call_service(&app, set_status(&assignee.token, task.id, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    // A change is checked against the status it replaces, even if another change
+    // commits while it waits for the task: in_progress cannot move to done.
+    let mut other_change = pool.begin().await.unwrap();
+    sqlx::query("UPDATE tasks SET status = 'in_progress' WHERE id = $1")
+        .bind(task.id)
+        .execute(&mut *other_change)
+        .await
+        .unwrap();
+    let (resp, _) = futures::join!(
+        test::call_service(&app, set_status(&owner.token, task.id, "done")),
+        async {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            other_change.commit().await.unwrap();
+        }
+    );
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+    let resp = test::call_service(&app, set_status(&assignee.token, task.id, "review")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // Bulk updates follow the same rules.
+    let req = test::TestRequest::post()
+        .uri("/api/tasks/bulk")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "mode": "partial", "operations": [
+            { "op": "update", "id": task.id, "status": "todo" }
+        ] }))
+        .to_request();
+    let bulk: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(bulk["results"][0]["status"], 409);
+
+    let resp = test::call_service(&app, set_status(&owner.token, task.id, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    // Nothing leaves `done` in this workflow.
+    let resp = test::call_service(&app, set_status(&owner.token, task.id, "todo")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    // 6. Resetting the workflow allows every change again.
+    let req = test::TestRequest::delete()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::NO_CONTENT
+    );
+    let resp = test::call_service(&app, set_status(&owner.token, task.id, "todo")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+    cleanup_user(&pool, owner_email).await;
+    cleanup_user(&pool, assignee_email).await;
+}
+
+#[actix_rt::test]
+async fn test_custom_task_statuses() {
+    dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(Config::from_env()))
+            .wrap(Logger::default())
+            .service(health::health)
+            .service(
+                web::scope("/api")
+                    .wrap(taskforge::auth::AuthMiddleware)
+                    .configure(routes::config),
+            ),
+    )
+    .await;
+
+    let owner_email = "statuses_owner@example.com";
+    cleanup_user(&pool, owner_email).await;
+    let owner = register_and_login_user(&app, owner_email, "statuses_owner", "PassStat123!")
+        .await
+        .expect("Failed to register owner");
+    let bearer = |token: &str| (header::AUTHORIZATION, format!("Bearer {}", token));
+    let create_task = |title: &str, status: &str| {
+        test::TestRequest::post()
+            .uri("/api/tasks")
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "title": title, "status": status }))
+            .to_request()
+    };
+    let set_status = |id: uuid::Uuid, status: &str| {
+        test::TestRequest::put()
+            .uri(&format!("/api/tasks/{}/status", id))
+            .append_header(bearer(&owner.token))
+            .set_json(json!({ "status": status }))
+            .to_request()
+    };
+
+    // 1. Every user starts with the four default statuses.
+    let req = test::TestRequest::get()
+        .uri("/api/statuses")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let statuses: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(
+        statuses,
+        json!([
+            { "name": "todo", "category": "todo", "sort_order": 0 },
+            { "name": "in_progress", "category": "active", "sort_order": 1 },
+            { "name": "review", "category": "active", "sort_order": 2 },
+            { "name": "done", "category": "done", "sort_order": 3 }
+        ])
+    );
+
+    // 2. New statuses go last unless placed, and names are unique and validated.
+    let req = test::TestRequest::post()
+        .uri("/api/statuses")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "name": "qa", "category": "active" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let qa: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(qa["sort_order"], 4);
+
+    for (body, expected) in [
+        (
+            json!({ "name": "qa", "category": "active" }),
+            actix_web::http::StatusCode::CONFLICT,
+        ),
+        (
+            json!({ "name": "Needs QA", "category": "active" }),
+            actix_web::http::StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/statuses")
+            .append_header(bearer(&owner.token))
+            .set_json(body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), expected);
+    }
+
+    // Move `qa` before `done`, and rename `done`.
+    for (name, body) in [
+        (
+            "qa",
+            json!({ "name": "qa", "category": "active", "sort_order": 3 }),
+        ),
+        (
+            "done",
+            json!({ "name": "deployed", "category": "done", "sort_order": 4 }),
+        ),
+    ] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/statuses/{}", name))
+            .append_header(bearer(&owner.token))
+            .set_json(body)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            actix_web::http::StatusCode::OK
+        );
+    }
+
+    // 3. Tasks take any of the owner's statuses; unknown names are rejected.
+    let resp = test::call_service(&app, create_task("Check release", "qa")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CREATED);
+    let in_qa: Task = test::read_body_json(resp).await;
+    assert_eq!(in_qa.status, TaskStatus::new("qa"));
+    assert!(in_qa.completed_at.is_none());
+
+    let resp = test::call_service(&app, create_task("Nowhere", "blocked")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    // 4. Default status names keep working after they are renamed.
+    let resp = test::call_service(&app, create_task("Ship it", "todo")).await;
+    let shipped: Task = test::read_body_json(resp).await;
+    let resp = test::call_service(&app, set_status(shipped.id, "done")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let shipped: Task = test::read_body_json(resp).await;
+    assert_eq!(shipped.status, TaskStatus::new("deployed"));
+    assert!(shipped.completed_at.is_some());
+
+    let resp = test::call_service(&app, create_task("Backlog item", "todo")).await;
+    let backlog: Task = test::read_body_json(resp).await;
+
+    // 5. Active statuses require finished blockers, like `in_progress` did.
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/tasks/{}/dependencies", shipped.id))
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "blocker_id": backlog.id }))
+        .to_request();
+    test::call_service(&app, req).await;
+    let resp = test::call_service(&app, set_status(shipped.id, "qa")).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+
+    // 6. `sort=status` follows the statuses' sort order.
+    let req = test::TestRequest::get()
+        .uri("/api/tasks?sort=status&order=asc&limit=1")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let mut titles = Vec::new();
+    let mut page: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    loop {
+        titles.push(page["items"][0]["title"].as_str().unwrap().to_string());
+        let Some(cursor) = page["next_cursor"].as_str() else {
+            break;
+        };
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/tasks?sort=status&order=asc&limit=1&cursor={}",
+                cursor
+            ))
+            .append_header(bearer(&owner.token))
+            .to_request();
+        page = test::read_body_json(test::call_service(&app, req).await).await;
+    }
+    assert_eq!(titles, vec!["Backlog item", "Check release", "Ship it"]);
+
+    // 7. Moving a status into the `done` category completes its tasks.
+    let req = test::TestRequest::put()
+        .uri("/api/statuses/qa")
+        .append_header(bearer(&owner.token))
+        .set_json(json!({ "name": "verified", "category": "done" }))
+        .to_request();
+    let verified: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(verified["sort_order"], 3);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/tasks/{}", in_qa.id))
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let task: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(task["status"], "verified");
+    assert!(!task["completed_at"].is_null());
+
+    // 8. Statuses in use cannot be deleted.
+    let req = test::TestRequest::delete()
+        .uri("/api/statuses/verified")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::CONFLICT
+    );
+    test::call_service(&app, set_status(in_qa.id, "review")).await;
+    for expected in [
+        actix_web::http::StatusCode::NO_CONTENT,
+        actix_web::http::StatusCode::NOT_FOUND,
+    ] {
+        let req = test::TestRequest::delete()
+            .uri("/api/statuses/verified")
+            .append_header(bearer(&owner.token))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), expected);
+    }
+
+    // 9. Neither are statuses the workflow uses, which stays as it was.
+    let workflow = json!({ "transitions": [
+        { "from": "todo", "to": "in_progress" },
+        { "from": "in_progress", "to": "review" }
+    ] });
+    let req = test::TestRequest::put()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .set_json(&workflow)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::OK
+    );
+    let req = test::TestRequest::delete()
+        .uri("/api/statuses/in_progress")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        actix_web::http::StatusCode::CONFLICT
+    );
+    let req = test::TestRequest::get()
+        .uri("/api/workflow")
+        .append_header(bearer(&owner.token))
+        .to_request();
+    let current: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(current["custom"], true);
+    assert_eq!(current["transitions"].as_array().unwrap().len(), 2);
+
+    cleanup_user(&pool, owner_email).await;
+}